@@ -854,6 +854,13 @@ pub fn CustomClientExample() -> impl IntoView {
             // delegate back out to BrowserClient to send the modified request
             BrowserClient::send(req)
         }
+
+        // Retries wait with the browser's timer, like BrowserClient.
+        fn sleep(
+            duration: std::time::Duration,
+        ) -> impl Future<Output = ()> + Send {
+            <BrowserClient as Client<CustErr>>::sleep(duration)
+        }
     }
 
     // Specify our custom client with `client = `
@@ -10,6 +10,7 @@
 use actix_files::NamedFile;
 use actix_http::header::{HeaderName, HeaderValue, ACCEPT, LOCATION, REFERER};
 use actix_web::{
+    body::{BodyStream, BoxBody, MessageBody},
     dev::{ServiceFactory, ServiceRequest},
     http::header,
     test,
@@ -23,6 +24,7 @@ use hydration_context::SsrSharedContext;
 use leptos::{
     config::LeptosOptions,
     context::{provide_context, use_context},
+    csrf::CsrfToken,
     prelude::expect_context,
     reactive::{computed::ScopedFuture, owner::Owner},
     IntoView,
@@ -34,6 +36,7 @@ use leptos_meta::ServerMetaContext;
 use leptos_router::{
     components::provide_server_redirect,
     location::RequestUrl,
+    sitemap::Sitemap,
     static_routes::{RegenerationFn, ResolvedStaticPath},
     ExpandOptionals, Method, PathSegment, RouteList, RouteListing, SsrMode,
 };
@@ -47,8 +50,10 @@ use std::{
     collections::HashSet,
     fmt::{Debug, Display},
     future::Future,
+    mem,
     ops::{Deref, DerefMut},
     path::Path,
+    pin::Pin,
     sync::Arc,
 };
 
@@ -114,6 +119,10 @@ impl DerefMut for Request {
 }
 
 /// Allows you to override details of the HTTP response like the status code and add Headers/Cookies.
+///
+/// Server functions can also set the status code and headers of their own response in a way
+/// that works with any server integration, by returning a
+/// [`WithResponse`](server_fn::codec::WithResponse).
 #[derive(Debug, Clone, Default)]
 pub struct ResponseOptions(pub Arc<RwLock<ResponseParts>>);
 
@@ -268,6 +277,34 @@ pub fn redirect(path: &str) {
     }
 }
 
+/// Provides a CSRF token via context, so that server functions marked with
+/// `#[server(csrf = true)]` can be called from this page.
+///
+/// This reuses the token from the request’s cookie if there is one, and otherwise generates a
+/// new token and adds a `Set-Cookie` header to the response. It can be called in the app
+/// itself, or in the `additional_context` given to [`LeptosRoutes::leptos_routes_with_context`].
+pub fn provide_csrf_token() {
+    let Some(res) = use_context::<ResponseOptions>() else {
+        return;
+    };
+    let token = use_context::<Request>().and_then(|req| {
+        req.headers()
+            .get_all(header::COOKIE)
+            .filter_map(|cookies| cookies.to_str().ok())
+            .find_map(CsrfToken::from_cookies)
+    });
+    let token = token.unwrap_or_else(|| {
+        let token = CsrfToken::new();
+        res.append_header(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&token.to_set_cookie())
+                .expect("CSRF cookie is a valid header value"),
+        );
+        token
+    });
+    leptos::csrf::provide_csrf_token(token);
+}
+
 /// An Actix [struct@Route](actix_web::Route) that listens for a `POST` request with
 /// Leptos server function arguments in the body, runs the server function if found,
 /// and returns the resulting [HttpResponse].
@@ -405,7 +442,33 @@ pub fn handle_server_fns_with_context(
 
                             // apply status code and headers if user changed them
                             res.extend_response(&res_options);
-                            res.0
+
+                            // batched calls run while the body is streamed, so they need the
+                            // owner too
+                            let is_batch = res
+                                .0
+                                .headers()
+                                .get(header::CONTENT_TYPE)
+                                .is_some_and(|ty| {
+                                    ty == server_fn::batch::BATCH_CONTENT_TYPE
+                                });
+                            match (is_batch, Owner::current()) {
+                                (true, Some(owner)) => {
+                                    res.0.map_body(|_, mut body| {
+                                        BoxBody::new(BodyStream::new(
+                                            futures::stream::poll_fn(
+                                                move |cx| {
+                                                    owner.with(|| {
+                                                        Pin::new(&mut body)
+                                                            .poll_next(cx)
+                                                    })
+                                                },
+                                            ),
+                                        ))
+                                    })
+                                }
+                                _ => res.0,
+                            }
                         })
                     })
                     .await
@@ -427,6 +490,82 @@ pub fn handle_server_fns_with_context(
     })
 }
 
+/// Returns an Actix [struct@Route](actix_web::Route) that serves an
+/// [OpenAPI](server_fn::openapi::OpenApi) document describing every registered server function,
+/// as JSON.
+///
+/// ```ignore
+/// use server_fn::openapi::OpenApi;
+///
+/// App::new()
+///     .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
+///     .route(
+///         "/openapi.json",
+///         leptos_actix::handle_openapi(OpenApi::new("My App", "1.0.0")),
+///     )
+/// ```
+///
+/// The document is generated once, when this is called.
+#[cfg(feature = "openapi")]
+pub fn handle_openapi(openapi: server_fn::openapi::OpenApi) -> Route {
+    let document = openapi.to_json();
+    web::get().to(move || {
+        let document = document.clone();
+        async move {
+            HttpResponse::Ok()
+                .content_type("application/json")
+                .body(document)
+        }
+    })
+}
+
+/// Returns an Actix [struct@Route](actix_web::Route) that serves a `sitemap.xml` listing the
+/// pages of the app, generated from its routes by the given [`Sitemap`].
+///
+/// ```ignore
+/// use leptos_router::sitemap::Sitemap;
+///
+/// let sitemap = Sitemap::new("https://example.com");
+/// App::new()
+///     .route("/sitemap.xml", leptos_actix::handle_sitemap(App, sitemap.clone()))
+///     .route("/robots.txt", leptos_actix::handle_robots_txt(sitemap))
+/// ```
+///
+/// The routes are listed once, when this is called, but the prerendered params of static
+/// routes are loaded for each request, so that the sitemap includes new pages.
+pub fn handle_sitemap<IV>(
+    app_fn: impl Fn() -> IV + Clone + Send + 'static,
+    sitemap: Sitemap,
+) -> Route
+where
+    IV: IntoView + 'static,
+{
+    let routes = Arc::new(generate_router_route_list(&app_fn, &|| {}));
+    web::get().to(move || {
+        let routes = Arc::clone(&routes);
+        let sitemap = sitemap.clone();
+        async move {
+            HttpResponse::Ok()
+                .content_type("application/xml")
+                .body(sitemap.generate(&routes).await)
+        }
+    })
+}
+
+/// Returns an Actix [struct@Route](actix_web::Route) that serves a `robots.txt` pointing to the
+/// sitemap served by [`handle_sitemap`] at `/sitemap.xml`.
+pub fn handle_robots_txt(sitemap: Sitemap) -> Route {
+    let robots_txt = sitemap.robots_txt();
+    web::get().to(move || {
+        let robots_txt = robots_txt.clone();
+        async move {
+            HttpResponse::Ok()
+                .content_type("text/plain")
+                .body(robots_txt)
+        }
+    })
+}
+
 /// Returns an Actix [struct@Route](actix_web::Route) that listens for a `GET` request and tries
 /// to route it using [leptos_router], serving an HTML stream of your application. The stream
 /// will include fallback content for any `<Suspense/>` nodes, and be immediately interactive,
@@ -761,6 +900,11 @@ fn provide_contexts(
     provide_context(RequestUrl::new(&path));
     provide_context(meta_context.clone());
     provide_context(res_options.clone());
+    leptos::csrf::provide_request_cookies(
+        req.headers()
+            .get_all(header::COOKIE)
+            .filter_map(|cookies| cookies.to_str().ok()),
+    );
     provide_context(req);
     provide_server_redirect(redirect);
     leptos::nonce::provide_nonce();
@@ -898,7 +1042,7 @@ impl ActixPath for Vec<PathSegment> {
             }
             match segment {
                 PathSegment::Static(s) => path.push_str(s),
-                PathSegment::Param(s) => {
+                PathSegment::Param(s) | PathSegment::ConstrainedParam(s, _) => {
                     path.push('{');
                     path.push_str(s);
                     path.push('}');
@@ -1011,20 +1155,7 @@ pub fn generate_route_list_with_exclusions_and_ssg_and_context<IV>(
 where
     IV: IntoView + 'static,
 {
-    let _ = any_spawner::Executor::init_tokio();
-
-    let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
-    let (mock_meta, _) = ServerMetaContext::new();
-    let routes = owner
-        .with(|| {
-            // stub out a path for now
-            provide_context(RequestUrl::new(""));
-            provide_context(ResponseOptions::default());
-            provide_context(mock_meta);
-            additional_context();
-            RouteList::generate(&app_fn)
-        })
-        .unwrap_or_default();
+    let routes = generate_router_route_list(&app_fn, &additional_context);
 
     let generator = StaticRouteGenerator::new(
         &routes,
@@ -1033,11 +1164,12 @@ where
     );
 
     // Axum's Router defines Root routes as "/" not ""
-    let mut routes = routes
-        .into_inner()
-        .into_iter()
-        .flat_map(IntoRouteListing::into_route_listing)
-        .collect::<Vec<_>>();
+    let mut routes = dedupe_param_names(
+        routes
+            .into_inner()
+            .into_iter()
+            .flat_map(IntoRouteListing::into_route_listing),
+    );
 
     let routes = if routes.is_empty() {
         vec![ActixRouteListing::new(
@@ -1069,6 +1201,97 @@ where
     (routes.into_iter().chain(excluded).collect(), generator)
 }
 
+/// Merges routes whose paths only differ in the names of their params, like `/posts/{id}` and
+/// `/posts/{slug}`. Actix only ever serves such a path from the first resource registered for
+/// it, so the first of them is kept, and handles the methods and static regeneration of all of
+/// them, as the Leptos router matches the request against the right route.
+///
+/// # Panics
+/// Panics if the merged routes use different [`SsrMode`]s, as a single Actix resource can only
+/// render in one of them.
+fn dedupe_param_names(
+    routes: impl IntoIterator<Item = ActixRouteListing>,
+) -> Vec<ActixRouteListing> {
+    fn shape(path: &str) -> Vec<&str> {
+        path.split('/')
+            .map(|segment| {
+                if segment.starts_with('{') && segment.ends_with(":.*}") {
+                    "*"
+                } else if segment.starts_with('{') {
+                    "{}"
+                } else {
+                    segment
+                }
+            })
+            .collect()
+    }
+
+    let mut deduped: Vec<ActixRouteListing> = Vec::new();
+    for route in routes {
+        match deduped
+            .iter_mut()
+            .find(|existing| shape(&existing.path) == shape(&route.path))
+        {
+            Some(existing) => {
+                existing.mode =
+                    match (mem::take(&mut existing.mode), route.mode) {
+                        (SsrMode::Static(this), SsrMode::Static(that)) => {
+                            SsrMode::Static(this.merge(that))
+                        }
+                        (this, that)
+                            if mem::discriminant(&this)
+                                == mem::discriminant(&that) =>
+                        {
+                            this
+                        }
+                        (this, that) => panic!(
+                            "The routes {} and {} can only be served from the \
+                         same Actix resource, but use different SSR modes \
+                         ({this:?} and {that:?}).",
+                            existing.path, route.path
+                        ),
+                    };
+                for regenerate in route.regenerate {
+                    if !existing.regenerate.contains(&regenerate) {
+                        existing.regenerate.push(regenerate);
+                    }
+                }
+                for method in route.methods {
+                    if !existing.methods.contains(&method) {
+                        existing.methods.push(method);
+                    }
+                }
+            }
+            None => deduped.push(route),
+        }
+    }
+    deduped
+}
+
+/// Generates the [`RouteList`] of the app, before it is converted into Actix paths.
+fn generate_router_route_list<IV>(
+    app_fn: &(impl Fn() -> IV + 'static + Send + Clone),
+    additional_context: &(impl Fn() + 'static + Send + Clone),
+) -> RouteList
+where
+    IV: IntoView + 'static,
+{
+    let _ = any_spawner::Executor::init_tokio();
+
+    let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
+    let (mock_meta, _) = ServerMetaContext::new();
+    owner
+        .with(|| {
+            // stub out a path for now
+            provide_context(RequestUrl::new(""));
+            provide_context(ResponseOptions::default());
+            provide_context(mock_meta);
+            additional_context();
+            RouteList::generate(app_fn)
+        })
+        .unwrap_or_default()
+}
+
 /// Allows generating any prerendered routes.
 #[allow(clippy::type_complexity)]
 pub struct StaticRouteGenerator(
@@ -1602,3 +1825,56 @@ where
     })
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dedupe_param_names, ActixRouteListing};
+    use leptos_router::{Method, SsrMode};
+
+    fn listing(
+        path: &str,
+        mode: SsrMode,
+        methods: &[Method],
+    ) -> ActixRouteListing {
+        ActixRouteListing::new(
+            path.to_string(),
+            mode,
+            methods.iter().copied(),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn merges_routes_that_only_differ_in_param_names() {
+        let routes = dedupe_param_names([
+            listing("/posts/{id}", SsrMode::Async, &[Method::Get]),
+            listing("/posts/{slug}", SsrMode::Async, &[Method::Post]),
+            listing("/posts/new", SsrMode::Async, &[Method::Get]),
+            listing("/files/{path:.*}", SsrMode::Async, &[Method::Get]),
+            listing("/files/{name}", SsrMode::Async, &[Method::Get]),
+        ]);
+        let paths = routes.iter().map(|route| route.path()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/posts/{id}",
+                "/posts/new",
+                "/files/{path:.*}",
+                "/files/{name}"
+            ]
+        );
+        assert_eq!(
+            routes[0].methods().collect::<Vec<_>>(),
+            [Method::Get, Method::Post]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "/posts/{id} and /posts/{slug}")]
+    fn refuses_to_merge_routes_with_different_modes() {
+        dedupe_param_names([
+            listing("/posts/{id}", SsrMode::Async, &[Method::Get]),
+            listing("/posts/{slug}", SsrMode::InOrder, &[Method::Get]),
+        ]);
+    }
+}
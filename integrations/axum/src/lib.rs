@@ -54,6 +54,7 @@ use hydration_context::SsrSharedContext;
 use leptos::{
     config::LeptosOptions,
     context::{provide_context, use_context},
+    csrf::CsrfToken,
     prelude::*,
     reactive::{computed::ScopedFuture, owner::Owner},
     IntoView,
@@ -66,8 +67,8 @@ use leptos_meta::ServerMetaContext;
 use leptos_router::static_routes::ResolvedStaticPath;
 use leptos_router::{
     components::provide_server_redirect, location::RequestUrl,
-    static_routes::RegenerationFn, ExpandOptionals, PathSegment, RouteList,
-    RouteListing, SsrMode,
+    sitemap::Sitemap, static_routes::RegenerationFn, ExpandOptionals,
+    PathSegment, RouteList, RouteListing, SsrMode,
 };
 #[cfg(feature = "default")]
 use once_cell::sync::Lazy;
@@ -75,7 +76,7 @@ use parking_lot::RwLock;
 use server_fn::{redirect::REDIRECT_HEADER, ServerFnError};
 #[cfg(feature = "default")]
 use std::path::Path;
-use std::{collections::HashSet, fmt::Debug, io, pin::Pin, sync::Arc};
+use std::{collections::HashSet, fmt::Debug, io, mem, pin::Pin, sync::Arc};
 #[cfg(feature = "default")]
 use tower::util::ServiceExt;
 #[cfg(feature = "default")]
@@ -112,6 +113,10 @@ impl ResponseParts {
 ///
 /// If you provide your own handler, you will need to provide `ResponseOptions` via context
 /// yourself if you want to access it via context.
+///
+/// Server functions can also set the status code and headers of their own response in a way
+/// that works with any server integration, by returning a
+/// [`WithResponse`](server_fn::codec::WithResponse).
 /// ```
 /// use leptos::prelude::*;
 ///
@@ -263,6 +268,35 @@ pub fn redirect(path: &str) {
     }
 }
 
+/// Provides a CSRF token via context, so that server functions marked with
+/// `#[server(csrf = true)]` can be called from this page.
+///
+/// This reuses the token from the request’s cookie if there is one, and otherwise generates a
+/// new token and adds a `Set-Cookie` header to the response. It can be called in the app
+/// itself, or in the `additional_context` given to [`LeptosRoutes::leptos_routes_with_context`].
+pub fn provide_csrf_token() {
+    let Some(res) = use_context::<ResponseOptions>() else {
+        return;
+    };
+    let token = use_context::<Parts>().and_then(|req| {
+        req.headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|cookies| cookies.to_str().ok())
+            .find_map(CsrfToken::from_cookies)
+    });
+    let token = token.unwrap_or_else(|| {
+        let token = CsrfToken::new();
+        res.append_header(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&token.to_set_cookie())
+                .expect("CSRF cookie is a valid header value"),
+        );
+        token
+    });
+    leptos::csrf::provide_csrf_token(token);
+}
+
 /// Decomposes an HTTP request into its parts, allowing you to read its headers
 /// and other data without consuming the body. Creates a new Request from the
 /// original parts for further processing
@@ -414,6 +448,21 @@ async fn handle_server_fns_inner(
 
                     // apply status code and headers if user changed them
                     res.extend_response(&res_options);
+
+                    // batched calls run while the body is streamed, so they need the owner too
+                    let is_batch =
+                        res.0.headers().get(header::CONTENT_TYPE).is_some_and(
+                            |ty| ty == server_fn::batch::BATCH_CONTENT_TYPE,
+                        );
+                    if let (true, Some(owner)) = (is_batch, Owner::current()) {
+                        let mut body =
+                            std::mem::take(res.0.body_mut()).into_data_stream();
+                        *res.0.body_mut() = Body::from_stream(
+                            futures::stream::poll_fn(move |cx| {
+                                owner.with(|| body.poll_next_unpin(cx))
+                            }),
+                        );
+                    }
                     Ok(res.0)
                 })
             })
@@ -435,6 +484,81 @@ async fn handle_server_fns_inner(
     .expect("could not build Response")
 }
 
+/// Returns an Axum route that serves an [OpenAPI](server_fn::openapi::OpenApi) document
+/// describing every registered server function, as JSON.
+///
+/// ```ignore
+/// use server_fn::openapi::OpenApi;
+///
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+///     .route(
+///         "/openapi.json",
+///         leptos_axum::handle_openapi(OpenApi::new("My App", "1.0.0")),
+///     );
+/// ```
+///
+/// The document is generated once, when this is called.
+#[cfg(feature = "openapi")]
+pub fn handle_openapi<S>(
+    openapi: server_fn::openapi::OpenApi,
+) -> axum::routing::MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let document = openapi.to_json();
+    get(move || {
+        let document = document.clone();
+        async move { ([(header::CONTENT_TYPE, "application/json")], document) }
+    })
+}
+
+/// Returns an Axum route that serves a `sitemap.xml` listing the pages of the app, generated
+/// from its routes by the given [`Sitemap`].
+///
+/// ```ignore
+/// use leptos_router::sitemap::Sitemap;
+///
+/// let sitemap = Sitemap::new("https://example.com");
+/// let app = Router::new()
+///     .route("/sitemap.xml", leptos_axum::handle_sitemap(App, sitemap.clone()))
+///     .route("/robots.txt", leptos_axum::handle_robots_txt(sitemap));
+/// ```
+///
+/// The routes are listed once, when this is called, but the prerendered params of static
+/// routes are loaded for each request, so that the sitemap includes new pages.
+pub fn handle_sitemap<S, IV>(
+    app_fn: impl Fn() -> IV + Clone + Send + 'static,
+    sitemap: Sitemap,
+) -> axum::routing::MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+    IV: IntoView + 'static,
+{
+    let routes = Arc::new(generate_router_route_list(&app_fn, &|| {}));
+    get(move || {
+        let routes = Arc::clone(&routes);
+        let sitemap = sitemap.clone();
+        async move {
+            let xml = sitemap.generate(&routes).await;
+            ([(header::CONTENT_TYPE, "application/xml")], xml)
+        }
+    })
+}
+
+/// Returns an Axum route that serves a `robots.txt` pointing to the sitemap served by
+/// [`handle_sitemap`] at `/sitemap.xml`.
+pub fn handle_robots_txt<S>(sitemap: Sitemap) -> axum::routing::MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let robots_txt = sitemap.robots_txt();
+    get(move || {
+        let robots_txt = robots_txt.clone();
+        async move { ([(header::CONTENT_TYPE, "text/plain")], robots_txt) }
+    })
+}
+
 /// A stream of bytes of HTML.
 pub type PinnedHtmlStream =
     Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
@@ -927,6 +1051,13 @@ fn provide_contexts(
 ) {
     provide_context(RequestUrl::new(path));
     provide_context(meta_context.clone());
+    leptos::csrf::provide_request_cookies(
+        parts
+            .headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|cookies| cookies.to_str().ok()),
+    );
     provide_context(parts);
     provide_context(default_res_options);
     provide_server_redirect(redirect);
@@ -1300,21 +1431,7 @@ pub fn generate_route_list_with_exclusions_and_ssg_and_context<IV>(
 where
     IV: IntoView + 'static,
 {
-    // do some basic reactive setup
-    init_executor();
-    let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
-
-    let routes = owner
-        .with(|| {
-            // stub out a path for now
-            provide_context(RequestUrl::new(""));
-            let (mock_parts, _) = Request::new(Body::from("")).into_parts();
-            let (mock_meta, _) = ServerMetaContext::new();
-            provide_contexts("", &mock_meta, mock_parts, Default::default());
-            additional_context();
-            RouteList::generate(&app_fn)
-        })
-        .unwrap_or_default();
+    let routes = generate_router_route_list(&app_fn, &additional_context);
 
     let generator = StaticRouteGenerator::new(
         &routes,
@@ -1323,11 +1440,12 @@ where
     );
 
     // Axum's Router defines Root routes as "/" not ""
-    let mut routes = routes
-        .into_inner()
-        .into_iter()
-        .flat_map(IntoRouteListing::into_route_listing)
-        .collect::<Vec<_>>();
+    let mut routes = dedupe_param_names(
+        routes
+            .into_inner()
+            .into_iter()
+            .flat_map(IntoRouteListing::into_route_listing),
+    );
 
     let routes = if routes.is_empty() {
         vec![AxumRouteListing::new(
@@ -1358,6 +1476,94 @@ where
     (routes.into_iter().chain(excluded).collect(), generator)
 }
 
+/// Merges routes whose paths only differ in the names of their params, like `/posts/:id<int>`
+/// and `/posts/:slug`, which Axum cannot tell apart and would panic on. The first of them is
+/// kept, and handles the methods and static regeneration of all of them, as the Leptos router
+/// matches the request against the right route.
+///
+/// # Panics
+/// Panics if the merged routes use different [`SsrMode`]s, as a single Axum route can only
+/// render in one of them.
+fn dedupe_param_names(
+    routes: impl IntoIterator<Item = AxumRouteListing>,
+) -> Vec<AxumRouteListing> {
+    fn shape(path: &str) -> Vec<&str> {
+        path.split('/')
+            .map(|segment| match segment.chars().next() {
+                Some(':') => ":",
+                Some('*') => "*",
+                _ => segment,
+            })
+            .collect()
+    }
+
+    let mut deduped: Vec<AxumRouteListing> = Vec::new();
+    for route in routes {
+        match deduped
+            .iter_mut()
+            .find(|existing| shape(&existing.path) == shape(&route.path))
+        {
+            Some(existing) => {
+                existing.mode =
+                    match (mem::take(&mut existing.mode), route.mode) {
+                        (SsrMode::Static(this), SsrMode::Static(that)) => {
+                            SsrMode::Static(this.merge(that))
+                        }
+                        (this, that)
+                            if mem::discriminant(&this)
+                                == mem::discriminant(&that) =>
+                        {
+                            this
+                        }
+                        (this, that) => panic!(
+                            "The routes {} and {} can only be served from the \
+                         same Axum route, but use different SSR modes \
+                         ({this:?} and {that:?}).",
+                            existing.path, route.path
+                        ),
+                    };
+                for regenerate in route.regenerate {
+                    if !existing.regenerate.contains(&regenerate) {
+                        existing.regenerate.push(regenerate);
+                    }
+                }
+                for method in route.methods {
+                    if !existing.methods.contains(&method) {
+                        existing.methods.push(method);
+                    }
+                }
+            }
+            None => deduped.push(route),
+        }
+    }
+    deduped
+}
+
+/// Generates the [`RouteList`] of the app, before it is converted into Axum paths.
+fn generate_router_route_list<IV>(
+    app_fn: &(impl Fn() -> IV + Clone + Send + 'static),
+    additional_context: &(impl Fn() + Clone + Send + 'static),
+) -> RouteList
+where
+    IV: IntoView + 'static,
+{
+    // do some basic reactive setup
+    init_executor();
+    let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
+
+    owner
+        .with(|| {
+            // stub out a path for now
+            provide_context(RequestUrl::new(""));
+            let (mock_parts, _) = Request::new(Body::from("")).into_parts();
+            let (mock_meta, _) = ServerMetaContext::new();
+            provide_contexts("", &mock_meta, mock_parts, Default::default());
+            additional_context();
+            RouteList::generate(app_fn)
+        })
+        .unwrap_or_default()
+}
+
 /// Allows generating any prerendered routes.
 #[allow(clippy::type_complexity)]
 pub struct StaticRouteGenerator(
@@ -1690,7 +1896,7 @@ impl AxumPath for Vec<PathSegment> {
             }
             match segment {
                 PathSegment::Static(s) => path.push_str(s),
-                PathSegment::Param(s) => {
+                PathSegment::Param(s) | PathSegment::ConstrainedParam(s, _) => {
                     path.push(':');
                     path.push_str(s);
                 }
@@ -2061,3 +2267,98 @@ async fn get_static_file(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dedupe_param_names, AxumRouteListing};
+    use futures::{executor::block_on, stream};
+    use leptos_router::{static_routes::StaticRoute, Method, SsrMode};
+
+    fn listing(path: &str, methods: &[Method]) -> AxumRouteListing {
+        AxumRouteListing::new(
+            path.to_string(),
+            SsrMode::default(),
+            methods.iter().copied(),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn merges_routes_that_only_differ_in_param_names() {
+        let routes = dedupe_param_names([
+            listing("/posts/:id", &[Method::Get]),
+            listing("/posts/:slug", &[Method::Get, Method::Post]),
+            listing("/posts/:id", &[Method::Get]),
+            listing("/posts/:id/edit", &[Method::Get]),
+            listing("/posts/new", &[Method::Get]),
+            listing("/files/*path", &[Method::Get]),
+            listing("/files/*rest", &[Method::Get]),
+        ]);
+        let paths = routes.iter().map(|route| route.path()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/posts/:id",
+                "/posts/:id/edit",
+                "/posts/new",
+                "/files/*path"
+            ]
+        );
+        assert_eq!(
+            routes[0].methods().collect::<Vec<_>>(),
+            [Method::Get, Method::Post]
+        );
+    }
+
+    #[test]
+    fn merges_static_routes() {
+        let first = StaticRoute::new()
+            .prerender_params(|| async {
+                [("id".to_string(), vec!["1".to_string()])]
+                    .into_iter()
+                    .collect()
+            })
+            .regenerate(|_| stream::empty());
+        let second = StaticRoute::new()
+            .prerender_params(|| async {
+                [("slug".to_string(), vec!["hello".to_string()])]
+                    .into_iter()
+                    .collect()
+            })
+            .regenerate(|_| stream::empty());
+        let static_listing = |path: &str, route: &StaticRoute| {
+            AxumRouteListing::new(
+                path.to_string(),
+                SsrMode::Static(route.clone()),
+                [Method::Get],
+                vec![],
+            )
+        };
+
+        let routes = dedupe_param_names([
+            static_listing("/posts/:id", &first),
+            static_listing("/posts/:slug", &second),
+        ]);
+        assert_eq!(routes.len(), 1);
+        let SsrMode::Static(merged) = routes[0].mode() else {
+            panic!("expected a static route");
+        };
+        let params = block_on(merged.to_prerendered_params()).unwrap();
+        assert_eq!(params.get("id").unwrap(), &["1"]);
+        assert_eq!(params.get("slug").unwrap(), &["hello"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "/posts/:id and /posts/:slug")]
+    fn refuses_to_merge_routes_with_different_modes() {
+        dedupe_param_names([
+            listing("/posts/:id", &[Method::Get]),
+            AxumRouteListing::new(
+                "/posts/:slug".to_string(),
+                SsrMode::Async,
+                [Method::Get],
+                vec![],
+            ),
+        ]);
+    }
+}
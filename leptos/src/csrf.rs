@@ -0,0 +1,128 @@
+use crate::context::{provide_context, use_context};
+use server_fn::middleware::csrf::{token_from_cookies, CSRF_COOKIE};
+use std::{fmt::Display, ops::Deref, sync::Arc};
+
+/// A token that protects server functions against cross-site request forgery (CSRF).
+///
+/// During server rendering, the server integrations can issue a token and store it in a
+/// cookie (see `provide_csrf_token` in `leptos_axum` or `leptos_actix`). The token is then
+/// sent back with every call to a server function: [`ActionForm`](crate::form::ActionForm)
+/// and [`MultiActionForm`](crate::form::MultiActionForm) add it to the form in a hidden
+/// input, and the browser client adds it to each request in a header. Server functions marked
+/// with `#[server(csrf = true)]` reject calls that do not send it back.
+///
+/// The token being used for the current page can be accessed using [`use_csrf_token`], to
+/// add it to forms that are not built with `ActionForm`:
+///
+/// ```rust,ignore
+/// #[component]
+/// pub fn DeleteAccount() -> impl IntoView {
+///     view! {
+///         <form method="post" action=DeleteAccount::url()>
+///             <input type="hidden" name="_csrf" value=use_csrf_token().map(|t| t.to_string())/>
+///             <input type="submit" value="Delete my account"/>
+///         </form>
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CsrfToken(pub(crate) Arc<str>);
+
+impl Deref for CsrfToken {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for CsrfToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl CsrfToken {
+    /// Generates a new token from 16 bytes (128 bits) of random data.
+    #[cfg(feature = "nonce")]
+    pub fn new() -> Self {
+        CsrfToken(crate::nonce::Nonce::new().0)
+    }
+
+    /// Finds the token in the value of a `Cookie` header.
+    pub fn from_cookies(cookies: &str) -> Option<Self> {
+        token_from_cookies(cookies).map(|token| CsrfToken(token.into()))
+    }
+
+    /// Returns the value of a `Set-Cookie` header that stores this token.
+    ///
+    /// The cookie is not `HttpOnly`, because the browser client needs to read it.
+    pub fn to_set_cookie(&self) -> String {
+        format!("{CSRF_COOKIE}={}; Path=/; SameSite=Lax", self.0)
+    }
+}
+
+#[cfg(feature = "nonce")]
+impl Default for CsrfToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The CSRF token in the cookies of the request that is being rendered on the server.
+#[derive(Clone, Debug)]
+struct RequestCsrfToken(CsrfToken);
+
+/// Accesses the CSRF token for the current page.
+///
+/// On the server, this is the token provided with [`provide_csrf_token`] or, if none has been
+/// provided, the token in the request's cookies. In the browser, this is read from the cookie in
+/// which the server stored it.
+pub fn use_csrf_token() -> Option<CsrfToken> {
+    let token = use_context::<CsrfToken>().or_else(|| {
+        use_context::<RequestCsrfToken>().map(|RequestCsrfToken(token)| token)
+    });
+    #[cfg(all(target_arch = "wasm32", not(feature = "ssr")))]
+    let token = token.or_else(|| {
+        server_fn::client::browser::csrf_token()
+            .map(|token| CsrfToken(token.into()))
+    });
+    token
+}
+
+/// Provides the CSRF token for the current page via context.
+pub fn provide_csrf_token(token: CsrfToken) {
+    provide_context(token)
+}
+
+/// Reads the CSRF token from the `Cookie` headers of the request that is being rendered, so
+/// that [`use_csrf_token`] returns the same token on the server as in the browser, which reads it
+/// from `document.cookie`, even if no token has been provided.
+///
+/// This is called by the server integrations before rendering a page.
+pub fn provide_request_cookies<'a>(cookies: impl IntoIterator<Item = &'a str>) {
+    if let Some(token) = cookies.into_iter().find_map(CsrfToken::from_cookies) {
+        provide_context(RequestCsrfToken(token));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactive::owner::Owner;
+
+    #[test]
+    fn falls_back_to_the_request_cookie() {
+        let owner = Owner::new();
+        owner.with(|| {
+            assert_eq!(use_csrf_token(), None);
+
+            let cookies = format!("theme=dark; {CSRF_COOKIE}=from-cookie");
+            provide_request_cookies([cookies.as_str()]);
+            assert_eq!(use_csrf_token(), Some(CsrfToken("from-cookie".into())));
+
+            provide_csrf_token(CsrfToken("provided".into()));
+            assert_eq!(use_csrf_token(), Some(CsrfToken("provided".into())));
+        });
+    }
+}
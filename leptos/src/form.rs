@@ -1,9 +1,15 @@
-use crate::{children::Children, component, prelude::*, IntoView};
+use crate::{
+    children::Children, component, csrf::use_csrf_token, prelude::*, IntoView,
+};
 use leptos_dom::helpers::window;
 use leptos_server::{ServerAction, ServerMultiAction};
 use serde::de::DeserializeOwned;
 use server_fn::{
-    client::Client, codec::PostUrl, request::ClientReq, ServerFn, ServerFnError,
+    client::Client,
+    codec::PostUrl,
+    middleware::csrf::CSRF_FIELD,
+    request::{ClientReq, UploadProgress},
+    ServerFn, ServerFnError,
 };
 use tachys::{
     either::Either,
@@ -29,6 +35,11 @@ use web_sys::{
 /// default `Url` encoding. This is to ensure that `<ActionForm/>` works correctly
 /// both before and after WASM has loaded.
 ///
+/// ## CSRF Protection
+/// If a [`CsrfToken`](crate::csrf::CsrfToken) has been provided, the form includes it in a
+/// hidden `_csrf` input, so that it can call server functions marked with
+/// `#[server(csrf = true)]` before WASM has loaded.
+///
 /// ## Complex Inputs
 /// Server function arguments that are structs with nested serializable fields
 /// should make use of indexing notation of `serde_qs`.
@@ -134,7 +145,7 @@ where
         .action(ServFn::url())
         .method("post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_input(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -142,6 +153,13 @@ where
     }
 }
 
+/// A hidden input that sends the CSRF token back with the form, if one has been issued.
+fn csrf_input() -> impl IntoView {
+    use_csrf_token().map(|token| {
+        view! { <input type="hidden" name=CSRF_FIELD value=token.to_string()/> }
+    })
+}
+
 /// Automatically turns a server [MultiAction](leptos_server::MultiAction) into an HTML
 /// [`form`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/form)
 /// progressively enhanced to use client-side routing.
@@ -199,7 +217,7 @@ where
         .method("post")
         .attr("method", "post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_input(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -232,6 +250,36 @@ pub(crate) fn resolve_redirect_url(loc: &str) -> Option<web_sys::Url> {
     }
 }
 
+/// Creates a signal that follows the progress of an upload, along with a callback that
+/// updates it.
+///
+/// The callback can be given to `MultipartForm::on_progress`, to show a progress bar while a
+/// file is uploaded to a server function:
+///
+/// ```rust,ignore
+/// let (progress, on_progress) = upload_progress();
+/// let upload = Action::new_local(move |data: &Vec<u8>| {
+///     let form = MultipartForm::new()
+///         .file("file", "upload.bin", "application/octet-stream", data.clone())
+///         .on_progress(on_progress.clone());
+///     upload_file(form.into())
+/// });
+///
+/// view! {
+///     <progress max="1" value=move || progress.get().fraction()/>
+/// }
+/// ```
+pub fn upload_progress() -> (
+    ReadSignal<UploadProgress>,
+    impl Fn(UploadProgress) + Clone + Send + Sync + 'static,
+) {
+    let (progress, set_progress) = signal(UploadProgress::default());
+    (progress, move |value| {
+        // the upload may outlive the component that started it
+        _ = set_progress.try_set(value);
+    })
+}
+
 /// Tries to deserialize a type from form data. This can be used for client-side
 /// validation during form submission.
 pub trait FromFormData
@@ -90,6 +90,9 @@
 //! - **`hydrate`** Hydration: use this to add interactivity to an SSRed Leptos app.
 //! - **`rkyv`** In SSR/hydrate mode, uses [`rkyv`](https://docs.rs/rkyv/latest/rkyv/) to serialize resources and send them
 //!   from the server to the client.
+//! - **`openapi`** In SSR mode, describes each server function with a JSON schema so that an
+//!   OpenAPI document can be generated with [`server_fn::openapi::OpenApi`]. The arguments and
+//!   outputs of server functions must implement `schemars::JsonSchema`.
 //! - **`tracing`** Adds support for [`tracing`](https://docs.rs/tracing/latest/tracing/).
 //!
 //! **Important Note:** You must enable one of `csr`, `hydrate`, or `ssr` to tell Leptos
@@ -218,6 +221,9 @@ pub mod portal;
 /// Components to enable server-side rendering and client-side hydration.
 pub mod hydration;
 
+/// Utilities for protecting server functions against cross-site request forgery.
+pub mod csrf;
+
 /// Utilities for exporting nonces to be used for a Content Security Policy.
 #[cfg(feature = "nonce")]
 pub mod nonce;
@@ -822,7 +822,10 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   relative to the prefix (defaults to the function name followed by unique hash)
 /// - `input`: the encoding for the arguments (defaults to `PostUrl`)
 /// - `output`: the encoding for the response (defaults to `Json`)
-/// - `client`: a custom `Client` implementation that will be used for this server fn
+///     - if `input` is a `Websocket<_, _>`, the server function takes and returns a
+///       `BoxedStream` over a websocket connection, and `output` should be omitted
+/// - `client`: a custom `Client` implementation that will be used for this server fn. For example,
+///   `server_fn::batch::Batch<BrowserClient>` batches calls made at the same time into one request
 /// - `encoding`: (legacy, may be deprecated in future) specifies the encoding, which may be one
 ///   of the following (not case sensitive)
 ///     - `"Url"`: `POST` request with URL-encoded arguments and JSON response
@@ -836,6 +839,38 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   to convert from the argument type to the server function type, and vice versa, allowing you to convert
 ///   between them easily. Setting `impl_from` to `false` disables this, which can be necessary for argument types
 ///   for which this would create a conflicting implementation. (defaults to `true`)
+/// - `structured_errors`: if `true`, errors are serialized with the `output` encoding instead of
+///   being flattened into a string, so a custom error type that implements `Serialize`,
+///   `Deserialize`, and `server_fn::error::ErrorStatus` keeps its structure, and the response
+///   uses the status code given by `ServerFnError::status_code`. (defaults to `false`)
+/// - `timeout`: the number of milliseconds the client waits for a response before failing with
+///   `ServerFnError::Timeout` (defaults to the options set with `server_fn::client::set_call_options`)
+/// - `retries`: how many times the client retries a `GET` request that failed with a network error,
+///   a timeout, or a `429`/`502`/`503`/`504` response, with exponential backoff (defaults to the
+///   options set with `server_fn::client::set_call_options`)
+/// - `cache`: a `Cache-Control` header value, like `"public, max-age=60"`, that makes successful
+///   responses cacheable: they are given this header and an `ETag`, and a request whose
+///   `If-None-Match` header matches the `ETag` receives a `304 Not Modified` response. This is
+///   intended for `GET` server functions (see `server_fn::middleware::cache::CacheLayer`)
+/// - `rate_limit`: how many calls are allowed in a period, like `"10/second"`, `"100/minute"`, or
+///   `"1000/hour"`. Calls over the limit receive a `429 Too Many Requests` response (see
+///   `server_fn::middleware::limit::RateLimitLayer`)
+/// - `rate_limit_key`: the key that calls are limited by, like
+///   `server_fn::middleware::limit::Header("X-Forwarded-For")` or a closure that takes the request
+///   and returns an `Option<String>` (defaults to limiting all calls together)
+/// - `concurrency_limit`: how many calls can run at the same time. Calls over the limit receive a
+///   `429 Too Many Requests` response (see `server_fn::middleware::limit::ConcurrencyLimitLayer`)
+/// - `csrf`: whether calls must send back the CSRF token issued while rendering the page. Calls
+///   without it receive a `403 Forbidden` response (see `server_fn::middleware::csrf::CsrfLayer`)
+/// - `version`: a version number for the server function’s API, which is added to its path after
+///   the prefix (as in `/api/v2/...`), so that several versions can be served side by side (see
+///   `server_fn::registry::versions`)
+///
+/// Unless an `endpoint` is given, the path of a server function ends with a hash of the location
+/// at which it is defined. This can be changed by setting the `SERVER_FN_PATH_HASH` environment
+/// variable while building: to `module`, to hash only its module path and name, or to `name`, to
+/// hash only its name, so that it keeps the same path when it is moved. The paths of every server
+/// function can be compared between releases with `server_fn::manifest::Manifest`.
 ///
 /// ```rust,ignore
 /// #[server(
@@ -869,6 +904,26 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 /// Whatever encoding is provided to `input` should implement `IntoReq` and `FromReq`. Whatever encoding is provided
 /// to `output` should implement `IntoRes` and `FromRes`.
 ///
+/// ## Middleware
+///
+/// Server middleware can be added to a server function with one or more `#[middleware(...)]`
+/// attributes, each of which takes an expression that implements
+/// [`Layer`](../server_fn/middleware/trait.Layer.html). Middleware that intercepts the
+/// request and response on the client can be added with `#[client_middleware(...)]`, which
+/// takes an expression that implements
+/// [`ClientMiddleware`](../server_fn/middleware/client/trait.ClientMiddleware.html).
+///
+/// ```rust,ignore
+/// #[server]
+/// #[client_middleware(MapRequest(|req: BrowserRequest| async move {
+///     req.headers().append("Authorization", &token());
+///     Ok(req)
+/// }))]
+/// pub async fn authenticated() -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
 /// ## Default Values for Parameters
 ///
 /// Individual function parameters can be annotated with `#[server(default)]`, which will pass
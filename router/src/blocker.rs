@@ -0,0 +1,127 @@
+use crate::location::Url;
+use or_poisoned::OrPoisoned;
+use reactive_graph::graph::untrack;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+static BLOCKER_ID: AtomicUsize = AtomicUsize::new(0);
+
+type WhenFn = Arc<dyn Fn() -> bool + Send + Sync>;
+type ConfirmFn =
+    Arc<dyn Fn(Url) -> Pin<Box<dyn Future<Output = bool>>> + Send + Sync>;
+
+#[derive(Clone)]
+struct Blocker {
+    id: usize,
+    when: WhenFn,
+    confirm: ConfirmFn,
+}
+
+/// The navigation blockers registered with [`use_blocker`](crate::hooks::use_blocker).
+#[derive(Clone, Default)]
+pub(crate) struct Blockers(Arc<Mutex<Vec<Blocker>>>);
+
+impl fmt::Debug for Blockers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blockers")
+            .field("len", &self.0.lock().or_poisoned().len())
+            .finish()
+    }
+}
+
+impl Blockers {
+    /// Adds a blocker, returning an ID that can be used to remove it.
+    pub fn add<When, Confirm, Fut>(&self, when: When, confirm: Confirm) -> usize
+    where
+        When: Fn() -> bool + Send + Sync + 'static,
+        Confirm: Fn(Url) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + 'static,
+    {
+        let id = BLOCKER_ID.fetch_add(1, Ordering::Relaxed);
+        self.0.lock().or_poisoned().push(Blocker {
+            id,
+            when: Arc::new(when),
+            confirm: Arc::new(move |url| Box::pin(confirm(url))),
+        });
+        id
+    }
+
+    pub fn remove(&self, id: usize) {
+        self.0
+            .lock()
+            .or_poisoned()
+            .retain(|blocker| blocker.id != id);
+    }
+
+    /// The blockers whose condition currently holds.
+    fn active(&self) -> Vec<Blocker> {
+        // the conditions are checked without holding the lock, as they may add or remove blockers
+        let blockers = self.0.lock().or_poisoned().clone();
+        blockers
+            .into_iter()
+            .filter(|blocker| untrack(|| (blocker.when)()))
+            .collect()
+    }
+
+    /// Whether any blocker currently blocks navigations.
+    pub fn is_blocking(&self) -> bool {
+        !self.active().is_empty()
+    }
+
+    /// Asks each active blocker to confirm a navigation to `url`, stopping at the first one
+    /// that does not.
+    pub async fn confirm(&self, url: &Url) -> bool {
+        for blocker in self.active() {
+            if !(blocker.confirm)(url.clone()).await {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Blockers;
+    use crate::location::{LocationProvider, MemoryHistory};
+    use futures::executor::block_on;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn only_active_blockers_are_consulted() {
+        let blockers = Blockers::default();
+        let url = MemoryHistory::parse("/next").unwrap();
+        assert!(!blockers.is_blocking());
+        assert!(block_on(blockers.confirm(&url)));
+
+        let dirty = Arc::new(AtomicBool::new(false));
+        let id = blockers.add(
+            {
+                let dirty = Arc::clone(&dirty);
+                move || dirty.load(Ordering::Relaxed)
+            },
+            |url| async move { url.path() != "/next" },
+        );
+        assert!(!blockers.is_blocking());
+        assert!(block_on(blockers.confirm(&url)));
+
+        dirty.store(true, Ordering::Relaxed);
+        assert!(blockers.is_blocking());
+        assert!(!block_on(blockers.confirm(&url)));
+        let other = MemoryHistory::parse("/other").unwrap();
+        assert!(block_on(blockers.confirm(&other)));
+
+        blockers.remove(id);
+        assert!(!blockers.is_blocking());
+    }
+}
@@ -3,18 +3,22 @@ pub use super::{form::*, link::*};
 use crate::location::RequestUrl;
 pub use crate::nested_router::Outlet;
 use crate::{
+    blocker::Blockers,
     flat_router::FlatRoutesView,
     hooks::use_navigate,
     location::{
-        BrowserUrl, Location, LocationChange, LocationProvider, State, Url,
+        BrowserUrl, HashUrl, Location, LocationChange, LocationProvider,
+        MemoryHistory, RouterLocation, State, Url,
     },
     navigate::NavigateOptions,
-    nested_router::NestedRoutesView,
+    nested_router::{preload_routes, NestedRoutesView},
     resolve_path::resolve_path,
     ChooseView, MatchNestedRoutes, NestedRoute, RouteDefs, SsrMode,
 };
+use any_spawner::Executor;
 use either_of::EitherOf3;
-use leptos::{children, prelude::*};
+use leptos::{children, prelude::*, server_fn::redirect::RedirectHook};
+use or_poisoned::OrPoisoned;
 use reactive_graph::{
     owner::{provide_context, use_context, Owner},
     signal::ArcRwSignal,
@@ -25,7 +29,10 @@ use std::{
     borrow::Cow,
     fmt::{Debug, Display},
     mem,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 use tachys::view::any_view::AnyView;
@@ -59,6 +66,18 @@ pub fn Router<Chil>(
     /// A signal that will be set while the navigation process is underway.
     #[prop(optional, into)]
     set_is_routing: Option<SignalSetter<bool>>,
+    /// If `true`, the route is kept in the hash of the browser's URL (`/#/users/1`) rather than
+    /// in its path, using [`HashUrl`]. This allows the app to be served by a static file host.
+    #[prop(optional)]
+    hash_routing: bool,
+    /// CSS selectors for scrollable elements whose scroll positions should be restored when
+    /// going back or forward, along with the window's, like `&["main", "#sidebar"]`.
+    #[prop(optional)]
+    scroll_containers: &'static [&'static str],
+    /// If `true`, every [`Routes`] or [`FlatRoutes`] inside the router swaps its views in a
+    /// view transition, as if its own `transition` prop were set.
+    #[prop(optional)]
+    transition: bool,
     // TODO trailing slashes
     ///// How trailing slashes should be handled in [`Route`] paths.
     //#[prop(optional)]
@@ -71,27 +90,82 @@ pub fn Router<Chil>(
 where
     Chil: IntoView,
 {
-    #[cfg(feature = "ssr")]
-    let (location_provider, current_url, redirect_hook) = {
-        let req = use_context::<RequestUrl>().expect("no RequestUrl provided");
-        let parsed = req.parse().expect("could not parse RequestUrl");
-        let current_url = ArcRwSignal::new(parsed);
+    // an in-memory history is used instead of the request or browser URL if one is provided
+    let memory_history = use_context::<MemoryHistory>();
+    let (location_provider, current_url, redirect_hook): (
+        Option<RouterLocation>,
+        _,
+        RedirectHook,
+    ) = match memory_history {
+        Some(history) => {
+            history.init(base.clone());
+            let current_url = history.as_url().clone();
+            let redirect_hook =
+                Box::new(|loc: &str| MemoryHistory::redirect(loc));
+            (
+                Some(RouterLocation::Memory(history)),
+                current_url,
+                redirect_hook,
+            )
+        }
+        None => {
+            #[cfg(feature = "ssr")]
+            {
+                let req = use_context::<RequestUrl>()
+                    .expect("no RequestUrl provided");
+                let parsed = req.parse().expect("could not parse RequestUrl");
+                let current_url = ArcRwSignal::new(parsed);
+
+                (None, current_url, Box::new(move |_: &str| {}))
+            }
 
-        (None, current_url, Box::new(move |_: &str| {}))
+            #[cfg(not(feature = "ssr"))]
+            {
+                if hash_routing {
+                    let location = HashUrl::new()
+                        .expect("could not access browser navigation");
+                    location.init(base.clone());
+                    provide_context(location.clone());
+                    let current_url = location.as_url().clone();
+
+                    let redirect_hook =
+                        Box::new(|loc: &str| HashUrl::redirect(loc));
+
+                    (
+                        Some(RouterLocation::Browser(location.0.clone())),
+                        current_url,
+                        redirect_hook,
+                    )
+                } else {
+                    let location = BrowserUrl::new()
+                        .expect("could not access browser navigation"); // TODO options here
+                    location.init(base.clone());
+                    provide_context(location.clone());
+                    let current_url = location.as_url().clone();
+
+                    let redirect_hook =
+                        Box::new(|loc: &str| BrowserUrl::redirect(loc));
+
+                    (
+                        Some(RouterLocation::Browser(location)),
+                        current_url,
+                        redirect_hook,
+                    )
+                }
+            }
+        }
     };
-
-    #[cfg(not(feature = "ssr"))]
-    let (location_provider, current_url, redirect_hook) = {
-        let location =
-            BrowserUrl::new().expect("could not access browser navigation"); // TODO options here
-        location.init(base.clone());
-        provide_context(location.clone());
-        let current_url = location.as_url().clone();
-
-        let redirect_hook = Box::new(|loc: &str| BrowserUrl::redirect(loc));
-
-        (Some(location), current_url, redirect_hook)
+    // blockers are shared with the location provider, which handles link clicks and back/forward
+    // navigations itself
+    let blockers = match &location_provider {
+        Some(RouterLocation::Browser(location)) => location.blockers.clone(),
+        Some(RouterLocation::Memory(history)) => history.blockers.clone(),
+        None => Default::default(),
     };
+    if let Some(RouterLocation::Browser(location)) = &location_provider {
+        location.scroll.set_containers(scroll_containers);
+    }
+
     // provide router context
     let state = ArcRwSignal::new(State::new(None));
     let location = Location::new(current_url.read_only(), state.read_only());
@@ -107,12 +181,19 @@ where
         set_is_routing,
         query_mutations: Default::default(),
         location_provider,
+        hash_routing,
+        transition,
+        prefetch: Default::default(),
+        blockers,
     });
 
     let children = children.into_inner();
     children()
 }
 
+/// Loads the routes that match a URL, without rendering them.
+pub(crate) type PrefetchFn = Arc<dyn Fn(&Url) + Send + Sync>;
+
 #[derive(Clone)]
 pub(crate) struct RouterContext {
     pub base: Option<Cow<'static, str>>,
@@ -122,34 +203,46 @@ pub(crate) struct RouterContext {
     pub set_is_routing: Option<SignalSetter<bool>>,
     pub query_mutations:
         ArcStoredValue<Vec<(Oco<'static, str>, Option<String>)>>,
-    pub location_provider: Option<BrowserUrl>,
+    pub location_provider: Option<RouterLocation>,
+    pub hash_routing: bool,
+    /// Whether routes use view transitions unless they opt in themselves.
+    pub transition: bool,
+    /// Added by each [`Routes`] or [`FlatRoutes`] while it exists, to prefetch its routes.
+    pub prefetch: ArcStoredValue<Vec<(usize, PrefetchFn)>>,
+    pub blockers: Blockers,
 }
 
 impl RouterContext {
     pub fn navigate(&self, path: &str, options: NavigateOptions) {
-        let current = self.current_url.read_untracked();
-        let resolved_to = if options.resolve {
-            resolve_path(
-                self.base.as_deref().unwrap_or_default(),
-                path,
-                // TODO this should be relative to the current *Route*, I think...
-                Some(current.path()),
-            )
-        } else {
-            resolve_path("", path, None)
-        };
-
-        let mut url = match resolved_to.map(|to| BrowserUrl::parse(&to)) {
-            Some(Ok(url)) => url,
-            Some(Err(e)) => {
-                leptos::logging::error!("Error parsing URL: {e:?}");
-                return;
+        if !self.blockers.is_blocking() {
+            return self.navigate_unblocked(path, options);
+        }
+        match self.resolve_url(path, options.resolve) {
+            Ok(target) => {
+                let this = self.clone();
+                let path = path.to_string();
+                Executor::spawn_local(async move {
+                    if this.blockers.confirm(&target).await {
+                        this.navigate_unblocked(&path, options);
+                    }
+                });
             }
-            None => {
-                leptos::logging::error!("Error resolving relative URL.");
+            // this will log the error
+            Err(_) => self.navigate_unblocked(path, options),
+        }
+    }
+
+    /// Navigates without consulting any blockers, as they have already confirmed this
+    /// navigation.
+    pub fn navigate_unblocked(&self, path: &str, options: NavigateOptions) {
+        let mut url = match self.resolve_url(path, options.resolve) {
+            Ok(url) => url,
+            Err(e) => {
+                leptos::logging::error!("{e}");
                 return;
             }
         };
+        let current = self.current_url.read_untracked();
         let query_mutations =
             mem::take(&mut *self.query_mutations.write_value());
         if !query_mutations.is_empty() {
@@ -168,7 +261,14 @@ impl RouterContext {
         }
 
         if url.origin() != current.origin() {
-            window().location().set_href(path).unwrap();
+            if matches!(self.location_provider, Some(RouterLocation::Memory(_)))
+            {
+                leptos::logging::error!(
+                    "Cannot navigate to {path:?}, as it is on another origin."
+                );
+            } else {
+                window().location().set_href(path).unwrap();
+            }
             return;
         }
 
@@ -179,18 +279,87 @@ impl RouterContext {
 
         // update URL signal, if necessary
         let value = url.to_full_path();
+        if let Some(location) = self
+            .location_provider
+            .as_ref()
+            .and_then(RouterLocation::browser)
+        {
+            location.scroll.save();
+        }
         if current != url {
             drop(current);
             self.current_url.set(url);
         }
 
+        let change = LocationChange {
+            value,
+            replace: options.replace,
+            scroll: options.scroll,
+            state: options.state,
+        };
         if let Some(location_provider) = &self.location_provider {
-            location_provider.complete_navigation(&LocationChange {
-                value,
-                replace: options.replace,
-                scroll: options.scroll,
-                state: options.state,
-            });
+            location_provider.complete_navigation(&change);
+        }
+    }
+
+    /// Loads the code and data of the route at this path, if it is on the current origin.
+    pub fn prefetch(&self, path: &str) {
+        let prefetch = self
+            .prefetch
+            .read_value()
+            .iter()
+            .map(|(_, prefetch)| Arc::clone(prefetch))
+            .collect::<Vec<_>>();
+        if prefetch.is_empty() {
+            return;
+        }
+        match self.parse(path) {
+            Ok(url)
+                if url.origin()
+                    == self.current_url.read_untracked().origin() =>
+            {
+                for prefetch in prefetch {
+                    prefetch(&url);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => leptos::logging::error!("Error parsing URL: {e}"),
+        }
+    }
+
+    pub fn resolve_url(
+        &self,
+        path: &str,
+        resolve: bool,
+    ) -> Result<Url, String> {
+        let current = self.current_url.read_untracked();
+        let resolved_to = if resolve {
+            resolve_path(
+                self.base.as_deref().unwrap_or_default(),
+                path,
+                // TODO this should be relative to the current *Route*, I think...
+                Some(current.path()),
+            )
+        } else {
+            resolve_path("", path, None)
+        };
+        match resolved_to {
+            Some(to) => self
+                .parse(&to)
+                .map_err(|e| format!("Error parsing URL: {e}")),
+            None => Err("Error resolving relative URL.".to_string()),
+        }
+    }
+
+    fn parse(&self, path: &str) -> Result<Url, String> {
+        match &self.location_provider {
+            Some(RouterLocation::Memory(_)) => {
+                MemoryHistory::parse(path).map_err(|e| format!("{e:?}"))
+            }
+            _ if self.hash_routing => {
+                HashUrl::parse_route(path).map_err(|e| format!("{e:?}"))
+            }
+            _ => BrowserUrl::parse(path).map_err(|e| format!("{e:?}")),
         }
     }
 
@@ -214,11 +383,47 @@ impl Debug for RouterContext {
     }
 }
 
+static PREFETCH_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Adds a function that prefetches these routes to the router, until the current owner is
+/// cleaned up, so that nested or sibling `<Routes>` each prefetch their own routes.
+fn register_prefetch<Defs>(
+    prefetch: &ArcStoredValue<Vec<(usize, PrefetchFn)>>,
+    routes: &RouteDefs<Defs>,
+    outer_owner: &Owner,
+) where
+    Defs: MatchNestedRoutes + Clone + Send + 'static,
+{
+    let id = PREFETCH_ID.fetch_add(1, Ordering::Relaxed);
+    let routes = Mutex::new(routes.clone());
+    let outer_owner = outer_owner.clone();
+    prefetch.write_value().push((
+        id,
+        Arc::new(move |url: &Url| {
+            let routes = routes.lock().or_poisoned().clone();
+            Executor::spawn_local(preload_routes(&routes, url, &outer_owner));
+        }),
+    ));
+    on_cleanup({
+        let prefetch = prefetch.clone();
+        move || {
+            prefetch
+                .write_value()
+                .retain(|(registered, _)| *registered != id)
+        }
+    });
+}
+
 #[component(transparent)]
 pub fn Routes<Defs, FallbackFn, Fallback>(
     /// A function that returns the view that should be shown if no route is matched.
     fallback: FallbackFn,
     /// Whether to use the View Transition API during navigation.
+    ///
+    /// The new view is swapped in once its async data has loaded, and the document element
+    /// gets `router-outlet-{level}` and `router-back` or `router-forward` classes while the
+    /// transition runs, so that CSS can animate back navigations differently. Views are
+    /// swapped without a transition in browsers that do not support it.
     #[prop(optional)]
     transition: bool,
     /// The route definitions. This should consist of one or more [`ParentRoute`] or [`Route`]
@@ -230,14 +435,17 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        location_provider: location,
+        transition: router_transition,
+        prefetch,
         ..
     } = use_context()
         .expect("<Routes> should be used inside a <Router> component");
+    let transition = transition || router_transition;
     let base = base.map(|base| {
         let mut base = Oco::from(base);
         base.upgrade_inplace();
@@ -249,6 +457,7 @@ where
     );
     let outer_owner =
         Owner::current().expect("creating Routes, but no Owner was found");
+    register_prefetch(&prefetch, &routes, &outer_owner);
     move || {
         current_url.track();
         outer_owner.with(|| {
@@ -272,6 +481,11 @@ pub fn FlatRoutes<Defs, FallbackFn, Fallback>(
     /// A function that returns the view that should be shown if no route is matched.
     fallback: FallbackFn,
     /// Whether to use the View Transition API during navigation.
+    ///
+    /// The new view is swapped in once its async data has loaded, and the document element
+    /// gets `router-outlet-{level}` and `router-back` or `router-forward` classes while the
+    /// transition runs, so that CSS can animate back navigations differently. Views are
+    /// swapped without a transition in browsers that do not support it.
     #[prop(optional)]
     transition: bool,
     /// The route definitions. This should consist of one or more [`ParentRoute`] or [`Route`]
@@ -283,14 +497,17 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        location_provider: location,
+        transition: router_transition,
+        prefetch,
         ..
     } = use_context()
         .expect("<FlatRoutes> should be used inside a <Router> component");
+    let transition = transition || router_transition;
 
     // TODO base
     #[allow(unused)]
@@ -306,6 +523,7 @@ where
 
     let outer_owner =
         Owner::current().expect("creating Router, but no Owner was found");
+    register_prefetch(&prefetch, &routes, &outer_owner);
 
     move || {
         current_url.track();
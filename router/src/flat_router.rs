@@ -303,6 +303,8 @@ where
                                             .await;
                                     set_is_routing.set(false);
                                     value
+                                } else if transition {
+                                    AsyncTransition::run(|| view.choose()).await
                                 } else {
                                     view.choose().await
                                 },
@@ -318,7 +320,12 @@ where
                                         .rebuild(&mut state.borrow_mut().view);
                                 };
                                 if transition {
-                                    start_view_transition(0, is_back, rebuild);
+                                    start_view_transition(
+                                        0,
+                                        is_back,
+                                        rebuild,
+                                        async {},
+                                    );
                                 } else {
                                     rebuild();
                                 }
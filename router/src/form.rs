@@ -1,11 +1,11 @@
 use crate::{
-    components::ToHref,
-    hooks::{has_router, use_navigate, use_resolved_path},
+    components::{RouterContext, ToHref},
+    hooks::{has_router, use_resolved_path},
     location::{BrowserUrl, LocationProvider},
     NavigateOptions,
 };
 use leptos::{ev, html::form, logging::*, prelude::*, task::spawn_local};
-use std::{error::Error, sync::Arc};
+use std::{cell::Cell, error::Error, rc::Rc, sync::Arc};
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::{FormData, RequestRedirect, Response};
 
@@ -99,13 +99,50 @@ where
         replace: bool,
     ) -> impl IntoView {
         let action_version = version;
-        let navigate = has_router.then(use_navigate);
+        let router = has_router.then(expect_context::<RouterContext>);
+        // any blockers are consulted once, before the form is submitted
+        let navigate = router.clone().map(|router| {
+            move |path: &str, options: NavigateOptions| {
+                router.navigate_unblocked(path, options)
+            }
+        });
+        let confirmed = Rc::new(Cell::new(false));
         let on_submit = {
             move |ev: web_sys::SubmitEvent| {
                 let navigate = navigate.clone();
                 if ev.default_prevented() {
                     return;
                 }
+                if let Some(router) = &router {
+                    if !confirmed.replace(false)
+                        && router.blockers.is_blocking()
+                    {
+                        ev.prevent_default();
+                        ev.stop_propagation();
+                        let (form, _, action, _) = extract_form_attributes(&ev);
+                        let submitter = ev.submitter();
+                        let target = router.resolve_url(&action, true);
+                        let blockers = router.blockers.clone();
+                        let confirmed = Rc::clone(&confirmed);
+                        spawn_local(async move {
+                            let allowed = match target {
+                                Ok(target) => blockers.confirm(&target).await,
+                                Err(e) => {
+                                    error!("{e}");
+                                    false
+                                }
+                            };
+                            if allowed {
+                                // submit the form again, now that it has been confirmed
+                                confirmed.set(true);
+                                _ = form.request_submit_with_submitter(
+                                    submitter.as_ref(),
+                                );
+                            }
+                        });
+                        return;
+                    }
+                }
                 let navigate_options = NavigateOptions {
                     scroll: !noscroll,
                     replace,
@@ -1,20 +1,29 @@
 use crate::{
     components::RouterContext,
-    location::{Location, Url},
+    location::{Location, RouterLocation, Url},
     navigate::NavigateOptions,
     params::{Params, ParamsError, ParamsMap},
 };
-use leptos::{leptos_dom::helpers::request_animation_frame, oco::Oco};
+use leptos::{
+    ev,
+    leptos_dom::helpers::{request_animation_frame, window_event_listener},
+    oco::Oco,
+};
 use reactive_graph::{
     computed::{ArcMemo, Memo},
-    owner::{expect_context, use_context},
+    graph::untrack,
+    owner::{expect_context, on_cleanup, use_context},
     signal::{ArcRwSignal, ReadSignal},
     traits::{Get, GetUntracked, ReadUntracked, With, WriteValue},
     wrappers::write::SignalSetter,
 };
 use std::{
+    future::Future,
     str::FromStr,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 /// See [`query_signal`].
@@ -278,6 +287,80 @@ pub fn use_navigate() -> impl Fn(&str, NavigateOptions) + Clone {
     move |path: &str, options: NavigateOptions| cx.navigate(path, options)
 }
 
+/// Blocks navigations away from the current page while `when` returns `true`, until `confirm`
+/// resolves to `true` for the URL that is being navigated to.
+///
+/// This is consulted for clicks on links, [`Form`](crate::components::Form) submissions,
+/// navigations with [`use_navigate`], and back/forward navigations. While `when` returns
+/// `true`, the browser will also ask the user to confirm before they reload or leave the page.
+/// The blocker is removed when the component that called this is unmounted.
+///
+/// ```rust
+/// # use leptos::prelude::*;
+/// # use leptos_router::hooks::use_blocker;
+/// # if false { // can't actually navigate, no <Router/>
+/// let (unsaved, set_unsaved) = signal(false);
+/// use_blocker(
+///     move || unsaved.get(),
+///     |_url| async {
+///         window()
+///             .confirm_with_message("Leave without saving your changes?")
+///             .unwrap_or(false)
+///     },
+/// );
+/// # }
+/// ```
+#[track_caller]
+pub fn use_blocker<When, Confirm, Fut>(when: When, confirm: Confirm)
+where
+    When: Fn() -> bool + Send + Sync + 'static,
+    Confirm: Fn(Url) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + 'static,
+{
+    let RouterContext { blockers, .. } = use_context()
+        .expect("You cannot call `use_blocker` outside a <Router>.");
+    let when = Arc::new(when);
+    let id = blockers.add(
+        {
+            let when = Arc::clone(&when);
+            move || when()
+        },
+        confirm,
+    );
+    let unload_handle = window_event_listener(ev::beforeunload, move |ev| {
+        if untrack(|| when()) {
+            ev.prevent_default();
+            ev.set_return_value("");
+        }
+    });
+    on_cleanup(move || {
+        blockers.remove(id);
+        unload_handle.remove();
+    });
+}
+
+/// Stops the router from restoring scroll positions on the route that calls this, while it is
+/// mounted.
+///
+/// By default, the router restores the scroll position of the page when the user goes back or
+/// forward to it. A route that manages its own scroll position, like an infinite list, can call
+/// this to opt out. This applies to the paths nested inside the route too, and to every route if
+/// it is called outside of one.
+#[track_caller]
+pub fn use_no_scroll_restoration() {
+    let RouterContext {
+        location_provider, ..
+    } = use_context().expect(
+        "You cannot call `use_no_scroll_restoration` outside a <Router>.",
+    );
+    if let Some(RouterLocation::Browser(location)) = location_provider {
+        let matched = use_context::<Matched>().map(|matched| matched.0);
+        let scroll = location.scroll;
+        let id = scroll.opt_out(matched);
+        on_cleanup(move || scroll.opt_in(id));
+    }
+}
+
 /// Returns a reactive string that contains the route that was matched for
 /// this [`Route`](crate::components::Route).
 #[track_caller]
@@ -115,6 +115,7 @@
 //! - `ssr` Server-side rendering: Generate an HTML string (typically on the server)
 //! - `nightly`: On `nightly` Rust, enables the function-call syntax for signal getters and setters.
 //! - `tracing`: Enables support for the `tracing` crate.
+//! - `regex`: Enables `RegexParam`, to constrain route params with regular expressions.
 //!
 //! [`Leptos`]: <https://github.com/leptos-rs/leptos>
 //! [`router`]: <https://github.com/leptos-rs/leptos/blob/main/examples/router/src/lib.rs>
@@ -124,6 +125,7 @@
 #![cfg_attr(feature = "nightly", feature(auto_traits))]
 #![cfg_attr(feature = "nightly", feature(negative_impls))]
 
+mod blocker;
 /// Components for route definition and for enhanced links and forms.
 pub mod components;
 /// An optimized "flat" router without nested routes.
@@ -142,82 +144,144 @@ mod navigate;
 pub mod nested_router;
 /// Support for maps of parameters in the path or in the query.
 pub mod params;
+/// Generation of sitemaps and `robots.txt` files from the route list.
+pub mod sitemap;
 mod ssr_mode;
 /// Support for static routing.
 pub mod static_routes;
+mod typed_route;
 
 pub use generate_route_list::*;
 #[doc(inline)]
 pub use leptos_router_macro::path;
+#[doc(inline)]
+pub use leptos_router_macro::TypedRoute;
 pub use matching::*;
 pub use method::*;
 pub use navigate::*;
 pub use ssr_mode::*;
+pub use typed_route::*;
 
 pub(crate) mod view_transition {
+    use any_spawner::Executor;
     use js_sys::{Function, Promise, Reflect};
     use leptos::leptos_dom::helpers::document;
+    use std::{
+        cell::RefCell,
+        future::Future,
+        rc::Rc,
+        sync::atomic::{AtomicBool, Ordering},
+    };
     use wasm_bindgen::{closure::Closure, intern, JsCast, JsValue};
 
+    static WARNED_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+    thread_local! {
+        /// The ID of the latest transition, and the classes it added to the document element.
+        static ACTIVE: RefCell<(usize, Vec<String>)> = Default::default();
+    }
+
+    /// Swaps the view in a view transition, if the browser supports them.
+    ///
+    /// `update` swaps in the new view, and `loaded` resolves once it has finished loading its
+    /// data, so that the transition animates to the loaded view rather than to a placeholder.
+    /// While the transition runs, the document element has a `router-outlet-{level}` class,
+    /// where `level` is the depth of the outermost outlet that changed, and a `router-back` or
+    /// `router-forward` class for the direction of the navigation.
     pub fn start_view_transition(
         level: u8,
         is_back_navigation: bool,
-        fun: impl FnOnce() + 'static,
+        update: impl FnOnce() + 'static,
+        loaded: impl Future<Output = ()> + 'static,
     ) {
         let document = document();
-        let document_element = document.document_element().unwrap();
-        let class_list = document_element.class_list();
         let svt = Reflect::get(
             &document,
             &JsValue::from_str(intern("startViewTransition")),
         )
         .and_then(|svt| svt.dyn_into::<Function>());
-        _ = class_list.add_1(&format!("router-outlet-{level}"));
-        if is_back_navigation {
-            _ = class_list.add_1("router-back");
-        }
-        match svt {
-            Ok(svt) => {
-                let cb = Closure::once_into_js(Box::new(move || {
-                    fun();
-                }));
-                match svt.call1(
-                    document.unchecked_ref(),
-                    cb.as_ref().unchecked_ref(),
-                ) {
-                    Ok(view_transition) => {
-                        let class_list = document_element.class_list();
-                        let finished = Reflect::get(
-                            &view_transition,
-                            &JsValue::from_str("finished"),
-                        )
-                        .expect("no `finished` property on ViewTransition")
-                        .unchecked_into::<Promise>();
-                        let cb = Closure::new(Box::new(move |_| {
-                            if is_back_navigation {
-                                class_list.remove_1("router-back").unwrap();
-                            }
-                            class_list
-                                .remove_1(&format!("router-outlet-{level}"))
-                                .unwrap();
-                        })
-                            as Box<dyn FnMut(JsValue)>);
-                        _ = finished.then(&cb);
-                        cb.into_js_value();
-                    }
-                    Err(e) => {
-                        web_sys::console::log_1(&e);
-                    }
-                }
-            }
-            Err(_) => {
+        let Ok(svt) = svt else {
+            if !WARNED_UNSUPPORTED.swap(true, Ordering::Relaxed) {
                 leptos::logging::warn!(
                     "NOTE: View transitions are not supported in this \
-                     browser; unless you provide a polyfill, view transitions \
-                     will not be applied."
+                     browser; unless you provide a polyfill, views will be \
+                     swapped without a transition."
                 );
-                fun();
+            }
+            update();
+            return;
+        };
+
+        let classes = vec![
+            format!("router-outlet-{level}"),
+            String::from(if is_back_navigation {
+                "router-back"
+            } else {
+                "router-forward"
+            }),
+        ];
+        let id = set_classes(classes);
+
+        // `update` is called by the transition, or directly if it cannot be started
+        let update = Rc::new(RefCell::new(Some(update)));
+        let cb = Closure::once_into_js({
+            let update = Rc::clone(&update);
+            move || {
+                if let Some(update) = update.borrow_mut().take() {
+                    update();
+                }
+                let mut loaded = Some(loaded);
+                Promise::new(&mut |resolve, _| {
+                    if let Some(loaded) = loaded.take() {
+                        Executor::spawn_local(async move {
+                            loaded.await;
+                            _ = resolve.call0(&JsValue::UNDEFINED);
+                        });
+                    }
+                })
+            }
+        });
+        match svt.call1(document.unchecked_ref(), cb.as_ref().unchecked_ref()) {
+            Ok(view_transition) => {
+                let finished = Reflect::get(
+                    &view_transition,
+                    &JsValue::from_str("finished"),
+                )
+                .expect("no `finished` property on ViewTransition")
+                .unchecked_into::<Promise>();
+                let cb = Closure::once(move |_: JsValue| {
+                    // a newer transition may have replaced this one's classes
+                    if ACTIVE.with_borrow(|(active, _)| *active == id) {
+                        set_classes(Vec::new());
+                    }
+                });
+                _ = finished.then(&cb);
+                cb.into_js_value();
+            }
+            Err(e) => {
+                web_sys::console::log_1(&e);
+                set_classes(Vec::new());
+                if let Some(update) = update.borrow_mut().take() {
+                    update();
+                }
             }
         }
     }
+
+    /// Replaces the classes added to the document element for the previous transition,
+    /// returning an ID for the new one.
+    fn set_classes(classes: Vec<String>) -> usize {
+        let class_list = document().document_element().unwrap().class_list();
+        ACTIVE.with_borrow_mut(|(id, active)| {
+            for class in active.iter() {
+                _ = class_list.remove_1(class);
+            }
+            for class in &classes {
+                _ = class_list.add_1(class);
+            }
+            *active = classes;
+            *id += 1;
+            *id
+        })
+    }
 }
@@ -1,7 +1,18 @@
-use crate::{components::RouterContext, hooks::use_resolved_path};
-use leptos::{children::Children, oco::Oco, prelude::*};
+use crate::{
+    components::RouterContext, hooks::use_resolved_path, location::hash_href,
+};
+use js_sys::Array;
+use leptos::{
+    callback::{Callable, Callback},
+    children::Children,
+    oco::Oco,
+    prelude::*,
+};
 use reactive_graph::{computed::ArcMemo, owner::use_context};
-use std::{borrow::Cow, rc::Rc};
+use send_wrapper::SendWrapper;
+use std::{borrow::Cow, rc::Rc, sync::Arc};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{IntersectionObserver, IntersectionObserverEntry};
 
 /// Describes a value that is either a static or a reactive URL, i.e.,
 /// a [`String`], a [`&str`], or a reactive `Fn() -> String`.
@@ -55,6 +66,25 @@ where
     }
 }
 
+/// When an [`A`] should load the route it links to, before the link is followed.
+///
+/// Prefetching a route loads the code of [`Lazy`](crate::Lazy) routes, and starts loading their
+/// data, so that following the link does not have to wait for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Prefetch {
+    /// The route is only loaded when the link is followed.
+    #[default]
+    None,
+    /// The route is loaded when the pointer moves over the link.
+    Hover,
+    /// The route is loaded when the link is focused.
+    Focus,
+    /// The route is loaded when the link is scrolled into view.
+    Visible,
+    /// The route is loaded as soon as the link is rendered in the browser.
+    Eager,
+}
+
 /// An HTML [`a`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/a)
 /// progressively enhanced to use client-side routing.
 ///
@@ -92,21 +122,37 @@ pub fn A<H>(
     /// a trailing slash.
     #[prop(optional)]
     strict_trailing_slash: bool,
+    /// When the route that the link points to should be loaded. Defaults to [`Prefetch::None`].
+    #[prop(optional)]
+    prefetch: Prefetch,
+    /// Called when the route that the link points to is prefetched. This can be used to start
+    /// loading data that the route will need, like calling a server function whose response
+    /// is cached.
+    #[prop(optional, into)]
+    on_prefetch: Option<Callback<()>>,
     /// The nodes or elements to be shown inside the link.
     children: Children,
 ) -> impl IntoView
 where
     H: ToHref + Send + Sync + 'static,
 {
+    #[allow(clippy::too_many_arguments)]
     fn inner(
         href: ArcMemo<Option<String>>,
         target: Option<Oco<'static, str>>,
         exact: bool,
         children: Children,
         strict_trailing_slash: bool,
+        prefetch: Prefetch,
+        on_prefetch: Option<Callback<()>>,
     ) -> impl IntoView {
-        let RouterContext { current_url, .. } =
-            use_context().expect("tried to use <A/> outside a <Router/>.");
+        let router = use_context::<RouterContext>()
+            .expect("tried to use <A/> outside a <Router/>.");
+        let RouterContext {
+            current_url,
+            hash_routing,
+            ..
+        } = router.clone();
         let is_active = {
             let href = href.clone();
             move || {
@@ -124,9 +170,60 @@ where
             }
         };
 
+        // each href is only prefetched once
+        let prefetch_route: Arc<dyn Fn() + Send + Sync> = {
+            let href = href.clone();
+            let prefetched = ArcStoredValue::new(None::<String>);
+            Arc::new(move || {
+                let Some(href) = href.get_untracked() else {
+                    return;
+                };
+                if prefetched.read_value().as_ref() == Some(&href) {
+                    return;
+                }
+                router.prefetch(&href);
+                if let Some(on_prefetch) = on_prefetch {
+                    on_prefetch.run(());
+                }
+                *prefetched.write_value() = Some(href);
+            })
+        };
+        let node_ref = NodeRef::<leptos::html::A>::new();
+        if matches!(prefetch, Prefetch::Visible | Prefetch::Eager) {
+            let prefetch_route = Arc::clone(&prefetch_route);
+            Effect::new(move |_| {
+                if let Some(el) = node_ref.get() {
+                    if prefetch == Prefetch::Eager {
+                        prefetch_route();
+                    } else {
+                        prefetch_when_visible(&el, Arc::clone(&prefetch_route));
+                    }
+                }
+            });
+        }
+        let on_pointerenter = {
+            let prefetch_route = Arc::clone(&prefetch_route);
+            move |_| {
+                if prefetch == Prefetch::Hover {
+                    prefetch_route();
+                }
+            }
+        };
+        let on_focus = move |_| {
+            if prefetch == Prefetch::Focus {
+                prefetch_route();
+            }
+        };
+
         view! {
             <a
-                href=move || href.get().unwrap_or_default()
+                node_ref=node_ref
+                on:pointerenter=on_pointerenter
+                on:focus=on_focus
+                href=move || {
+                    let href = href.get().unwrap_or_default();
+                    if hash_routing { hash_href(href) } else { href }
+                }
                 target=target
                 aria-current=move || if is_active() { Some("page") } else { None }
             >
@@ -137,7 +234,43 @@ where
     }
 
     let href = use_resolved_path(move || href.to_href()());
-    inner(href, target, exact, children, strict_trailing_slash)
+    inner(
+        href,
+        target,
+        exact,
+        children,
+        strict_trailing_slash,
+        prefetch,
+        on_prefetch,
+    )
+}
+
+/// Calls `prefetch_route` once the element has been scrolled into view.
+fn prefetch_when_visible(
+    el: &web_sys::HtmlAnchorElement,
+    prefetch_route: Arc<dyn Fn() + Send + Sync>,
+) {
+    let callback = Closure::<dyn Fn(Array, IntersectionObserver)>::new(
+        move |entries: Array, observer: IntersectionObserver| {
+            if entries.iter().any(|entry| {
+                entry
+                    .unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            }) {
+                observer.disconnect();
+                prefetch_route();
+            }
+        },
+    );
+    match IntersectionObserver::new(callback.as_ref().unchecked_ref()) {
+        Ok(observer) => {
+            observer.observe(el);
+            // the callback has to live as long as the observer
+            let observer = SendWrapper::new((observer, callback));
+            on_cleanup(move || observer.0.disconnect());
+        }
+        Err(e) => leptos::logging::error!("{e:?}"),
+    }
 }
 
 // Test if `href` is active for `location`.  Assumes _both_ `href` and `location` begin with a `'/'`.
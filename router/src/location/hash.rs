@@ -0,0 +1,163 @@
+use super::{BrowserUrl, LocationChange, LocationProvider, Url};
+use leptos::prelude::*;
+use reactive_graph::signal::ArcRwSignal;
+use std::borrow::Cow;
+use tachys::dom::window;
+use wasm_bindgen::JsValue;
+
+/// A [`LocationProvider`] that keeps the route in the hash of the browser's URL, like
+/// `/index.html#/users/1?tab=posts`, rather than in its path.
+///
+/// This allows an app to be served by a static file host, or from a `file://` URL, where
+/// the server cannot respond to every route with the app. It is used by the
+/// [`Router`](crate::components::Router) if its `hash_routing` prop is set:
+///
+/// ```rust,ignore
+/// view! {
+///     <Router hash_routing=true>
+///         <A href="/users/1">"User"</A> // rendered as `<a href="#/users/1">`
+///         <Routes fallback=|| "Not found.">
+///             <Route path=path!("/users/:id") view=User/>
+///         </Routes>
+///     </Router>
+/// }
+/// ```
+///
+/// Routes, `<A>` and `<Form>` components, and [`use_navigate`](crate::hooks::use_navigate)
+/// work with route paths as usual. Clicks on `<a>` elements are only handled by the router if
+/// the hash of their `href` begins with `/`, so that other hashes can still be used to link to
+/// elements on the page.
+#[derive(Clone, Debug)]
+pub struct HashUrl(pub(crate) BrowserUrl);
+
+impl LocationProvider for HashUrl {
+    type Error = JsValue;
+
+    fn new() -> Result<Self, JsValue> {
+        BrowserUrl::with_current(Self::current()?, true).map(Self)
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        self.0.as_url()
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        let hash = window().location().hash()?;
+        Self::parse_route(initial_route(&hash))
+    }
+
+    fn parse(url: &str) -> Result<Url, Self::Error> {
+        let base = window().location().origin()?;
+        Self::parse_with_base(url, &base)
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        let url = web_sys::Url::new_with_base(url, base)?;
+        let hash = url.hash();
+        let route = route_from_hash(&hash).ok_or_else(|| {
+            JsValue::from_str(&format!("{hash:?} is not a route"))
+        })?;
+        BrowserUrl::parse_with_base(route, &url.href())
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        self.0.init(base);
+    }
+
+    fn ready_to_complete(&self) {
+        self.0.ready_to_complete();
+    }
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        self.0.complete_navigation(loc);
+    }
+
+    fn redirect(loc: &str) {
+        BrowserUrl::redirect(loc);
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.0.is_back()
+    }
+}
+
+impl HashUrl {
+    /// Parses the path, query and hash of a route.
+    ///
+    /// The route is resolved against the URL of the document, rather than its origin, as pages
+    /// loaded from `file://` URLs have an opaque origin.
+    pub(crate) fn parse_route(route: &str) -> Result<Url, JsValue> {
+        BrowserUrl::parse_with_base(route, &window().location().href()?)
+    }
+
+    /// Returns the route in the current hash, after the browser has moved to another history
+    /// entry or hash, or `None` if the hash is not a route.
+    ///
+    /// Unlike [`current`](LocationProvider::current), this does not fall back to the root
+    /// route, so that following a link to an element, like `#top`, keeps the current route.
+    pub(crate) fn current_route() -> Result<Option<Url>, JsValue> {
+        let hash = window().location().hash()?;
+        route_from_hash(&hash).map(Self::parse_route).transpose()
+    }
+}
+
+/// Returns the route that a page is loaded at: the route in its hash or, if there is none, like
+/// for `/index.html` or `/index.html#top`, the root route.
+fn initial_route(hash: &str) -> &str {
+    route_from_hash(hash).unwrap_or("/")
+}
+
+/// Returns the route in the hash of a URL, or `None` if the hash does not contain a route.
+///
+/// An empty hash does not contain a route, so that links to other pages on the same origin
+/// are not handled by the router.
+fn route_from_hash(hash: &str) -> Option<&str> {
+    let hash = hash.strip_prefix('#').unwrap_or(hash);
+    hash.starts_with('/').then_some(hash)
+}
+
+/// Converts the `href` of a link to a route into one that can be followed with hash routing.
+pub(crate) fn hash_href(href: String) -> String {
+    if href.starts_with('/') {
+        format!("#{href}")
+    } else {
+        href
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_href, initial_route, route_from_hash};
+
+    #[test]
+    fn finds_routes_in_hashes() {
+        assert_eq!(route_from_hash(""), None);
+        assert_eq!(route_from_hash("#"), None);
+        assert_eq!(route_from_hash("#/users/1"), Some("/users/1"));
+        assert_eq!(
+            route_from_hash("#/users/1?tab=posts#top"),
+            Some("/users/1?tab=posts#top")
+        );
+        assert_eq!(route_from_hash("#top"), None);
+    }
+
+    #[test]
+    fn only_falls_back_to_the_root_route_on_load() {
+        assert_eq!(initial_route(""), "/");
+        assert_eq!(initial_route("#top"), "/");
+        assert_eq!(initial_route("#/users/1"), "/users/1");
+        // moving to `#top` once the page has loaded is not a move to another route, so the
+        // current route is kept
+        assert_eq!(route_from_hash("#top"), None);
+    }
+
+    #[test]
+    fn hashes_route_hrefs() {
+        assert_eq!(hash_href("/users/1".into()), "#/users/1");
+        assert_eq!(
+            hash_href("https://leptos.dev".into()),
+            "https://leptos.dev"
+        );
+        assert_eq!(hash_href("#top".into()), "#top");
+    }
+}
@@ -1,7 +1,11 @@
-use super::{handle_anchor_click, LocationChange, LocationProvider, Url};
-use crate::{hooks::use_navigate, params::ParamsMap};
+use super::{
+    entry_key, handle_anchor_click, new_entry_key, with_entry_key, HashUrl,
+    LocationChange, LocationProvider, ScrollPositions, Url,
+};
+use crate::{blocker::Blockers, hooks::use_navigate, params::ParamsMap};
+use any_spawner::Executor;
 use core::fmt;
-use futures::channel::oneshot;
+use futures::{channel::oneshot, future::Either};
 use js_sys::{try_iter, Array, JsString};
 use leptos::prelude::*;
 use or_poisoned::OrPoisoned;
@@ -12,12 +16,14 @@ use reactive_graph::{
 use std::{
     borrow::Cow,
     boxed::Box,
+    cell::Cell,
+    rc::Rc,
     string::String,
     sync::{Arc, Mutex},
 };
 use tachys::dom::{document, window};
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
-use web_sys::{Event, UrlSearchParams};
+use web_sys::{Event, ScrollRestoration, UrlSearchParams};
 
 #[derive(Clone)]
 pub struct BrowserUrl {
@@ -25,6 +31,10 @@ pub struct BrowserUrl {
     pub(crate) pending_navigation: Arc<Mutex<Option<oneshot::Sender<()>>>>,
     pub(crate) path_stack: ArcStoredValue<Vec<Url>>,
     pub(crate) is_back: ArcRwSignal<bool>,
+    pub(crate) blockers: Blockers,
+    pub(crate) scroll: Arc<ScrollPositions>,
+    /// Whether the route is kept in the hash of the browser's URL, rather than its path.
+    hash_routing: bool,
 }
 
 impl fmt::Debug for BrowserUrl {
@@ -34,8 +44,41 @@ impl fmt::Debug for BrowserUrl {
 }
 
 impl BrowserUrl {
-    fn scroll_to_el(loc_scroll: bool) {
-        if let Ok(hash) = window().location().hash() {
+    pub(crate) fn with_current(
+        current: Url,
+        hash_routing: bool,
+    ) -> Result<Self, JsValue> {
+        Ok(Self {
+            url: ArcRwSignal::new(current.clone()),
+            pending_navigation: Default::default(),
+            path_stack: ArcStoredValue::new(vec![current]),
+            is_back: Default::default(),
+            blockers: Default::default(),
+            scroll: Default::default(),
+            hash_routing,
+        })
+    }
+
+    fn current_url(&self) -> Result<Url, JsValue> {
+        if self.hash_routing {
+            HashUrl::current()
+        } else {
+            Self::current()
+        }
+    }
+
+    /// The URL that the browser has moved to with a `popstate` event, or `None` if it has moved
+    /// to a hash that is not a route while using hash routing, which leaves the route as it is.
+    fn moved_to_url(&self) -> Result<Option<Url>, JsValue> {
+        if self.hash_routing {
+            HashUrl::current_route()
+        } else {
+            Self::current().map(Some)
+        }
+    }
+
+    fn scroll_to_el(&self, loc_scroll: bool) {
+        if let Ok(Url { hash, .. }) = self.current_url() {
             if !hash.is_empty() {
                 let hash = js_sys::decode_uri(&hash[1..])
                     .ok()
@@ -60,16 +103,7 @@ impl LocationProvider for BrowserUrl {
     type Error = JsValue;
 
     fn new() -> Result<Self, JsValue> {
-        let url = ArcRwSignal::new(Self::current()?);
-        let path_stack = ArcStoredValue::new(
-            Self::current().map(|n| vec![n]).unwrap_or_default(),
-        );
-        Ok(Self {
-            url,
-            pending_navigation: Default::default(),
-            path_stack,
-            is_back: Default::default(),
-        })
+        Self::with_current(Self::current()?, false)
     }
 
     fn as_url(&self) -> &ArcRwSignal<Url> {
@@ -117,13 +151,33 @@ impl LocationProvider for BrowserUrl {
 
     fn init(&self, base: Option<Cow<'static, str>>) {
         let window = window();
+        // scroll positions are restored by the router once the route has loaded, and saved
+        // by the key of each history entry
+        if let Ok(history) = window.history() {
+            _ = history.set_scroll_restoration(ScrollRestoration::Manual);
+            self.scroll.set_current(entry_key(&history));
+        }
+        // the user scrolling stops a position from being restored over their scroll
+        let cancel_restore = {
+            let scroll = Arc::clone(&self.scroll);
+            Closure::wrap(Box::new(move || scroll.cancel()) as Box<dyn Fn()>)
+                .into_js_value()
+        };
+        for event in ["wheel", "touchstart", "keydown"] {
+            _ = window.add_event_listener_with_callback(
+                event,
+                cancel_restore.unchecked_ref(),
+            );
+        }
         let navigate = {
             let url = self.url.clone();
             let pending = Arc::clone(&self.pending_navigation);
             let this = self.clone();
+            let scroll = Arc::clone(&self.scroll);
             move |new_url: Url, loc| {
                 let same_path = {
                     let curr = url.read_untracked();
+                    scroll.save();
                     curr.origin() == new_url.origin()
                         && curr.path() == new_url.path()
                 };
@@ -157,8 +211,31 @@ impl LocationProvider for BrowserUrl {
             }
         };
 
+        // navigations are only started once they have been confirmed by any blockers
+        let navigate = {
+            let blockers = self.blockers.clone();
+            move |new_url: Url, loc| {
+                if blockers.is_blocking() {
+                    let blockers = blockers.clone();
+                    let navigate = navigate.clone();
+                    Either::Left(async move {
+                        if blockers.confirm(&new_url).await {
+                            navigate(new_url, loc).await;
+                        }
+                    })
+                } else {
+                    Either::Right(navigate(new_url, loc))
+                }
+            }
+        };
+
+        let parse_with_base = if self.hash_routing {
+            HashUrl::parse_with_base
+        } else {
+            Self::parse_with_base
+        };
         let handle_anchor_click =
-            handle_anchor_click(base, Self::parse_with_base, navigate);
+            handle_anchor_click(base, parse_with_base, navigate);
         let closure = Closure::wrap(Box::new(move |ev: Event| {
             if let Err(e) = handle_anchor_click(ev) {
                 #[cfg(feature = "tracing")]
@@ -183,15 +260,58 @@ impl LocationProvider for BrowserUrl {
             let url = self.url.clone();
             let path_stack = self.path_stack.clone();
             let is_back = self.is_back.clone();
-            move || match Self::current() {
-                Ok(new_url) => {
+            let this = self.clone();
+            let blockers = self.blockers.clone();
+            let scroll = Arc::clone(&self.scroll);
+            // a popstate event cannot be canceled, so a blocked navigation is undone, and then
+            // redone if it is confirmed
+            let history = window.history().unwrap();
+            let undoing = Rc::new(Cell::new(false));
+            let confirmed = Rc::new(Cell::new(false));
+            move || match this.moved_to_url() {
+                Ok(None) => {}
+                Ok(Some(new_url)) => {
+                    if undoing.replace(false) {
+                        return;
+                    }
                     let stack = path_stack.read_value();
                     let is_navigating_back = stack.len() == 1
                         || (stack.len() >= 2
                             && stack.get(stack.len() - 2) == Some(&new_url));
+                    drop(stack);
+
+                    if !confirmed.replace(false) && blockers.is_blocking() {
+                        let delta = if is_navigating_back { 1 } else { -1 };
+                        undoing.set(true);
+                        _ = history.go_with_delta(delta);
+                        let history = history.clone();
+                        let blockers = blockers.clone();
+                        let confirmed = Rc::clone(&confirmed);
+                        Executor::spawn_local(async move {
+                            if blockers.confirm(&new_url).await {
+                                confirmed.set(true);
+                                _ = history.go_with_delta(-delta);
+                            }
+                        });
+                        return;
+                    }
 
                     is_back.set(is_navigating_back);
 
+                    // the scroll position is restored once the route has loaded, which it
+                    // already has if only the query or hash has changed
+                    let same_path = {
+                        let curr = url.read_untracked();
+                        scroll.save();
+                        curr.path() == new_url.path()
+                    };
+                    let key = entry_key(&history);
+                    scroll.restore_later(&key, &new_url);
+                    scroll.set_current(key);
+                    if same_path {
+                        scroll.restore_pending();
+                    }
+
                     url.set(new_url);
                 }
                 Err(e) => {
@@ -216,36 +336,45 @@ impl LocationProvider for BrowserUrl {
         if let Some(tx) = self.pending_navigation.lock().or_poisoned().take() {
             _ = tx.send(());
         }
+        self.scroll.restore_pending();
     }
 
     fn complete_navigation(&self, loc: &LocationChange) {
         let history = window().history().unwrap();
+        let value = if self.hash_routing {
+            Cow::Owned(format!("#{}", loc.value))
+        } else {
+            Cow::Borrowed(loc.value.as_str())
+        };
 
+        // the state is stored along with the key of the entry, which its scroll position is
+        // saved by
         if loc.replace {
+            let key = self.scroll.current().unwrap_or_else(new_entry_key);
+            let state = with_entry_key(&key, loc.state.to_js_value());
             history
-                .replace_state_with_url(
-                    &loc.state.to_js_value(),
-                    "",
-                    Some(&loc.value),
-                )
+                .replace_state_with_url(&state, "", Some(&value))
                 .unwrap();
+            self.scroll.set_current(key);
         } else {
             // push the "forward direction" marker
-            let state = &loc.state.to_js_value();
+            let key = new_entry_key();
+            let state = with_entry_key(&key, loc.state.to_js_value());
             history
-                .push_state_with_url(state, "", Some(&loc.value))
+                .push_state_with_url(&state, "", Some(&value))
                 .unwrap();
+            self.scroll.set_current(key);
         }
 
         // add this URL to the "path stack" for detecting back navigations, and
         // unset "navigating back" state
-        if let Ok(url) = Self::current() {
+        if let Ok(url) = self.current_url() {
             self.path_stack.write_value().push(url);
             self.is_back.set(false);
         }
 
         // scroll to el
-        Self::scroll_to_el(loc.scroll);
+        self.scroll_to_el(loc.scroll);
     }
 
     fn redirect(loc: &str) {
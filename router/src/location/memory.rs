@@ -0,0 +1,557 @@
+use super::{LocationChange, LocationProvider, State, Url, BASE};
+use crate::{blocker::Blockers, hooks::use_navigate, params::ParamsMap};
+use any_spawner::Executor;
+use core::fmt;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    signal::{ArcRwSignal, ReadSignal},
+    traits::{ReadUntracked, Set},
+};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+/// A [`LocationProvider`] that keeps its history in memory, rather than in the browser.
+///
+/// This allows the router to be used where there is no browser history, like in tests, in
+/// native or webview shells, or when previewing a page on the server. It is used by the
+/// [`Router`](crate::components::Router) if it is provided via context:
+///
+/// ```rust,ignore
+/// let history = MemoryHistory::with_url("/users/1").unwrap();
+/// provide_context(history.clone());
+/// let html = view! { <App/> }.to_html();
+///
+/// history.push("/users/2");
+/// history.back();
+/// assert_eq!(history.current_path(), "/users/1");
+/// ```
+///
+/// Navigations made with [`use_navigate`] or with server function redirects are added to the
+/// history, but clicks on `<a>` elements are not intercepted. Like those navigations, moving
+/// through the history with [`push`](Self::push), [`back`](Self::back) and the other methods
+/// waits for any [`use_blocker`](crate::hooks::use_blocker) to confirm it.
+#[derive(Clone)]
+pub struct MemoryHistory {
+    url: ArcRwSignal<Url>,
+    stack: Arc<Mutex<HistoryStack>>,
+    is_back: ArcRwSignal<bool>,
+    pub(crate) blockers: Blockers,
+}
+
+#[derive(Debug)]
+struct HistoryStack {
+    entries: Vec<(Url, State)>,
+    index: usize,
+}
+
+impl fmt::Debug for MemoryHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryHistory")
+            .field("entries", &self.entries())
+            .field("index", &self.index())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryHistory {
+    /// Creates a history with a single entry for the given URL.
+    pub fn with_url(url: &str) -> Result<Self, url::ParseError> {
+        let url = Self::parse(url)?;
+        Ok(Self {
+            url: ArcRwSignal::new(url.clone()),
+            stack: Arc::new(Mutex::new(HistoryStack {
+                entries: vec![(url, State::default())],
+                index: 0,
+            })),
+            is_back: Default::default(),
+            blockers: Default::default(),
+        })
+    }
+
+    /// Adds a new entry for the given URL after the current one, removing any entries that
+    /// could have been reached by going forward.
+    pub fn push(&self, url: &str) {
+        self.navigate_blocked(url, false);
+    }
+
+    /// Replaces the current entry with the given URL.
+    pub fn replace(&self, url: &str) {
+        self.navigate_blocked(url, true);
+    }
+
+    /// Goes back one entry, if there is one.
+    pub fn back(&self) {
+        self.go(-1);
+    }
+
+    /// Goes forward one entry, if there is one.
+    pub fn forward(&self) {
+        self.go(1);
+    }
+
+    /// Moves by the given number of entries, backward if it is negative. Like the browser's
+    /// [`History.go`](https://developer.mozilla.org/en-US/docs/Web/API/History/go), this does
+    /// nothing if there is no entry at that position.
+    pub fn go(&self, delta: isize) {
+        let Some((_, url)) = self.entry_at(delta) else {
+            return;
+        };
+        self.when_confirmed(&url, move |this| {
+            let Some((index, url)) = this.entry_at(delta) else {
+                return;
+            };
+            this.stack.lock().or_poisoned().index = index;
+            this.is_back.set(delta < 0);
+            this.url.set(url);
+        });
+    }
+
+    /// Whether there is an entry before the current one.
+    pub fn can_go_back(&self) -> bool {
+        self.index() > 0
+    }
+
+    /// Whether there is an entry after the current one.
+    pub fn can_go_forward(&self) -> bool {
+        let stack = self.stack.lock().or_poisoned();
+        stack.index + 1 < stack.entries.len()
+    }
+
+    /// The position of the current entry in the history.
+    pub fn index(&self) -> usize {
+        self.stack.lock().or_poisoned().index
+    }
+
+    /// The path, query and hash of each entry in the history, from oldest to newest.
+    pub fn entries(&self) -> Vec<String> {
+        self.stack
+            .lock()
+            .or_poisoned()
+            .entries
+            .iter()
+            .map(|(url, _)| url.to_full_path())
+            .collect()
+    }
+
+    /// The path, query and hash of the current entry.
+    pub fn current_path(&self) -> String {
+        let stack = self.stack.lock().or_poisoned();
+        stack.entries[stack.index].0.to_full_path()
+    }
+
+    /// The [`State`] that was added with the current entry.
+    pub fn state(&self) -> State {
+        let stack = self.stack.lock().or_poisoned();
+        stack.entries[stack.index].1.clone()
+    }
+
+    /// The index and URL of the entry that is `delta` entries away from the current one.
+    fn entry_at(&self, delta: isize) -> Option<(usize, Url)> {
+        let stack = self.stack.lock().or_poisoned();
+        let index = stack
+            .index
+            .checked_add_signed(delta)
+            .filter(|index| *index < stack.entries.len() && delta != 0)?;
+        Some((index, stack.entries[index].0.clone()))
+    }
+
+    /// Runs `navigate` once the blockers have confirmed a navigation to `url`, which is right
+    /// away if none of them is blocking.
+    fn when_confirmed(
+        &self,
+        url: &Url,
+        navigate: impl FnOnce(&Self) + 'static,
+    ) {
+        if !self.blockers.is_blocking() {
+            return navigate(self);
+        }
+        let this = self.clone();
+        let url = url.clone();
+        Executor::spawn_local(async move {
+            if this.blockers.confirm(&url).await {
+                navigate(&this);
+            }
+        });
+    }
+
+    fn navigate_blocked(&self, url: &str, replace: bool) {
+        match Self::parse(url) {
+            Ok(parsed) => {
+                let url = url.to_string();
+                self.when_confirmed(&parsed, move |this| {
+                    this.navigate(&url, replace, State::default())
+                });
+            }
+            Err(e) => {
+                leptos::logging::error!("Error parsing URL {url:?}: {e}")
+            }
+        }
+    }
+
+    fn navigate(&self, url: &str, replace: bool, state: State) {
+        let url = match Self::parse(url) {
+            Ok(url) => url,
+            Err(e) => {
+                leptos::logging::error!("Error parsing URL {url:?}: {e}");
+                return;
+            }
+        };
+        {
+            let mut stack = self.stack.lock().or_poisoned();
+            if replace {
+                let index = stack.index;
+                stack.entries[index] = (url.clone(), state);
+            } else {
+                let index = stack.index + 1;
+                stack.entries.truncate(index);
+                stack.entries.push((url.clone(), state));
+                stack.index = index;
+            }
+        }
+        self.is_back.set(false);
+        // the router may already have updated the URL before completing the navigation
+        if *self.url.read_untracked() != url {
+            self.url.set(url);
+        }
+    }
+}
+
+impl LocationProvider for MemoryHistory {
+    type Error = url::ParseError;
+
+    fn new() -> Result<Self, Self::Error> {
+        Self::with_url("/")
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        &self.url
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        // there is no global history to read from, so this is the starting URL
+        Self::parse("/")
+    }
+
+    fn init(&self, _base: Option<Cow<'static, str>>) {}
+
+    fn ready_to_complete(&self) {}
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        self.navigate(&loc.value, loc.replace, loc.state.clone());
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        let base = url::Url::parse(base)?;
+        let url = url::Url::options().base_url(Some(&base)).parse(url)?;
+
+        let search_params = url
+            .query_pairs()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<ParamsMap>();
+
+        Ok(Url {
+            origin: url.origin().unicode_serialization(),
+            path: url.path().to_string(),
+            search: url.query().unwrap_or_default().to_string(),
+            search_params,
+            // like the browser's `Location.hash`, this includes the `#`
+            hash: url
+                .fragment()
+                .filter(|hash| !hash.is_empty())
+                .map(|hash| format!("#{hash}"))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn redirect(loc: &str) {
+        match Self::parse(loc) {
+            Ok(url) if url.origin() == BASE => {
+                let navigate = use_navigate();
+                navigate(&url.to_full_path(), Default::default());
+            }
+            Ok(_) => leptos::logging::error!(
+                "Cannot redirect to {loc:?}, as it is on another origin."
+            ),
+            Err(e) => {
+                leptos::logging::error!("Invalid redirect location: {e}")
+            }
+        }
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.is_back.read_only().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryHistory;
+    use crate::{
+        components::{FlatRoutes, Route, Router, RouterContext, Routes, A},
+        hooks::{use_blocker, use_navigate},
+        location::LocationProvider,
+        StaticSegment,
+    };
+    use any_spawner::Executor;
+    use hydration_context::SsrSharedContext;
+    use leptos::prelude::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    fn history() -> MemoryHistory {
+        MemoryHistory::with_url("/a").unwrap()
+    }
+
+    #[test]
+    fn parses_paths_queries_and_hashes() {
+        let history =
+            MemoryHistory::with_url("/users/1?tab=posts#top").unwrap();
+        let url = history.as_url().get_untracked();
+        assert_eq!(url.path(), "/users/1");
+        assert_eq!(url.search(), "tab=posts");
+        assert_eq!(url.search_params().get_str("tab"), Some("posts"));
+        assert_eq!(url.hash(), "#top");
+        assert_eq!(history.current_path(), "/users/1?tab=posts#top");
+    }
+
+    #[test]
+    fn pushing_removes_forward_entries() {
+        let history = history();
+        history.push("/b");
+        history.push("/c");
+        history.back();
+        history.back();
+        assert_eq!(history.current_path(), "/a");
+        assert!(history.can_go_forward());
+
+        history.push("/d");
+        assert_eq!(history.entries(), ["/a", "/d"]);
+        assert_eq!(history.index(), 1);
+        assert!(!history.can_go_forward());
+    }
+
+    #[test]
+    fn replacing_keeps_position() {
+        let history = history();
+        history.push("/b");
+        history.replace("/c");
+        assert_eq!(history.entries(), ["/a", "/c"]);
+        assert_eq!(history.as_url().get_untracked().path(), "/c");
+    }
+
+    #[test]
+    fn tracks_back_navigations() {
+        let owner = Owner::new();
+        owner.set();
+        let history = history();
+        history.push("/b");
+        assert!(!history.is_back().get_untracked());
+
+        history.back();
+        assert!(history.is_back().get_untracked());
+        assert_eq!(history.as_url().get_untracked().path(), "/a");
+        assert!(!history.can_go_back());
+
+        // going past either end does nothing
+        history.back();
+        assert_eq!(history.index(), 0);
+
+        history.forward();
+        assert!(!history.is_back().get_untracked());
+        assert_eq!(history.as_url().get_untracked().path(), "/b");
+        history.go(5);
+        assert_eq!(history.index(), 1);
+    }
+
+    #[test]
+    fn renders_routes_for_the_current_entry() {
+        let owner = Owner::new();
+        owner.set();
+        let history = history();
+        provide_context(history.clone());
+
+        let navigate = StoredValue::new_local(None);
+        let app = move || {
+            view! {
+                <Router>
+                    <nav>
+                        <A href="/a">"A"</A>
+                        <A href="/b">"B"</A>
+                    </nav>
+                    <Routes fallback=|| "Not found.">
+                        <Route path=StaticSegment("a") view=move || {
+                            navigate.set_value(Some(use_navigate()));
+                            "Page A"
+                        }/>
+                        <Route path=StaticSegment("b") view=|| "Page B"/>
+                    </Routes>
+                </Router>
+            }
+            .to_html()
+        };
+
+        let html = app();
+        assert!(html.contains("Page A"));
+        assert!(html.contains(r#"<a href="/a" aria-current="page">"#));
+
+        navigate.with_value(|navigate| {
+            navigate.as_ref().unwrap()("/b", Default::default())
+        });
+        assert_eq!(history.entries(), ["/a", "/b"]);
+        let html = app();
+        assert!(html.contains("Page B"));
+        assert!(html.contains(r#"<a href="/b" aria-current="page">"#));
+
+        history.back();
+        assert!(history.is_back().get_untracked());
+        assert!(app().contains("Page A"));
+    }
+
+    #[test]
+    fn each_routes_prefetches_until_cleaned_up() {
+        let owner = Owner::new();
+        owner.set();
+        provide_context(history());
+
+        let router = StoredValue::new_local(None::<RouterContext>);
+        let app = Owner::new();
+        app.with(|| {
+            view! {
+                <Router>
+                    <Routes fallback=|| "Not found.">
+                        <Route path=StaticSegment("a") view=move || {
+                            router.set_value(use_context::<RouterContext>());
+                            "Page A"
+                        }/>
+                    </Routes>
+                    <FlatRoutes fallback=|| "Not found.">
+                        <Route path=StaticSegment("b") view=|| "Page B"/>
+                    </FlatRoutes>
+                </Router>
+            }
+            .to_html()
+        });
+
+        let prefetch = router
+            .with_value(|router| router.as_ref().unwrap().prefetch.clone());
+        assert_eq!(prefetch.read_value().len(), 2);
+        app.cleanup();
+        assert!(prefetch.read_value().is_empty());
+    }
+
+    #[test]
+    fn renders_flat_routes_and_reports_back_navigations() {
+        let owner = Owner::new();
+        owner.set();
+        let history = history();
+        provide_context(history.clone());
+
+        let router = StoredValue::new_local(None);
+        let app = move || {
+            view! {
+                <Router>
+                    <A href="/b">"B"</A>
+                    <FlatRoutes fallback=|| "Not found.">
+                        <Route path=StaticSegment("a") view=move || {
+                            router.set_value(use_context::<RouterContext>());
+                            "Page A"
+                        }/>
+                        <Route path=StaticSegment("b") view=|| "Page B"/>
+                    </FlatRoutes>
+                </Router>
+            }
+            .to_html()
+        };
+
+        let html = app();
+        assert!(html.contains("Page A"));
+        assert!(!html.contains("aria-current"));
+
+        let router = router.get_value().unwrap();
+        router.navigate("/b", Default::default());
+        assert_eq!(history.entries(), ["/a", "/b"]);
+        let html = app();
+        assert!(html.contains("Page B"));
+        assert!(html.contains(r#"<a href="/b" aria-current="page">"#));
+
+        // the routes see the history's back navigations, to run their view transitions
+        let location = router.location_provider.unwrap();
+        assert!(!location.is_back().get_untracked());
+        history.back();
+        assert!(location.is_back().get_untracked());
+        assert!(app().contains("Page A"));
+
+        history.push("/c");
+        assert!(app().contains("Not found."));
+    }
+
+    #[test]
+    fn blockers_veto_and_confirm_navigations() {
+        _ = Executor::init_futures_executor();
+        // the blocker's `beforeunload` listener is only skipped on the server
+        let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
+        owner.set();
+        let history = history();
+        provide_context(history.clone());
+
+        let confirm = Arc::new(AtomicBool::new(false));
+        let navigate = StoredValue::new_local(None);
+        let page_a = {
+            let confirm = Arc::clone(&confirm);
+            move || {
+                let confirm = Arc::clone(&confirm);
+                use_blocker(
+                    || true,
+                    move |_| {
+                        let confirmed = confirm.load(Ordering::Relaxed);
+                        async move { confirmed }
+                    },
+                );
+                navigate.set_value(Some(use_navigate()));
+                "Page A"
+            }
+        };
+        let html = view! {
+            <Router>
+                <Routes fallback=|| "Not found.">
+                    <Route path=StaticSegment("a") view=page_a/>
+                    <Route path=StaticSegment("b") view=|| "Page B"/>
+                </Routes>
+            </Router>
+        }
+        .to_html();
+        assert!(html.contains("Page A"));
+        let navigate = move |path: &str| {
+            navigate.with_value(|navigate| {
+                navigate.as_ref().unwrap()(path, Default::default())
+            });
+            Executor::poll_local();
+        };
+
+        navigate("/b");
+        history.push("/b");
+        Executor::poll_local();
+        assert_eq!(history.entries(), ["/a"]);
+        assert_eq!(history.as_url().get_untracked().path(), "/a");
+
+        confirm.store(true, Ordering::Relaxed);
+        navigate("/b");
+        assert_eq!(history.entries(), ["/a", "/b"]);
+        assert_eq!(history.as_url().get_untracked().path(), "/b");
+
+        confirm.store(false, Ordering::Relaxed);
+        history.back();
+        Executor::poll_local();
+        assert_eq!(history.index(), 1);
+
+        confirm.store(true, Ordering::Relaxed);
+        history.back();
+        Executor::poll_local();
+        assert_eq!(history.index(), 0);
+        assert_eq!(history.as_url().get_untracked().path(), "/a");
+    }
+}
@@ -16,10 +16,16 @@ use tachys::dom::window;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{Event, HtmlAnchorElement, MouseEvent};
 
+mod hash;
 mod history;
+mod memory;
+mod scroll;
 mod server;
 use crate::params::ParamsMap;
+pub use hash::*;
 pub use history::*;
+pub use memory::*;
+pub(crate) use scroll::*;
 pub use server::*;
 
 pub(crate) const BASE: &str = "https://leptos.dev";
@@ -91,18 +97,20 @@ impl Url {
             path.push_str(&self.search);
         }
         if !self.hash.is_empty() {
-            path.push('#');
+            if !self.hash.starts_with('#') {
+                path.push('#');
+            }
             path.push_str(&self.hash);
         }
         path
     }
 
     pub fn escape(s: &str) -> String {
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(target_arch = "wasm32", not(feature = "ssr")))]
         {
             js_sys::encode_uri_component(s).as_string().unwrap()
         }
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             percent_encoding::utf8_percent_encode(
                 s,
@@ -113,7 +121,7 @@ impl Url {
     }
 
     pub fn unescape(s: &str) -> String {
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             percent_encoding::percent_decode_str(s)
                 .decode_utf8()
@@ -121,19 +129,19 @@ impl Url {
                 .to_string()
         }
 
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(target_arch = "wasm32", not(feature = "ssr")))]
         {
             js_sys::decode_uri_component(s).unwrap().into()
         }
     }
 
     pub fn unescape_minimal(s: &str) -> String {
-        #[cfg(not(feature = "ssr"))]
+        #[cfg(all(target_arch = "wasm32", not(feature = "ssr")))]
         {
             js_sys::decode_uri(s).unwrap().into()
         }
 
-        #[cfg(feature = "ssr")]
+        #[cfg(any(feature = "ssr", not(target_arch = "wasm32")))]
         {
             Self::unescape(s)
         }
@@ -234,6 +242,82 @@ pub trait LocationProvider: Clone + 'static {
     fn is_back(&self) -> ReadSignal<bool>;
 }
 
+/// The location provider used by a [`Router`](crate::components::Router): the browser's
+/// history (with either path or hash routing), or a [`MemoryHistory`] provided via context.
+///
+/// Its associated functions use the browser's location, as a [`MemoryHistory`] is only ever
+/// used through an instance.
+#[derive(Debug, Clone)]
+pub(crate) enum RouterLocation {
+    Browser(BrowserUrl),
+    Memory(MemoryHistory),
+}
+
+impl RouterLocation {
+    /// The browser history, if this is not an in-memory history.
+    pub fn browser(&self) -> Option<&BrowserUrl> {
+        match self {
+            Self::Browser(location) => Some(location),
+            Self::Memory(_) => None,
+        }
+    }
+}
+
+impl LocationProvider for RouterLocation {
+    type Error = JsValue;
+
+    fn new() -> Result<Self, Self::Error> {
+        BrowserUrl::new().map(Self::Browser)
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        match self {
+            Self::Browser(location) => location.as_url(),
+            Self::Memory(history) => history.as_url(),
+        }
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        BrowserUrl::current()
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        match self {
+            Self::Browser(location) => location.init(base),
+            Self::Memory(history) => history.init(base),
+        }
+    }
+
+    fn ready_to_complete(&self) {
+        match self {
+            Self::Browser(location) => location.ready_to_complete(),
+            Self::Memory(history) => history.ready_to_complete(),
+        }
+    }
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        match self {
+            Self::Browser(location) => location.complete_navigation(loc),
+            Self::Memory(history) => history.complete_navigation(loc),
+        }
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        BrowserUrl::parse_with_base(url, base)
+    }
+
+    fn redirect(loc: &str) {
+        BrowserUrl::redirect(loc);
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        match self {
+            Self::Browser(location) => location.is_back(),
+            Self::Memory(history) => history.is_back(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct State(Option<SendWrapper<JsValue>>);
 
@@ -319,7 +403,10 @@ where
                 return Ok(());
             }
 
-            let url = parse_with_base(href.as_str(), &origin).unwrap();
+            // let browser handle this event if the link is not to a route
+            let Ok(url) = parse_with_base(href.as_str(), &origin) else {
+                return Ok(());
+            };
             let path_name = Url::unescape_minimal(&url.path);
 
             // let browser handle this event if it leaves our domain
@@ -0,0 +1,349 @@
+use super::Url;
+use js_sys::{Object, Reflect};
+use leptos::prelude::request_animation_frame;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{computed::ArcMemo, traits::GetUntracked};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tachys::dom::{document, window};
+use wasm_bindgen::JsValue;
+use web_sys::History;
+
+/// How many animation frames a scroll position is restored for, while the content of the page
+/// it was saved for is still loading.
+const RESTORE_FRAMES: u8 = 60;
+
+/// The property of `history.state` that holds the key of a history entry.
+const ENTRY_KEY: &str = "leptosEntryKey";
+
+/// The property of `history.state` that holds the state added with the navigation.
+const ENTRY_STATE: &str = "state";
+
+static OPT_OUT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The scroll positions of the window and of the scrollable containers on a page.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SavedScroll {
+    window: (f64, f64),
+    containers: Vec<Option<(i32, i32)>>,
+}
+
+/// Keeps track of the scroll position of each history entry, so that it can be restored when
+/// the user goes back or forward to it.
+///
+/// Positions are saved by the key of their history entry, rather than by URL, so that two
+/// entries for the same URL each keep their own position.
+#[derive(Debug, Default)]
+pub(crate) struct ScrollPositions {
+    positions: Mutex<HashMap<String, SavedScroll>>,
+    /// The key of the history entry that is currently shown.
+    current: Mutex<Option<String>>,
+    /// The position to restore once the route has loaded, and the path it is restored on.
+    pending: Mutex<Option<(String, SavedScroll)>>,
+    /// Changed whenever a position that is being restored should no longer be.
+    generation: AtomicUsize,
+    containers: Mutex<&'static [&'static str]>,
+    /// The paths matched by routes that restore their own scroll positions, or `None` for an
+    /// opt-out outside any route, which applies to every route.
+    opt_outs: Mutex<Vec<(usize, Option<ArcMemo<String>>)>>,
+}
+
+impl ScrollPositions {
+    /// Sets the CSS selectors for the scrollable containers whose positions are restored, in
+    /// addition to the window's.
+    pub fn set_containers(&self, containers: &'static [&'static str]) {
+        *self.containers.lock().or_poisoned() = containers;
+    }
+
+    /// Sets the key of the history entry that is now shown.
+    pub fn set_current(&self, key: String) {
+        *self.current.lock().or_poisoned() = Some(key);
+    }
+
+    /// The key of the history entry that is currently shown, if it has one.
+    pub fn current(&self) -> Option<String> {
+        self.current.lock().or_poisoned().clone()
+    }
+
+    /// Saves the current scroll position for the current history entry, before navigating
+    /// away from it.
+    pub fn save(&self) {
+        let window = window();
+        let saved = SavedScroll {
+            window: (
+                window.scroll_x().unwrap_or_default(),
+                window.scroll_y().unwrap_or_default(),
+            ),
+            containers: self
+                .containers
+                .lock()
+                .or_poisoned()
+                .iter()
+                .map(|selector| {
+                    container(selector)
+                        .map(|el| (el.scroll_left(), el.scroll_top()))
+                })
+                .collect(),
+        };
+        self.save_position(saved);
+    }
+
+    fn save_position(&self, saved: SavedScroll) {
+        if let Some(key) = self.current() {
+            self.positions.lock().or_poisoned().insert(key, saved);
+        }
+        // a new navigation replaces any that had not finished restoring
+        self.pending.lock().or_poisoned().take();
+        self.cancel();
+    }
+
+    /// Restores the position saved for the history entry with the given key, once
+    /// [`restore_pending`](Self::restore_pending) is called after the route at `url` has
+    /// loaded.
+    pub fn restore_later(&self, key: &str, url: &Url) {
+        let saved = self.positions.lock().or_poisoned().get(key).cloned();
+        *self.pending.lock().or_poisoned() =
+            saved.map(|saved| (url.path().to_string(), saved));
+    }
+
+    /// Restores the position for a back or forward navigation, if there is one.
+    pub fn restore_pending(self: &Arc<Self>) {
+        if let Some((generation, saved)) = self.take_pending() {
+            self.restore(generation, saved, RESTORE_FRAMES);
+        }
+    }
+
+    /// Takes the pending position, unless the route it is restored on has opted out, along
+    /// with the generation that restoring it belongs to.
+    fn take_pending(&self) -> Option<(usize, SavedScroll)> {
+        let (path, saved) = self.pending.lock().or_poisoned().take()?;
+        if self.is_opted_out(&path) {
+            return None;
+        }
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        Some((generation, saved))
+    }
+
+    fn restore(
+        self: &Arc<Self>,
+        generation: usize,
+        saved: SavedScroll,
+        frames: u8,
+    ) {
+        if self.generation.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        let window = window();
+        window.scroll_to_with_x_and_y(saved.window.0, saved.window.1);
+        let mut restored = window.scroll_x().ok() == Some(saved.window.0)
+            && window.scroll_y().ok() == Some(saved.window.1);
+        let containers = *self.containers.lock().or_poisoned();
+        for (selector, position) in containers.iter().zip(&saved.containers) {
+            if let Some((left, top)) = *position {
+                match container(selector) {
+                    Some(el) => {
+                        el.set_scroll_left(left);
+                        el.set_scroll_top(top);
+                        restored &=
+                            el.scroll_left() == left && el.scroll_top() == top;
+                    }
+                    None => restored = false,
+                }
+            }
+        }
+
+        // the page may not be tall enough yet if its content is still loading, so this is
+        // retried until the position is reached, the content has had time to load, or the
+        // restore is canceled
+        if !restored && frames > 0 {
+            let this = Arc::clone(self);
+            request_animation_frame(move || {
+                this.restore(generation, saved, frames - 1)
+            });
+        }
+    }
+
+    /// Stops restoring a position, because the user has started scrolling or navigated again.
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Stops scroll positions from being restored on the path that `matched` contains, or on
+    /// every path if it is `None`, until [`opt_in`](Self::opt_in) is called with the returned
+    /// ID.
+    pub fn opt_out(&self, matched: Option<ArcMemo<String>>) -> usize {
+        let id = OPT_OUT_ID.fetch_add(1, Ordering::Relaxed);
+        self.opt_outs.lock().or_poisoned().push((id, matched));
+        id
+    }
+
+    pub fn opt_in(&self, id: usize) {
+        self.opt_outs
+            .lock()
+            .or_poisoned()
+            .retain(|(opt_out, _)| *opt_out != id);
+    }
+
+    fn is_opted_out(&self, path: &str) -> bool {
+        self.opt_outs
+            .lock()
+            .or_poisoned()
+            .iter()
+            .any(|(_, matched)| {
+                matched
+                    .as_ref()
+                    .map(|matched| is_within(path, &matched.get_untracked()))
+                    .unwrap_or(true)
+            })
+    }
+}
+
+/// Whether `path` is the route path `matched`, or a path nested inside it.
+fn is_within(path: &str, matched: &str) -> bool {
+    let matched = matched.trim_end_matches('/');
+    path.strip_prefix(matched)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Returns the key of the current history entry, adding one to its state if it has none, like
+/// when the page has just been loaded or the browser has followed a link to an element.
+pub(crate) fn entry_key(history: &History) -> String {
+    let state = history.state().unwrap_or(JsValue::UNDEFINED);
+    if let Some(key) = Reflect::get(&state, &JsValue::from_str(ENTRY_KEY))
+        .ok()
+        .and_then(|key| key.as_string())
+    {
+        return key;
+    }
+    let key = new_entry_key();
+    _ = history.replace_state(&with_entry_key(&key, state), "");
+    key
+}
+
+/// Creates a key for a new history entry, which is unique across page loads.
+pub(crate) fn new_entry_key() -> String {
+    format!(
+        "{:x}-{:x}",
+        js_sys::Date::now() as u64,
+        (js_sys::Math::random() * f64::from(u32::MAX)) as u32
+    )
+}
+
+/// Wraps the state of a history entry in an object that also holds its key.
+pub(crate) fn with_entry_key(key: &str, state: JsValue) -> JsValue {
+    let wrapped = Object::new();
+    _ = Reflect::set(&wrapped, &ENTRY_KEY.into(), &key.into());
+    _ = Reflect::set(&wrapped, &ENTRY_STATE.into(), &state);
+    wrapped.into()
+}
+
+fn container(selector: &str) -> Option<web_sys::Element> {
+    document().query_selector(selector).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_within, SavedScroll, ScrollPositions};
+    use crate::location::{LocationProvider, MemoryHistory, Url};
+    use reactive_graph::computed::ArcMemo;
+    use std::sync::atomic::Ordering;
+
+    fn at(y: f64) -> SavedScroll {
+        SavedScroll {
+            window: (0.0, y),
+            containers: vec![Some((0, y as i32)), None],
+        }
+    }
+
+    fn url(path: &str) -> Url {
+        MemoryHistory::parse(path).unwrap()
+    }
+
+    #[test]
+    fn saves_positions_by_history_entry() {
+        let scroll = ScrollPositions::default();
+        // nothing is saved for an entry without a key
+        scroll.save_position(at(10.0));
+        assert!(scroll.positions.lock().unwrap().is_empty());
+
+        scroll.set_current("first".into());
+        scroll.save_position(at(100.0));
+        scroll.set_current("second".into());
+        scroll.save_position(at(200.0));
+
+        // both entries are for the same URL, but keep their own positions
+        scroll.restore_later("first", &url("/a"));
+        assert_eq!(scroll.take_pending().map(|(_, s)| s), Some(at(100.0)));
+        scroll.restore_later("second", &url("/a"));
+        assert_eq!(scroll.take_pending().map(|(_, s)| s), Some(at(200.0)));
+        scroll.restore_later("third", &url("/a"));
+        assert_eq!(scroll.take_pending(), None);
+    }
+
+    #[test]
+    fn a_new_navigation_replaces_the_pending_position() {
+        let scroll = ScrollPositions::default();
+        scroll.set_current("first".into());
+        scroll.save_position(at(100.0));
+        scroll.restore_later("first", &url("/a"));
+        scroll.save_position(at(50.0));
+        assert_eq!(scroll.take_pending(), None);
+        assert_eq!(scroll.positions.lock().unwrap()["first"], at(50.0));
+    }
+
+    #[test]
+    fn canceling_ends_the_current_restore() {
+        let scroll = ScrollPositions::default();
+        scroll.set_current("first".into());
+        scroll.save_position(at(100.0));
+
+        scroll.restore_later("first", &url("/a"));
+        let (generation, _) = scroll.take_pending().unwrap();
+        assert_eq!(scroll.generation.load(Ordering::Relaxed), generation);
+        scroll.cancel();
+        assert_ne!(scroll.generation.load(Ordering::Relaxed), generation);
+
+        // each restore has its own generation
+        scroll.restore_later("first", &url("/a"));
+        let (next, _) = scroll.take_pending().unwrap();
+        assert_ne!(next, generation);
+    }
+
+    #[test]
+    fn opt_outs_only_apply_to_their_routes() {
+        let scroll = ScrollPositions::default();
+        scroll.set_current("first".into());
+        scroll.save_position(at(100.0));
+
+        let id = scroll.opt_out(Some(ArcMemo::new(|_| "/users".to_string())));
+        scroll.restore_later("first", &url("/users/1"));
+        assert_eq!(scroll.take_pending(), None);
+        scroll.restore_later("first", &url("/posts"));
+        assert!(scroll.take_pending().is_some());
+
+        scroll.opt_in(id);
+        scroll.restore_later("first", &url("/users/1"));
+        assert!(scroll.take_pending().is_some());
+
+        let id = scroll.opt_out(None);
+        scroll.restore_later("first", &url("/posts"));
+        assert_eq!(scroll.take_pending(), None);
+        scroll.opt_in(id);
+    }
+
+    #[test]
+    fn matches_nested_paths() {
+        assert!(is_within("/users", "/users"));
+        assert!(is_within("/users/1", "/users"));
+        assert!(is_within("/users/1", "/users/"));
+        assert!(is_within("/users/1", "/"));
+        assert!(is_within("/users/1", ""));
+        assert!(!is_within("/users2", "/users"));
+        assert!(!is_within("/posts", "/users"));
+    }
+}
@@ -0,0 +1,208 @@
+use super::{ParamSegment, PartialPathMatch, PathSegment, PossibleRouteMatch};
+use std::{fmt::Debug, sync::Arc};
+
+/// A check on the value of a [`ConstrainedSegment`], which must pass for its route to match.
+///
+/// This is implemented for [`IntegerParam`], [`UuidParam`], [`OneOfParam`] and (with the
+/// `regex` feature) `RegexParam`, and can be implemented to match values in other ways.
+pub trait ParamConstraint: Debug + Send + Sync + 'static {
+    /// Whether the value of the param, after percent-decoding, is valid.
+    fn accepts(&self, value: &str) -> bool;
+}
+
+/// Only matches integers, with an optional leading `-`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntegerParam;
+
+impl ParamConstraint for IntegerParam {
+    fn accepts(&self, value: &str) -> bool {
+        let digits = value.strip_prefix('-').unwrap_or(value);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    }
+}
+
+/// Only matches hyphenated UUIDs, like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UuidParam;
+
+impl ParamConstraint for UuidParam {
+    fn accepts(&self, value: &str) -> bool {
+        let groups = value.split('-').map(str::len).collect::<Vec<_>>();
+        groups == [8, 4, 4, 4, 12]
+            && value.bytes().all(|b| b == b'-' || b.is_ascii_hexdigit())
+    }
+}
+
+/// Only matches one of a list of values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OneOfParam(pub &'static [&'static str]);
+
+impl ParamConstraint for OneOfParam {
+    fn accepts(&self, value: &str) -> bool {
+        self.0.contains(&value)
+    }
+}
+
+/// Only matches values that match a regular expression as a whole.
+///
+/// The expression is compiled the first time it is used, and an invalid expression does not
+/// match anything.
+#[cfg(feature = "regex")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RegexParam(pub &'static str);
+
+#[cfg(feature = "regex")]
+impl ParamConstraint for RegexParam {
+    fn accepts(&self, value: &str) -> bool {
+        use once_cell::sync::Lazy;
+        use or_poisoned::OrPoisoned;
+        use std::{collections::HashMap, sync::Mutex};
+
+        static COMPILED: Lazy<
+            Mutex<HashMap<&'static str, Option<regex::Regex>>>,
+        > = Lazy::new(Default::default);
+
+        COMPILED
+            .lock()
+            .or_poisoned()
+            .entry(self.0)
+            .or_insert_with(|| {
+                regex::Regex::new(&format!("^(?:{})$", self.0))
+                    .inspect_err(|e| {
+                        leptos::logging::error!(
+                            "Invalid param pattern {:?}: {e}",
+                            self.0
+                        )
+                    })
+                    .ok()
+            })
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(value))
+    }
+}
+
+/// A segment that captures a value from the url and maps it to a key, like [`ParamSegment`],
+/// but only matches if the value is accepted by a [`ParamConstraint`].
+///
+/// This allows routes like `/users/:id` and `/users/new` to be defined in any order, as only
+/// one of them can match a given URL.
+///
+/// # Examples
+/// ```rust
+/// # (|| -> Option<()> { // Option does not impl Terminate, so no main
+/// use leptos::prelude::*;
+/// use leptos_router::{
+///     path, ConstrainedSegment, IntegerParam, PossibleRouteMatch,
+///     StaticSegment,
+/// };
+///
+/// // Manual definition
+/// let manual = (StaticSegment("users"), ConstrainedSegment("id", IntegerParam));
+/// let params = manual.test("/users/42")?.params();
+/// assert_eq!(params.last()?.1, "42");
+/// assert!(manual.test("/users/new").is_none());
+///
+/// // Macro definition
+/// let using_macro = path!("/users/:id<int>");
+/// assert!(using_macro.test("/users/42").is_some());
+/// assert!(using_macro.test("/users/new").is_none());
+/// # Some(())
+/// # })().unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConstrainedSegment<C>(pub &'static str, pub C);
+
+impl<C> PossibleRouteMatch for ConstrainedSegment<C>
+where
+    C: ParamConstraint + Clone,
+{
+    fn test<'a>(&self, path: &'a str) -> Option<PartialPathMatch<'a>> {
+        let matched = ParamSegment(self.0).test(path)?;
+        let (_, value) = matched.params.first()?;
+        let value = percent_encoding::percent_decode_str(value).decode_utf8();
+        match value {
+            Ok(value) if self.1.accepts(&value) => Some(matched),
+            _ => None,
+        }
+    }
+
+    fn generate_path(&self, path: &mut Vec<PathSegment>) {
+        path.push(PathSegment::ConstrainedParam(
+            self.0.into(),
+            SegmentConstraint(Arc::new(self.1.clone())),
+        ));
+    }
+}
+
+/// The [`ParamConstraint`] of a [`PathSegment::ConstrainedParam`].
+#[derive(Clone, Debug)]
+pub struct SegmentConstraint(pub Arc<dyn ParamConstraint>);
+
+impl SegmentConstraint {
+    /// Whether the value of the param, after percent-decoding, is valid.
+    pub fn accepts(&self, value: &str) -> bool {
+        self.0.accepts(value)
+    }
+}
+
+impl PartialEq for SegmentConstraint {
+    fn eq(&self, other: &Self) -> bool {
+        // constraints are compared by their definitions, as they cannot be compared directly
+        format!("{:?}", self.0) == format!("{:?}", other.0)
+    }
+}
+
+impl Eq for SegmentConstraint {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConstrainedSegment, IntegerParam, OneOfParam, ParamConstraint,
+        UuidParam,
+    };
+    use crate::{PossibleRouteMatch, StaticSegment};
+
+    #[test]
+    fn constraints_check_values() {
+        assert!(IntegerParam.accepts("42"));
+        assert!(IntegerParam.accepts("-7"));
+        assert!(!IntegerParam.accepts("-"));
+        assert!(!IntegerParam.accepts("4e2"));
+
+        assert!(UuidParam.accepts("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!UuidParam.accepts("67e55044-10b1-426f-9247"));
+        assert!(!UuidParam.accepts("67e55044-10b1-426f-9247-bb680e5fe0cg"));
+
+        let tab = OneOfParam(&["posts", "comments"]);
+        assert!(tab.accepts("posts"));
+        assert!(!tab.accepts("likes"));
+    }
+
+    #[test]
+    fn constrained_segments_only_match_valid_values() {
+        let def = (
+            StaticSegment("users"),
+            ConstrainedSegment("id", IntegerParam),
+        );
+        let matched = def.test("/users/42/posts").expect("couldn't match");
+        assert_eq!(matched.matched(), "/users/42");
+        assert_eq!(matched.remaining(), "/posts");
+        assert_eq!(matched.params(), vec![("id".into(), "42".into())]);
+        assert!(def.test("/users/new").is_none());
+        assert!(def.test("/users").is_none());
+
+        let def = ConstrainedSegment("name", OneOfParam(&["a b"]));
+        assert!(def.test("/a%20b").is_some());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_constraints_match_whole_values() {
+        use super::RegexParam;
+
+        let year = RegexParam("[0-9]{4}");
+        assert!(year.accepts("2024"));
+        assert!(!year.accepts("20245"));
+        assert!(!RegexParam("(").accepts("("));
+    }
+}
@@ -1,7 +1,9 @@
 use super::{PartialPathMatch, PathSegment};
+mod constrained_segment;
 mod param_segments;
 mod static_segment;
 mod tuples;
+pub use constrained_segment::*;
 pub use param_segments::*;
 pub use static_segment::*;
 
@@ -1,3 +1,4 @@
+use crate::SegmentConstraint;
 use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -5,6 +6,7 @@ pub enum PathSegment {
     Unit,
     Static(Cow<'static, str>),
     Param(Cow<'static, str>),
+    ConstrainedParam(Cow<'static, str>, SegmentConstraint),
     OptionalParam(Cow<'static, str>),
     Splat(Cow<'static, str>),
 }
@@ -15,6 +17,7 @@ impl PathSegment {
             PathSegment::Unit => "",
             PathSegment::Static(i) => i,
             PathSegment::Param(i) => i,
+            PathSegment::ConstrainedParam(i, _) => i,
             PathSegment::OptionalParam(i) => i,
             PathSegment::Splat(i) => i,
         }
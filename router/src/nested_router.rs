@@ -22,6 +22,7 @@ use reactive_graph::{
 };
 use send_wrapper::SendWrapper;
 use std::{
+    borrow::Cow,
     cell::RefCell,
     fmt::Debug,
     future::Future,
@@ -175,7 +176,7 @@ where
                     &mut full_loaders,
                     &mut state.outlets,
                     &self.outer_owner,
-                    self.set_is_routing.is_some(),
+                    self.set_is_routing.is_some() || self.transition,
                     0,
                 );
 
@@ -184,6 +185,7 @@ where
                     .as_ref()
                     .map(|nav| nav.is_back().get_untracked())
                     .unwrap_or(false);
+                let (loaded_tx, loaded_rx) = oneshot::channel();
                 Executor::spawn_local(async move {
                     let triggers = join_all(preloaders).await;
                     // tell each one of the outlet triggers that it's ready
@@ -193,7 +195,15 @@ where
                         }
                     };
                     if self.transition {
-                        start_view_transition(different_level, is_back, notify);
+                        // the transition only animates to the new view once its data has loaded
+                        start_view_transition(
+                            different_level,
+                            is_back,
+                            notify,
+                            async move {
+                                _ = loaded_rx.await;
+                            },
+                        );
                     } else {
                         notify();
                     }
@@ -201,6 +211,7 @@ where
 
                 Executor::spawn_local(async move {
                     join_all(full_loaders).await;
+                    _ = loaded_tx.send(());
                     if let Some(set_is_routing) = self.set_is_routing {
                         set_is_routing.set(false);
                     }
@@ -506,6 +517,28 @@ impl Clone for RouteContext {
     }
 }
 
+/// Loads the code and data of the routes that match a URL, without rendering them.
+///
+/// Anything created while the routes are loaded is disposed of once they have loaded.
+pub(crate) fn preload_routes<Defs>(
+    routes: &RouteDefs<Defs>,
+    url: &Url,
+    parent: &Owner,
+) -> impl Future<Output = ()>
+where
+    Defs: MatchNestedRoutes,
+{
+    let owner = parent.child();
+    let mut preloads = Vec::new();
+    if let Some(route) = routes.match_route(url.path()) {
+        route.preload_nested_route(url, &[], "", &mut preloads, &owner);
+    }
+    async move {
+        join_all(preloads).await;
+        drop(owner);
+    }
+}
+
 trait AddNestedRoute {
     fn build_nested_route(
         self,
@@ -526,9 +559,18 @@ trait AddNestedRoute {
         full_loaders: &mut Vec<oneshot::Receiver<()>>,
         outlets: &mut Vec<RouteContext>,
         parent: &Owner,
-        set_is_routing: bool,
+        async_transition: bool,
         level: u8,
     ) -> u8;
+
+    fn preload_nested_route(
+        self,
+        url: &Url,
+        parent_params: &[(Cow<'static, str>, String)],
+        parent_matched: &str,
+        preloads: &mut Vec<Pin<Box<dyn Future<Output = ()>>>>,
+        parent: &Owner,
+    );
 }
 
 impl<Match> AddNestedRoute for Match
@@ -673,7 +715,7 @@ where
         full_loaders: &mut Vec<oneshot::Receiver<()>>,
         outlets: &mut Vec<RouteContext>,
         parent: &Owner,
-        set_is_routing: bool,
+        async_transition: bool,
         level: u8,
     ) -> u8 {
         let (parent_params, parent_matches): (Vec<_>, Vec<_>) = outlets
@@ -785,7 +827,7 @@ where
                                                 owner.with(|| {
                                                     ScopedFuture::new(
                                                         async move {
-                                                            if set_is_routing {
+                                                            if async_transition {
                                                                 AsyncTransition::run(|| view.choose()).await
                                                             } else {
                                                                 view.choose().await
@@ -842,7 +884,7 @@ where
                         full_loaders,
                         outlets,
                         &owner,
-                        set_is_routing,
+                        async_transition,
                         level + 1,
                     )
                 } else {
@@ -851,6 +893,49 @@ where
             }
         }
     }
+
+    fn preload_nested_route(
+        self,
+        url: &Url,
+        parent_params: &[(Cow<'static, str>, String)],
+        parent_matched: &str,
+        preloads: &mut Vec<Pin<Box<dyn Future<Output = ()>>>>,
+        parent: &Owner,
+    ) {
+        let owner = parent.child();
+        let params = parent_params
+            .iter()
+            .cloned()
+            .chain(self.to_params())
+            .collect::<Vec<_>>();
+        let matched = format!("{parent_matched}{}", self.as_matched());
+        let (view, child) = self.into_view_and_child();
+
+        // provide the same contexts as when the route is rendered, so that lazy routes can
+        // start loading their data
+        preloads.push(Box::pin(owner.with(|| {
+            ScopedFuture::new({
+                let params = params.iter().cloned().collect::<ParamsMap>();
+                let params = ArcMemo::new(move |_| params.clone());
+                let url = ArcRwSignal::new(url.to_owned());
+                let matched = {
+                    let matched = matched.clone();
+                    Matched(ArcMemo::new(move |_| matched.clone()))
+                };
+                async move {
+                    provide_context(params);
+                    provide_context(url);
+                    provide_context(matched);
+                    view.preload().await;
+                }
+            })
+        })));
+
+        if let Some(child) = child {
+            child
+                .preload_nested_route(url, &params, &matched, preloads, &owner);
+        }
+    }
 }
 
 impl<Fal> Mountable for NestedRouteViewState<Fal>
@@ -891,3 +976,52 @@ where
         view_fn()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::preload_routes;
+    use crate::{
+        hooks::use_params_map,
+        location::{LocationProvider, MemoryHistory},
+        Lazy, LazyRoute, NestedRoute, ParamSegment, RouteDefs, StaticSegment,
+    };
+    use leptos::prelude::*;
+    use std::sync::Mutex;
+
+    static LOADED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct UserRoute {
+        id: Option<String>,
+    }
+
+    impl LazyRoute for UserRoute {
+        fn data() -> Self {
+            Self {
+                id: use_params_map().read_untracked().get("id"),
+            }
+        }
+
+        async fn view(self) -> AnyView {
+            LOADED.lock().unwrap().push(self.id.unwrap_or_default());
+            ().into_any()
+        }
+    }
+
+    #[test]
+    fn preloads_matching_lazy_routes() {
+        let owner = Owner::new();
+        owner.set();
+        let routes = RouteDefs::new(NestedRoute::new(
+            (StaticSegment("users"), ParamSegment("id")),
+            Lazy::<UserRoute>::new(),
+        ));
+
+        let url = MemoryHistory::parse("/users/1").unwrap();
+        futures::executor::block_on(preload_routes(&routes, &url, &owner));
+        assert_eq!(*LOADED.lock().unwrap(), ["1"]);
+
+        let url = MemoryHistory::parse("/posts/1").unwrap();
+        futures::executor::block_on(preload_routes(&routes, &url, &owner));
+        assert_eq!(*LOADED.lock().unwrap(), ["1"]);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{
+    static_routes::StaticPath, ExpandOptionals, Method, RouteList, RouteListing,
+};
+use percent_encoding::{
+    percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS,
+};
+use std::{collections::HashSet, fmt, fmt::Write, sync::Arc};
+
+/// The characters that are escaped in a path segment of a sitemap URL: those that are not
+/// allowed in one, and `%`, as segments are decoded before they are encoded.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// How often the page at a URL is likely to change, as a hint for search engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeFrequency {
+    /// The page changes each time it is accessed.
+    Always,
+    /// The page changes about once an hour.
+    Hourly,
+    /// The page changes about once a day.
+    Daily,
+    /// The page changes about once a week.
+    Weekly,
+    /// The page changes about once a month.
+    Monthly,
+    /// The page changes about once a year.
+    Yearly,
+    /// The page is archived, and does not change.
+    Never,
+}
+
+impl fmt::Display for ChangeFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Always => "always",
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+            Self::Yearly => "yearly",
+            Self::Never => "never",
+        })
+    }
+}
+
+/// A URL in a sitemap, with its optional metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    /// The path of the page, like `/posts/hello-world`. Its segments are percent-encoded in the
+    /// sitemap.
+    pub path: String,
+    /// When the page was last modified, in W3C datetime format, like `2024-11-30`.
+    pub lastmod: Option<String>,
+    /// How often the page is likely to change.
+    pub changefreq: Option<ChangeFrequency>,
+    /// The priority of the page relative to the other pages of the site, from `0.0` to `1.0`.
+    pub priority: Option<f32>,
+}
+
+type MetadataFn = Arc<dyn Fn(&mut SitemapEntry) -> bool + Send + Sync>;
+
+/// Generates a `sitemap.xml` for the routes of an app, and a `robots.txt` that points to it.
+///
+/// The sitemap includes a URL for each route without params, and for each set of prerendered
+/// params of [`SsrMode::Static`](crate::SsrMode::Static) routes. Routes with params that are
+/// rendered on demand are left out, as their URLs cannot be known in advance.
+///
+/// ```rust,ignore
+/// let sitemap = Sitemap::new("https://example.com").with_metadata(|entry| {
+///     if entry.path.starts_with("/posts/") {
+///         entry.changefreq = Some(ChangeFrequency::Weekly);
+///         entry.priority = Some(0.8);
+///     }
+///     // leave the admin pages out of the sitemap
+///     !entry.path.starts_with("/admin")
+/// });
+/// let xml = sitemap.generate(&RouteList::generate(App).unwrap()).await;
+/// ```
+#[derive(Clone)]
+pub struct Sitemap {
+    base_url: String,
+    metadata: Option<MetadataFn>,
+}
+
+impl fmt::Debug for Sitemap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sitemap")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sitemap {
+    /// Creates a sitemap for a site served at `base_url`, like `https://example.com`, which
+    /// is added to each path as sitemaps only contain full URLs.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        base_url.truncate(base_url.trim_end_matches('/').len());
+        Self {
+            base_url,
+            metadata: None,
+        }
+    }
+
+    /// Sets a function that adds metadata to each entry of the sitemap, and returns whether
+    /// the entry should be included in it.
+    pub fn with_metadata(
+        mut self,
+        metadata: impl Fn(&mut SitemapEntry) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.metadata = Some(Arc::new(metadata));
+        self
+    }
+
+    /// Lists the pages of the given routes, loading the prerendered params of static routes.
+    pub async fn entries(&self, routes: &RouteList) -> Vec<SitemapEntry> {
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for route in routes.iter().filter(|route| is_page(route)) {
+            let params = match route.static_route() {
+                Some(route) => route.to_prerendered_params().await,
+                None => None,
+            };
+            for path in route.path().to_vec().expand_optionals() {
+                for path in StaticPath::new(path).into_paths(params.clone()) {
+                    let path = match path.as_ref() {
+                        "" => "/".to_string(),
+                        path => path.to_string(),
+                    };
+                    if !seen.insert(path.clone()) {
+                        continue;
+                    }
+                    let mut entry = SitemapEntry {
+                        path,
+                        lastmod: None,
+                        changefreq: None,
+                        priority: None,
+                    };
+                    if self
+                        .metadata
+                        .as_ref()
+                        .map_or(true, |metadata| metadata(&mut entry))
+                    {
+                        entries.push(entry);
+                    }
+                }
+            }
+        }
+        entries
+    }
+
+    /// Generates the `sitemap.xml` for the given routes.
+    pub async fn generate(&self, routes: &RouteList) -> String {
+        self.to_xml(&self.entries(routes).await)
+    }
+
+    /// Renders entries as a `sitemap.xml`.
+    pub fn to_xml(&self, entries: &[SitemapEntry]) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset \
+             xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for entry in entries {
+            xml.push_str("  <url>\n");
+            _ = writeln!(
+                xml,
+                "    <loc>{}</loc>",
+                escape_xml(&format!(
+                    "{}{}",
+                    self.base_url,
+                    encode_path(&entry.path)
+                ))
+            );
+            if let Some(lastmod) = &entry.lastmod {
+                _ = writeln!(
+                    xml,
+                    "    <lastmod>{}</lastmod>",
+                    escape_xml(lastmod)
+                );
+            }
+            if let Some(changefreq) = entry.changefreq {
+                _ = writeln!(xml, "    <changefreq>{changefreq}</changefreq>");
+            }
+            if let Some(priority) = entry.priority {
+                _ = writeln!(
+                    xml,
+                    "    <priority>{:.1}</priority>",
+                    priority.clamp(0.0, 1.0)
+                );
+            }
+            xml.push_str("  </url>\n");
+        }
+        xml.push_str("</urlset>\n");
+        xml
+    }
+
+    /// Generates a `robots.txt` that allows all pages to be crawled, and points to the sitemap
+    /// served at `/sitemap.xml`.
+    pub fn robots_txt(&self) -> String {
+        format!(
+            "User-agent: *\nAllow: /\n\nSitemap: {}/sitemap.xml\n",
+            self.base_url
+        )
+    }
+}
+
+/// Whether a route renders a page, rather than only handling form submissions.
+fn is_page(route: &RouteListing) -> bool {
+    let mut methods = route.methods().peekable();
+    methods.peek().is_none() || methods.any(|method| method == Method::Get)
+}
+
+/// Percent-encodes each segment of a path, decoding any that are already encoded so that they
+/// are not encoded twice.
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let segment = percent_decode_str(segment).decode_utf8_lossy();
+            utf8_percent_encode(&segment, SEGMENT).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeFrequency, Sitemap, SitemapEntry};
+    use crate::{
+        static_routes::{StaticParamsMap, StaticRoute},
+        Method, PathSegment, RouteList, RouteListing, SsrMode,
+    };
+    use futures::executor::block_on;
+
+    fn routes() -> RouteList {
+        let posts = StaticRoute::new().prerender_params(|| async {
+            let mut params = StaticParamsMap::new();
+            params.insert("slug", vec!["first".into(), "a&b".into()]);
+            params
+        });
+        RouteList::from(vec![
+            RouteListing::from_path([PathSegment::Static("".into())]),
+            RouteListing::from_path([PathSegment::Static("about".into())]),
+            RouteListing::new(
+                [
+                    PathSegment::Static("posts".into()),
+                    PathSegment::Param("slug".into()),
+                ],
+                SsrMode::Static(posts),
+                [Method::Get],
+                [],
+            ),
+            // rendered on demand, so its pages are unknown
+            RouteListing::from_path([
+                PathSegment::Static("users".into()),
+                PathSegment::Param("id".into()),
+            ]),
+            RouteListing::new(
+                [PathSegment::Static("upload".into())],
+                SsrMode::Async,
+                [Method::Post],
+                [],
+            ),
+        ])
+    }
+
+    #[test]
+    fn lists_static_and_prerendered_pages() {
+        let sitemap = Sitemap::new("https://example.com/");
+        let paths = block_on(sitemap.entries(&routes()))
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["/", "/about", "/posts/first", "/posts/a&b"]);
+    }
+
+    #[test]
+    fn renders_metadata_as_xml() {
+        let sitemap =
+            Sitemap::new("https://example.com").with_metadata(|entry| {
+                if entry.path.starts_with("/posts/") {
+                    entry.lastmod = Some("2024-11-30".into());
+                    entry.changefreq = Some(ChangeFrequency::Weekly);
+                    entry.priority = Some(0.8);
+                }
+                entry.path != "/about"
+            });
+        let xml = block_on(sitemap.generate(&routes()));
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<loc>https://example.com/</loc>"));
+        assert!(!xml.contains("/about"));
+        assert!(xml.contains(
+            "    <loc>https://example.com/posts/first</loc>\n    \
+             <lastmod>2024-11-30</lastmod>\n    \
+             <changefreq>weekly</changefreq>\n    \
+             <priority>0.8</priority>\n"
+        ));
+        assert!(xml.contains("<loc>https://example.com/posts/a&amp;b</loc>"));
+        assert_eq!(
+            sitemap.robots_txt(),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+
+    #[test]
+    fn percent_encodes_paths() {
+        let entry = |path: &str| SitemapEntry {
+            path: path.into(),
+            lastmod: None,
+            changefreq: None,
+            priority: None,
+        };
+        let xml = Sitemap::new("https://example.com").to_xml(&[
+            entry("/posts/hello world"),
+            entry("/posts/café/ünïcode"),
+            entry("/posts/50%25 off"),
+        ]);
+        assert!(
+            xml.contains("<loc>https://example.com/posts/hello%20world</loc>")
+        );
+        assert!(xml.contains(
+            "<loc>https://example.com/posts/caf%C3%A9/%C3%BCn%C3%AFcode</loc>"
+        ));
+        // segments that are already encoded are not encoded again
+        assert!(
+            xml.contains("<loc>https://example.com/posts/50%25%20off</loc>")
+        );
+    }
+}
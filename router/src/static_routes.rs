@@ -94,6 +94,50 @@ impl StaticRoute {
             Some(params) => Some(params().await),
         }
     }
+
+    /// Combines two static routes that are served from the same path, prerendering the params of
+    /// both and regenerating whenever either of them would.
+    pub fn merge(self, other: StaticRoute) -> StaticRoute {
+        if self == other {
+            return self;
+        }
+        let prerender_params =
+            match (self.prerender_params, other.prerender_params) {
+                (Some(this), Some(that)) => {
+                    let params: StaticParams = Arc::new(move || {
+                        Box::pin(merge_params(this(), that()))
+                    });
+                    Some(params)
+                }
+                (this, that) => this.or(that),
+            };
+        let regenerate = match (self.regenerate, other.regenerate) {
+            (Some(this), Some(that)) => {
+                Some(RegenerationFn(Arc::new(move |params| {
+                    Box::pin(stream::select(this(params), that(params)))
+                })))
+            }
+            (this, that) => this.or(that),
+        };
+        StaticRoute {
+            prerender_params,
+            regenerate,
+        }
+    }
+}
+
+async fn merge_params(
+    this: PinnedFuture<StaticParamsMap>,
+    that: PinnedFuture<StaticParamsMap>,
+) -> StaticParamsMap {
+    let mut params = this.await;
+    for (key, values) in that.await {
+        match params.0.iter_mut().find(|item| item.0 == key) {
+            Some(item) => item.1.extend(values),
+            None => params.0.push((key, values)),
+        }
+    }
+    params
 }
 
 impl Debug for StaticRoute {
@@ -232,12 +276,30 @@ impl StaticPath {
                         })
                         .collect::<Vec<_>>();
                 }
-                Param(name) | Splat(name) => {
+                Param(name) | ConstrainedParam(name, _) | Splat(name) => {
+                    let constraint = match segment {
+                        ConstrainedParam(_, constraint) => Some(constraint),
+                        _ => None,
+                    };
                     let mut new_paths = vec![];
                     if let Some(params) = params.as_ref() {
                         for path in paths {
                             if let Some(params) = params.get(name) {
                                 for val in params.iter() {
+                                    // the route would not match a page for this value
+                                    if constraint.is_some_and(|constraint| {
+                                        !constraint.accepts(
+                                            val.trim_start_matches('/'),
+                                        )
+                                    }) {
+                                        leptos::logging::error!(
+                                            "Not prerendering {}: {val:?} is \
+                                             not a valid value for param \
+                                             {name:?}.",
+                                            path.path
+                                        );
+                                        continue;
+                                    }
                                     new_paths.push(if val.starts_with("/") {
                                         ResolvedStaticPath {
                                             path: format!(
@@ -381,6 +443,7 @@ impl ResolvedStaticPath {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{IntegerParam, SegmentConstraint};
 
     #[test]
     fn static_path_segments_into_path_ignore_empty_segments() {
@@ -413,6 +476,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn static_path_segments_into_path_skip_invalid_params() {
+        let mut params = StaticParamsMap::new();
+        params
+            .0
+            .push(("id".into(), vec!["1".into(), "new".into(), "/2".into()]));
+        let segments = StaticPath::new(vec![
+            PathSegment::Static("/post".into()),
+            PathSegment::ConstrainedParam(
+                "id".into(),
+                SegmentConstraint(Arc::new(IntegerParam)),
+            ),
+        ]);
+        assert_eq!(
+            segments.into_paths(Some(params)),
+            vec![
+                ResolvedStaticPath::new("/post/1"),
+                ResolvedStaticPath::new("/post/2")
+            ]
+        );
+    }
+
     #[test]
     fn static_path_segments_into_path_no_double_slash() {
         let segments = StaticPath::new(vec![
@@ -440,4 +525,38 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn merged_static_routes_prerender_and_regenerate_both() {
+        let first = StaticRoute::new()
+            .prerender_params(|| async {
+                [("id".to_string(), vec!["1".to_string()])]
+                    .into_iter()
+                    .collect()
+            })
+            .regenerate(|_| stream::iter([()]));
+        let second = StaticRoute::new()
+            .prerender_params(|| async {
+                [
+                    ("id".to_string(), vec!["2".to_string()]),
+                    ("slug".to_string(), vec!["hello".to_string()]),
+                ]
+                .into_iter()
+                .collect()
+            })
+            .regenerate(|_| stream::iter([(), ()]));
+
+        let merged = first.clone().merge(second);
+        let params =
+            futures::executor::block_on(merged.to_prerendered_params())
+                .unwrap();
+        assert_eq!(params.get("id").unwrap(), &["1", "2"]);
+        assert_eq!(params.get("slug").unwrap(), &["hello"]);
+        let regenerate = merged.regenerate.unwrap();
+        let regenerations =
+            futures::executor::block_on(regenerate(&ParamsMap::new()).count());
+        assert_eq!(regenerations, 3);
+
+        assert!(first.clone().merge(first.clone()) == first);
+    }
 }
@@ -0,0 +1,92 @@
+use crate::params::{Params, ParamsError};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// The characters that are escaped in a path segment: everything but the unreserved ones.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A route whose path is defined together with the params it is matched with, so that links to
+/// it are built from typed params rather than from strings.
+///
+/// This is usually derived with [`TypedRoute`](macro@crate::TypedRoute), which checks at
+/// compile time that each param in the path has a field, and each field has a param. The same
+/// type then defines the [`Route`](crate::components::Route), renders links to it (as it
+/// implements [`ToHref`](crate::components::ToHref)) and reads its params with
+/// [`use_params`](crate::hooks::use_params):
+///
+/// ```rust,ignore
+/// #[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+/// #[route("/users/:id")]
+/// struct UserPage {
+///     id: Option<usize>,
+/// }
+///
+/// view! {
+///     <Routes fallback=|| "Not found.">
+///         <Route path=UserPage::path() view=User/>
+///     </Routes>
+///     <A href=UserPage { id: Some(1) }>"User 1"</A> // links to `/users/1`
+/// }
+/// ```
+pub trait TypedRoute: Params {
+    /// The segments of the path, as created by the [`path`](crate::path) macro.
+    type Path;
+
+    /// The path to use in the route definition.
+    ///
+    /// For a route nested in a [`ParentRoute`](crate::components::ParentRoute), this is the
+    /// part of the path after its parent's.
+    fn path() -> Self::Path;
+
+    /// The full path to the route, with these params.
+    ///
+    /// Optional params that are `None` are left out of the path, but a required param that is
+    /// `None` is a [`ParamsError::MissingParam`].
+    fn to_path(&self) -> Result<String, ParamsError>;
+}
+
+/// The `href` of a link to a [`TypedRoute`], which logs an error and links to the current page
+/// if a required param is missing.
+#[doc(hidden)]
+pub fn typed_route_href(route: &impl TypedRoute) -> String {
+    route.to_path().unwrap_or_else(|e| {
+        leptos::logging::error!("Cannot link to a route: {e}");
+        String::new()
+    })
+}
+
+/// Adds a param to a path being built by a [`TypedRoute`], so that it is matched as a single
+/// segment.
+#[doc(hidden)]
+pub fn push_param_segment(path: &mut String, value: &str) {
+    path.push('/');
+    path.extend(utf8_percent_encode(value, SEGMENT));
+}
+
+/// Adds a wildcard param to a path being built by a [`TypedRoute`], keeping the `/`s between
+/// its segments.
+#[doc(hidden)]
+pub fn push_wildcard_segment(path: &mut String, value: &str) {
+    for segment in value.trim_start_matches('/').split('/') {
+        push_param_segment(path, segment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{push_param_segment, push_wildcard_segment};
+
+    #[test]
+    fn escapes_param_segments() {
+        let mut path = String::from("/users");
+        push_param_segment(&mut path, "a b/c");
+        assert_eq!(path, "/users/a%20b%2Fc");
+
+        let mut path = String::from("/files");
+        push_wildcard_segment(&mut path, "/docs/a b.txt");
+        assert_eq!(path, "/files/docs/a%20b.txt");
+    }
+}
@@ -1,4 +1,4 @@
-//! A macro to make path definitions easier with [`leptos_router`].
+//! Macros to make path definitions easier with [`leptos_router`].
 
 #![deny(missing_docs)]
 
@@ -7,6 +7,8 @@ use proc_macro2::Span;
 use proc_macro_error2::abort;
 use quote::{quote, ToTokens};
 
+mod typed_route;
+
 const RFC3986_UNRESERVED: [char; 4] = ['-', '.', '_', '~'];
 const RFC3986_PCHAR_OTHER: [char; 1] = ['@'];
 
@@ -33,6 +35,30 @@ const RFC3986_PCHAR_OTHER: [char; 1] = ['@'];
 ///
 /// assert_eq!(path, output);
 /// ```
+///
+/// A param can be constrained to only match some values, with
+/// [`ConstrainedSegment`](leptos_router::ConstrainedSegment)s:
+///
+/// - `:id<int>` only matches integers;
+/// - `:id<uuid>` only matches UUIDs;
+/// - `:tab<posts|comments>` only matches one of the listed values;
+/// - `:year<regex:[0-9]{4}>` only matches values that match the regular expression, which
+///   cannot contain `/`. This requires the `regex` feature of `leptos_router`.
+///
+/// ```rust
+/// use leptos_router::{
+///     path, ConstrainedSegment, IntegerParam, OneOfParam, StaticSegment,
+/// };
+///
+/// let path = path!("/users/:id<int>/:tab<posts|comments>");
+/// let output = (
+///     StaticSegment("users"),
+///     ConstrainedSegment("id", IntegerParam),
+///     ConstrainedSegment("tab", OneOfParam(&["posts", "comments"])),
+/// );
+///
+/// assert_eq!(path, output);
+/// ```
 #[proc_macro_error2::proc_macro_error]
 #[proc_macro]
 pub fn path(tokens: TokenStream) -> TokenStream {
@@ -42,6 +68,55 @@ pub fn path(tokens: TokenStream) -> TokenStream {
     segments.into_token_stream().into()
 }
 
+/// Derives [`TypedRoute`](leptos_router::TypedRoute) for a struct whose fields are the params
+/// of a route, and [`ToHref`](leptos_router::components::ToHref) so that it can be used as the
+/// `href` of a link to that route.
+///
+/// The path is given in a `#[route]` attribute, in the same format as for [`path!`]. Each param
+/// in the path must have a field with the same name, and each field must be a param, so that
+/// renaming a param or a field is a compile error rather than a broken link. Fields are
+/// converted with [`ToString`]. Optional params whose fields are `None` are left out of the
+/// path, while a `None` for a required param makes
+/// [`to_path`](leptos_router::TypedRoute::to_path) return an error, and the link point to the
+/// current page.
+///
+/// For a route nested in a `<ParentRoute>`, the parent's path is given as `parent`: its params
+/// are included in the struct and in the links, but not in
+/// [`path()`](leptos_router::TypedRoute::path).
+///
+/// # Examples
+///
+/// ```rust
+/// use leptos::prelude::*;
+/// use leptos_router::{
+///     components::ToHref, params::Params, ParamSegment, StaticSegment,
+///     TypedRoute,
+/// };
+///
+/// #[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+/// #[route("/posts/:post", parent = "/users/:id")]
+/// struct UserPost {
+///     id: Option<usize>,
+///     post: Option<String>,
+/// }
+///
+/// let route = UserPost {
+///     id: Some(1),
+///     post: Some("hello world".into()),
+/// };
+/// assert_eq!(route.to_href()(), "/users/1/posts/hello%20world");
+/// assert_eq!(
+///     UserPost::path(),
+///     (StaticSegment("posts"), ParamSegment("post"))
+/// );
+/// ```
+#[proc_macro_error2::proc_macro_error]
+#[proc_macro_derive(TypedRoute, attributes(route))]
+pub fn typed_route_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    typed_route::typed_route_impl(input).into()
+}
+
 #[derive(Debug, PartialEq)]
 struct Segments(pub Vec<Segment>);
 
@@ -49,10 +124,57 @@ struct Segments(pub Vec<Segment>);
 enum Segment {
     Static(String),
     Param(String),
+    ConstrainedParam(String, Constraint),
     OptionalParam(String),
     Wildcard(String),
 }
 
+#[derive(Debug, PartialEq)]
+enum Constraint {
+    Integer,
+    Uuid,
+    OneOf(Vec<String>),
+    Regex(String),
+}
+
+impl Constraint {
+    fn parse(constraint: &str) -> Self {
+        match constraint {
+            "int" => Self::Integer,
+            "uuid" => Self::Uuid,
+            _ => {
+                if let Some(regex) = constraint.strip_prefix("regex:") {
+                    // the pattern is checked here, as it is only compiled when it is matched
+                    let anchored = format!("^(?:{regex})$");
+                    if let Err(e) = regex_syntax::Parser::new().parse(&anchored)
+                    {
+                        abort!(
+                            Span::call_site(),
+                            "Invalid param regex {}: {}",
+                            regex,
+                            e
+                        );
+                    }
+                    Self::Regex(regex.to_string())
+                } else {
+                    let values = constraint
+                        .split('|')
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    if values.iter().any(String::is_empty) {
+                        abort!(
+                            Span::call_site(),
+                            "Invalid param constraint: {}",
+                            constraint
+                        );
+                    }
+                    Self::OneOf(values)
+                }
+            }
+        }
+    }
+}
+
 struct SegmentParser {
     input: proc_macro::token_stream::IntoIter,
     segments: Vec<Segment>,
@@ -73,20 +195,7 @@ impl SegmentParser {
             match input {
                 TokenTree::Literal(lit) => {
                     let lit = lit.to_string();
-                    if lit.contains("//") {
-                        abort!(
-                            proc_macro2::Span::call_site(),
-                            "Consecutive '/' is not allowed"
-                        );
-                    }
-                    Self::parse_str(
-                        &mut self.segments,
-                        lit.trim_start_matches(['"', '/'])
-                            .trim_end_matches(['"', '/']),
-                    );
-                    if lit.ends_with(r#"/""#) && lit != r#""/""# {
-                        self.segments.push(Segment::Static("/".to_string()));
-                    }
+                    Self::parse_path(&mut self.segments, lit.trim_matches('"'));
                 }
                 TokenTree::Group(_) => unimplemented!(),
                 TokenTree::Ident(_) => unimplemented!(),
@@ -95,6 +204,19 @@ impl SegmentParser {
         }
     }
 
+    pub fn parse_path(segments: &mut Vec<Segment>, path: &str) {
+        if path.contains("//") {
+            abort!(
+                proc_macro2::Span::call_site(),
+                "Consecutive '/' is not allowed"
+            );
+        }
+        Self::parse_str(segments, path.trim_matches('/'));
+        if path.ends_with('/') && path != "/" {
+            segments.push(Segment::Static("/".to_string()));
+        }
+    }
+
     pub fn parse_str(segments: &mut Vec<Segment>, current_str: &str) {
         if ["", "*"].contains(&current_str) {
             return;
@@ -102,7 +224,27 @@ impl SegmentParser {
 
         for segment in current_str.split('/') {
             if let Some(segment) = segment.strip_prefix(':') {
-                if let Some(segment) = segment.strip_suffix('?') {
+                // the constraint is split off first, as a regex may contain or end with `?`
+                if let Some((name, constraint)) = segment.split_once('<') {
+                    let Some(constraint) = constraint.strip_suffix('>') else {
+                        if constraint.ends_with(">?") {
+                            abort!(
+                                Span::call_site(),
+                                "Optional params cannot be constrained: {}",
+                                segment
+                            );
+                        }
+                        abort!(
+                            Span::call_site(),
+                            "Param constraint must end with '>': {}",
+                            segment
+                        );
+                    };
+                    segments.push(Segment::ConstrainedParam(
+                        name.to_string(),
+                        Constraint::parse(constraint),
+                    ));
+                } else if let Some(segment) = segment.strip_suffix('?') {
                     segments.push(Segment::OptionalParam(segment.to_string()));
                 } else {
                     segments.push(Segment::Param(segment.to_string()));
@@ -136,7 +278,9 @@ impl Segment {
             Self::Static(s) if !Self::is_valid(s) => {
                 abort!(Span::call_site(), "Invalid static segment: {}", s)
             }
-            Self::Param(s) if !Self::is_valid(s) => {
+            Self::Param(s) | Self::ConstrainedParam(s, _)
+                if !Self::is_valid(s) =>
+            {
                 abort!(Span::call_site(), "Invalid param segment: {}", s)
             }
             _ => (),
@@ -169,6 +313,11 @@ impl ToTokens for Segment {
             Segment::Param(p) => {
                 tokens.extend(quote! { leptos_router::ParamSegment(#p) });
             }
+            Segment::ConstrainedParam(p, constraint) => {
+                tokens.extend(quote! {
+                    leptos_router::ConstrainedSegment(#p, #constraint)
+                });
+            }
             Segment::OptionalParam(p) => {
                 tokens
                     .extend(quote! { leptos_router::OptionalParamSegment(#p) });
@@ -177,6 +326,27 @@ impl ToTokens for Segment {
     }
 }
 
+impl ToTokens for Constraint {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            Constraint::Integer => {
+                tokens.extend(quote! { leptos_router::IntegerParam });
+            }
+            Constraint::Uuid => {
+                tokens.extend(quote! { leptos_router::UuidParam });
+            }
+            Constraint::OneOf(values) => {
+                tokens.extend(
+                    quote! { leptos_router::OneOfParam(&[#(#values),*]) },
+                );
+            }
+            Constraint::Regex(regex) => {
+                tokens.extend(quote! { leptos_router::RegexParam(#regex) });
+            }
+        }
+    }
+}
+
 impl ToTokens for Segments {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         self.ensure_valid();
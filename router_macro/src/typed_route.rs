@@ -0,0 +1,247 @@
+use crate::{Constraint, Segment, SegmentParser, Segments};
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error2::abort;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Data, DeriveInput, Field, Fields, Ident, LitStr, Token, Type,
+};
+
+/// The arguments of a `#[route("/path", parent = "/parent")]` attribute.
+struct RouteAttr {
+    path: LitStr,
+    parent: Option<LitStr>,
+}
+
+impl Parse for RouteAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut parent = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key != "parent" {
+                return Err(syn::Error::new(key.span(), "expected `parent`"));
+            }
+            input.parse::<Token![=]>()?;
+            parent = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Self { path, parent })
+    }
+}
+
+pub fn typed_route_impl(input: DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let Some(attr) = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("route"))
+    else {
+        abort!(
+            Span::call_site(),
+            "TypedRoute requires a `#[route(\"/path\")]` attribute"
+        );
+    };
+    let attr: RouteAttr = match attr.parse_args() {
+        Ok(attr) => attr,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let fields: Vec<&Field> = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(fields) => abort!(
+                fields.span(),
+                "TypedRoute fields must be named after the params of the route"
+            ),
+        },
+        _ => abort!(name.span(), "TypedRoute can only be derived for structs"),
+    };
+
+    let mut segments = Vec::new();
+    if let Some(parent) = &attr.parent {
+        SegmentParser::parse_path(&mut segments, &parent.value());
+    }
+    let parent_len = segments.len();
+    SegmentParser::parse_path(&mut segments, &attr.path.value());
+
+    // links must match the route, so each param needs a field, and each field a param
+    let field_for = |param: &str| {
+        fields
+            .iter()
+            .find(|field| field.ident.as_ref().is_some_and(|f| f == param))
+    };
+    for segment in &segments {
+        if let Some(param) = segment.param_name() {
+            if field_for(param).is_none() {
+                abort!(
+                    attr.path.span(),
+                    "the route has a param `{}`, but `{}` has no field with \
+                     that name",
+                    param,
+                    name
+                );
+            }
+        }
+    }
+    for field in &fields {
+        let ident = field.ident.as_ref().expect("named field");
+        if !segments
+            .iter()
+            .any(|segment| segment.param_name().is_some_and(|p| ident == p))
+        {
+            abort!(ident.span(), "`{}` is not a param of the route", ident);
+        }
+    }
+
+    let push_segments: Vec<_> = segments.iter().map(|segment| match segment {
+        Segment::Static(s) if s == "/" => quote! { path.push('/'); },
+        Segment::Static(s) => quote! {
+            path.push('/');
+            path.push_str(#s);
+        },
+        Segment::Param(param)
+        | Segment::ConstrainedParam(param, _)
+        | Segment::OptionalParam(param)
+        | Segment::Wildcard(param) => {
+            let field = field_for(param).expect("checked above");
+            let ident = &field.ident;
+            let push = if matches!(segment, Segment::Wildcard(_)) {
+                quote! { leptos_router::push_wildcard_segment }
+            } else {
+                quote! { leptos_router::push_param_segment }
+            };
+            // optional params are left out of the path if they are `None`, but required params
+            // cannot be
+            if is_option(&field.ty) {
+                let none = if matches!(segment, Segment::OptionalParam(_)) {
+                    quote! {}
+                } else {
+                    quote! {
+                        return ::core::result::Result::Err(
+                            leptos_router::params::ParamsError::MissingParam(
+                                ::std::string::ToString::to_string(#param)
+                            )
+                        );
+                    }
+                };
+                quote_spanned! {field.span()=>
+                    match &self.#ident {
+                        ::core::option::Option::Some(value) => {
+                            #push(&mut path, &::std::string::ToString::to_string(value));
+                        }
+                        ::core::option::Option::None => { #none }
+                    }
+                }
+            } else {
+                quote_spanned! {field.span()=>
+                    #push(&mut path, &::std::string::ToString::to_string(&self.#ident));
+                }
+            }
+        }
+    }).collect();
+
+    let own_segments = Segments(segments.split_off(parent_len));
+    let path_ty = own_segments.to_type();
+
+    quote! {
+        impl #impl_generics leptos_router::TypedRoute for #name #ty_generics #where_clause {
+            type Path = #path_ty;
+
+            #[allow(clippy::unused_unit)]
+            fn path() -> Self::Path {
+                #own_segments
+            }
+
+            fn to_path(
+                &self,
+            ) -> ::core::result::Result<
+                ::std::string::String,
+                leptos_router::params::ParamsError,
+            > {
+                let mut path = ::std::string::String::new();
+                #(#push_segments)*
+                if path.is_empty() {
+                    path.push('/');
+                }
+                ::core::result::Result::Ok(path)
+            }
+        }
+
+        impl #impl_generics leptos_router::components::ToHref for #name #ty_generics #where_clause {
+            fn to_href(
+                &self,
+            ) -> ::std::boxed::Box<dyn ::core::ops::Fn() -> ::std::string::String + '_> {
+                let path = leptos_router::typed_route_href(self);
+                ::std::boxed::Box::new(move || path.clone())
+            }
+        }
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+impl Segment {
+    fn param_name(&self) -> Option<&str> {
+        match self {
+            Segment::Static(_) => None,
+            Segment::Param(p)
+            | Segment::ConstrainedParam(p, _)
+            | Segment::OptionalParam(p)
+            | Segment::Wildcard(p) => Some(p),
+        }
+    }
+
+    fn to_type(&self) -> TokenStream {
+        match self {
+            Segment::Static(_) => {
+                quote! { leptos_router::StaticSegment<&'static str> }
+            }
+            Segment::Param(_) => quote! { leptos_router::ParamSegment },
+            Segment::ConstrainedParam(_, constraint) => {
+                let constraint = constraint.to_type();
+                quote! { leptos_router::ConstrainedSegment<#constraint> }
+            }
+            Segment::OptionalParam(_) => {
+                quote! { leptos_router::OptionalParamSegment }
+            }
+            Segment::Wildcard(_) => quote! { leptos_router::WildcardSegment },
+        }
+    }
+}
+
+impl Constraint {
+    fn to_type(&self) -> TokenStream {
+        match self {
+            Constraint::Integer => quote! { leptos_router::IntegerParam },
+            Constraint::Uuid => quote! { leptos_router::UuidParam },
+            Constraint::OneOf(_) => quote! { leptos_router::OneOfParam },
+            Constraint::Regex(_) => quote! { leptos_router::RegexParam },
+        }
+    }
+}
+
+impl Segments {
+    fn to_type(&self) -> TokenStream {
+        let types = self.0.iter().map(Segment::to_type);
+        match self.0.as_slice() {
+            [] => quote! { () },
+            [_] => quote! { (#(#types,)*) },
+            _ => quote! { (#(#types),*) },
+        }
+    }
+}
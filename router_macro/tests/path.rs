@@ -1,5 +1,6 @@
 use leptos_router::{
-    OptionalParamSegment, ParamSegment, StaticSegment, WildcardSegment,
+    ConstrainedSegment, IntegerParam, OneOfParam, OptionalParamSegment,
+    ParamSegment, RegexParam, StaticSegment, UuidParam, WildcardSegment,
 };
 use leptos_router_macro::path;
 
@@ -181,6 +182,50 @@ fn parses_complex() {
     );
 }
 
+#[test]
+fn parses_constrained_params() {
+    let output = path!("/users/:id<int>/:key<uuid>/:tab<posts|comments>");
+    assert_eq!(
+        output,
+        (
+            StaticSegment("users"),
+            ConstrainedSegment("id", IntegerParam),
+            ConstrainedSegment("key", UuidParam),
+            ConstrainedSegment("tab", OneOfParam(&["posts", "comments"])),
+        )
+    );
+}
+
+#[test]
+fn parses_regex_constrained_param() {
+    let output = path!("/archive/:year<regex:[0-9]{4}>");
+    assert_eq!(
+        output,
+        (
+            StaticSegment("archive"),
+            ConstrainedSegment("year", RegexParam("[0-9]{4}")),
+        )
+    );
+}
+
+#[test]
+fn parses_regex_ending_in_question_mark() {
+    let output = path!("/docs/:version<regex:v[0-9]+(-beta)?>");
+    assert_eq!(
+        output,
+        (
+            StaticSegment("docs"),
+            ConstrainedSegment("version", RegexParam("v[0-9]+(-beta)?")),
+        )
+    );
+}
+
+#[test]
+fn red() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/path/red.rs")
+}
+
 // #[test]
 // fn deny_consecutive_slashes() {
 //     let _ = path!("/////foo///bar/////baz/");
@@ -0,0 +1,9 @@
+use leptos_router_macro::path;
+
+fn main() {
+    let _ = path!("/archive/:year<regex:[0-9]{4>");
+
+    let _ = path!("/archive/:year<regex:[0-9]{4}");
+
+    let _ = path!("/archive/:year<int>?");
+}
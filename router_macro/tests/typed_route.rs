@@ -0,0 +1,134 @@
+use leptos::prelude::*;
+use leptos_router::{
+    components::ToHref,
+    params::{Params, ParamsError, ParamsMap},
+    ConstrainedSegment, IntegerParam, OptionalParamSegment, ParamSegment,
+    StaticSegment, TypedRoute, WildcardSegment,
+};
+
+#[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+#[route("/")]
+struct Home {}
+
+#[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+#[route("/users/:id/posts/:post?")]
+struct UserPosts {
+    id: Option<usize>,
+    post: Option<String>,
+}
+
+#[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+#[route("/edit/", parent = "/users/:id")]
+struct EditUser {
+    id: Option<usize>,
+}
+
+#[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+#[route("/orders/:id<int>")]
+struct Order {
+    id: Option<i64>,
+}
+
+#[derive(Params, TypedRoute, Clone, Debug, PartialEq)]
+#[route("/files/*path")]
+struct File {
+    path: Option<String>,
+}
+
+#[test]
+fn builds_paths_without_params() {
+    assert_eq!(Home::path(), ());
+    assert_eq!(Home {}.to_path().unwrap(), "/");
+    assert_eq!(Home {}.to_href()(), "/");
+}
+
+#[test]
+fn builds_paths_from_params() {
+    assert_eq!(
+        UserPosts::path(),
+        (
+            StaticSegment("users"),
+            ParamSegment("id"),
+            StaticSegment("posts"),
+            OptionalParamSegment("post")
+        )
+    );
+    let route = UserPosts {
+        id: Some(1),
+        post: Some("first post".into()),
+    };
+    assert_eq!(route.to_path().unwrap(), "/users/1/posts/first%20post");
+
+    // optional params are left out
+    let route = UserPosts {
+        id: Some(1),
+        post: None,
+    };
+    assert_eq!(route.to_path().unwrap(), "/users/1/posts");
+}
+
+#[test]
+fn requires_required_params() {
+    let route = UserPosts {
+        id: None,
+        post: Some("first post".into()),
+    };
+    assert_eq!(route.to_path(), Err(ParamsError::MissingParam("id".into())));
+    // the link points to the current page rather than to another route
+    assert_eq!(route.to_href()(), "");
+
+    assert_eq!(
+        EditUser { id: None }.to_path(),
+        Err(ParamsError::MissingParam("id".into()))
+    );
+}
+
+#[test]
+fn only_defines_nested_part_of_path() {
+    assert_eq!(
+        EditUser::path(),
+        (StaticSegment("edit"), StaticSegment("/"))
+    );
+    assert_eq!(
+        EditUser { id: Some(2) }.to_path().unwrap(),
+        "/users/2/edit/"
+    );
+}
+
+#[test]
+fn builds_paths_with_constrained_params() {
+    assert_eq!(
+        Order::path(),
+        (
+            StaticSegment("orders"),
+            ConstrainedSegment("id", IntegerParam)
+        )
+    );
+    assert_eq!(Order { id: Some(-1) }.to_path().unwrap(), "/orders/-1");
+}
+
+#[test]
+fn keeps_slashes_in_wildcards() {
+    assert_eq!(
+        File::path(),
+        (StaticSegment("files"), WildcardSegment("path"))
+    );
+    let route = File {
+        path: Some("docs/index".into()),
+    };
+    assert_eq!(route.to_path().unwrap(), "/files/docs/index");
+}
+
+#[test]
+fn parses_params_into_route() {
+    let mut params = ParamsMap::new();
+    params.insert("id", "1".into());
+    params.insert("post", "first%20post".into());
+    assert_eq!(
+        UserPosts::from_map(&params),
+        Ok(UserPosts {
+            id: Some(1),
+            post: Some("first post".into()),
+        })
+    );
+}
@@ -0,0 +1,1156 @@
+//! A server function that uses the [`Batch`] client does not send its own request. Instead, the
+//! calls made within the same tick are collected and sent together as a single `POST` request to
+//! the batch endpoint next to them: calls to `/api/get_user…` are sent to `/api/_batch` (see
+//! [`batch_path`]). The server runs each call through the server function registry, with its own
+//! middleware, and streams each result back as soon as it is ready, so that every call resolves
+//! individually.
+//!
+//! Batching is opted into for each server function with its `client`:
+//!
+//! ```rust,ignore
+//! use server_fn::{batch::Batch, client::browser::BrowserClient};
+//!
+//! #[server(client = Batch<BrowserClient>)]
+//! pub async fn get_user(id: u32) -> Result<User, ServerFnError> {
+//!     todo!()
+//! }
+//! ```
+//!
+//! Each call is sent with the headers of the batch request (like its cookies), but calls with
+//! streaming bodies or with the browser's `FormData` cannot be batched.
+//!
+//! The batch endpoint is not served unless it is registered, once for each prefix that batched
+//! server functions use:
+//!
+//! ```rust,ignore
+//! server_fn::axum::register_batch_endpoint("/api");
+//! // or, with Actix
+//! server_fn::actix::register_batch_endpoint("/api");
+//! ```
+//!
+//! A batch holds at most [`MAX_BATCH_CALLS`] calls, and the server runs at most
+//! [`MAX_CONCURRENT_CALLS`] of them at the same time.
+
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError},
+    request::ClientReq,
+    response::ClientRes,
+};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures::{
+    channel::oneshot,
+    future::{select, BoxFuture, Either, Shared},
+    FutureExt, Stream, StreamExt,
+};
+use http::Method;
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The last segment of the path of the endpoint that batched server function calls are sent to.
+pub const BATCH_ENDPOINT: &str = "_batch";
+
+/// The most calls that can be sent in one batch. The client starts a new batch once a batch is
+/// full, and the server rejects batches with more calls.
+pub const MAX_BATCH_CALLS: usize = 64;
+
+/// The most calls of one batch that the server runs at the same time.
+pub const MAX_CONCURRENT_CALLS: usize = 8;
+
+/// Returns the path of the batch endpoint for server functions with the given prefix.
+///
+/// ```rust
+/// # use server_fn::batch::batch_path;
+/// assert_eq!(batch_path("/api"), "/api/_batch");
+/// assert_eq!(batch_path("/rpc/"), "/rpc/_batch");
+/// ```
+pub fn batch_path(prefix: &str) -> String {
+    format!("{}/{BATCH_ENDPOINT}", prefix.trim_end_matches('/'))
+}
+
+/// Whether a path is that of a batch endpoint, which cannot be called from within a batch.
+#[cfg(any(feature = "axum-no-default", feature = "actix", test))]
+fn is_batch_path(path: &str) -> bool {
+    path.rsplit('/').next() == Some(BATCH_ENDPOINT)
+}
+
+/// The content type of a batch request, and of its response.
+pub const BATCH_CONTENT_TYPE: &str = "application/x-server-fn-batch";
+
+/// A server function call that will be sent as part of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    method: Method,
+    path: String,
+    query: String,
+    content_type: String,
+    accepts: String,
+    body: Bytes,
+}
+
+impl BatchRequest {
+    fn new(
+        method: Method,
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+        body: Bytes,
+    ) -> Self {
+        Self {
+            method,
+            path: path.to_string(),
+            query: query.to_string(),
+            content_type: content_type.to_string(),
+            accepts: accepts.to_string(),
+            body,
+        }
+    }
+
+    /// The HTTP method of the call.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path of the server function being called.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The path and query string of the call.
+    pub fn uri(&self) -> String {
+        if self.query.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}?{}", self.path, self.query)
+        }
+    }
+
+    /// The `Content-Type` of the call.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// The `Accept` header of the call.
+    pub fn accepts(&self) -> &str {
+        &self.accepts
+    }
+
+    /// The body of the call.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        put_field(buf, self.method.as_str().as_bytes());
+        put_field(buf, self.path.as_bytes());
+        put_field(buf, self.query.as_bytes());
+        put_field(buf, self.content_type.as_bytes());
+        put_field(buf, self.accepts.as_bytes());
+        put_field(buf, &self.body);
+    }
+
+    fn decode(buf: &mut Bytes) -> Option<Self> {
+        let method = Method::from_bytes(&get_field(buf)?).ok()?;
+        Some(Self {
+            method,
+            path: get_string(buf)?,
+            query: get_string(buf)?,
+            content_type: get_string(buf)?,
+            accepts: get_string(buf)?,
+            body: get_field(buf)?,
+        })
+    }
+
+    /// Encodes a list of calls as the body of a batch request.
+    pub fn encode_all(requests: &[BatchRequest]) -> Bytes {
+        let mut buf = BytesMut::new();
+        for req in requests {
+            req.encode(&mut buf);
+        }
+        buf.freeze()
+    }
+
+    /// Decodes the body of a batch request into its calls.
+    ///
+    /// Fails if the body holds more than [`MAX_BATCH_CALLS`] calls.
+    pub fn decode_all(mut body: Bytes) -> Result<Vec<Self>, ServerFnError> {
+        let mut requests = Vec::new();
+        while body.has_remaining() {
+            if requests.len() == MAX_BATCH_CALLS {
+                return Err(ServerFnError::Args(format!(
+                    "a batch cannot hold more than {MAX_BATCH_CALLS} calls"
+                )));
+            }
+            let req = Self::decode(&mut body).ok_or_else(|| {
+                ServerFnError::<NoCustomError>::Deserialization(
+                    "invalid batch request body".into(),
+                )
+            })?;
+            requests.push(req);
+        }
+        Ok(requests)
+    }
+}
+
+impl<CustErr> ClientReq<CustErr> for BatchRequest {
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::GET,
+            path,
+            accepts,
+            content_type,
+            query,
+            Bytes::new(),
+        ))
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path,
+            accepts,
+            content_type,
+            "",
+            body.into(),
+        ))
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path,
+            accepts,
+            content_type,
+            "",
+            body,
+        ))
+    }
+
+    fn try_new_post_form_data(
+        path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch(path, "form data"))
+    }
+
+    fn try_new_multipart(
+        path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch(path, "multipart bodies"))
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch(path, "streaming bodies"))
+    }
+
+    fn try_clone_request(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+fn cannot_batch<CustErr>(path: &str, what: &str) -> ServerFnError<CustErr> {
+    ServerFnError::Request(format!(
+        "The server function at {path} cannot be batched, as batched calls \
+         cannot send {what}."
+    ))
+}
+
+/// The result of a single call in a batch, as received by the client.
+#[derive(Debug, Clone)]
+pub struct BatchResponse {
+    index: u32,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl BatchResponse {
+    /// Creates the result of the call at `index` in a batch.
+    pub fn new(
+        index: u32,
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Bytes,
+    ) -> Self {
+        Self {
+            index,
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// Encodes the result as a frame of the batch response.
+    pub fn encode(&self) -> Bytes {
+        let mut frame = BytesMut::new();
+        frame.put_u32(self.index);
+        frame.put_u16(self.status);
+        frame.put_u32(self.headers.len() as u32);
+        for (name, value) in &self.headers {
+            put_field(&mut frame, name.as_bytes());
+            put_field(&mut frame, value.as_bytes());
+        }
+        put_field(&mut frame, &self.body);
+
+        let mut buf = BytesMut::with_capacity(frame.len() + 4);
+        put_field(&mut buf, &frame);
+        buf.freeze()
+    }
+
+    fn decode(mut frame: Bytes) -> Option<Self> {
+        if frame.remaining() < 10 {
+            return None;
+        }
+        let index = frame.get_u32();
+        let status = frame.get_u16();
+        let count = frame.get_u32();
+        let headers = (0..count)
+            .map(|_| Some((get_string(&mut frame)?, get_string(&mut frame)?)))
+            .collect::<Option<_>>()?;
+        let body = get_field(&mut frame)?;
+        Some(Self {
+            index,
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+impl<CustErr> ClientRes<CustErr> for BatchResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        String::from_utf8(self.body.into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.body)
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        Ok(futures::stream::once(async move { Ok(self.body) }))
+    }
+
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn status_text(&self) -> String {
+        http::StatusCode::from_u16(self.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn location(&self) -> String {
+        ClientRes::<CustErr>::header(self, "Location").unwrap_or_default()
+    }
+
+    fn has_redirect(&self) -> bool {
+        ClientRes::<CustErr>::header(self, crate::redirect::REDIRECT_HEADER)
+            .is_some()
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// A [`Client`] that batches server function calls, and sends each batch with the client `C`.
+pub struct Batch<C>(PhantomData<C>);
+
+impl<CustErr, C> Client<CustErr> for Batch<C>
+where
+    C: Client<NoCustomError> + 'static,
+{
+    type Request = BatchRequest;
+    type Response = BatchResponse;
+
+    fn send(
+        req: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    {
+        let (batch, index) = PendingBatch::join::<C>(req);
+        async move {
+            let reader = batch.sent.await.map_err(|e| {
+                ServerFnError::Request(format!("batch request failed: {e}"))
+            })?;
+            reader
+                .result(index)
+                .await
+                .map_err(|e| ServerFnError::Request(e.to_string()))
+        }
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        C::sleep(duration)
+    }
+}
+
+type SentBatch = Shared<BoxFuture<'static, Result<Arc<BatchReader>, Arc<str>>>>;
+
+/// A batch that calls are still being added to.
+#[derive(Clone)]
+struct PendingBatch {
+    client: TypeId,
+    path: Arc<str>,
+    requests: Arc<Mutex<Vec<BatchRequest>>>,
+    sent: SentBatch,
+}
+
+/// The batches that have not been sent yet, one for each client.
+static PENDING: Mutex<Vec<PendingBatch>> = Mutex::new(Vec::new());
+
+impl PendingBatch {
+    /// Adds a call to the pending batch for the client `C` and the call's prefix, creating it if
+    /// necessary, and returns the batch with the index of the call in it.
+    fn join<C>(req: BatchRequest) -> (PendingBatch, u32)
+    where
+        C: Client<NoCustomError> + 'static,
+    {
+        let client = TypeId::of::<C>();
+        let path = batch_path(prefix_of(req.path()));
+        let mut pending = PENDING.lock().expect("lock poisoned");
+        let batch = pending.iter().find(|batch| {
+            batch.client == client
+                && *batch.path == path
+                && batch.requests.lock().expect("lock poisoned").len()
+                    < MAX_BATCH_CALLS
+        });
+        let batch = match batch {
+            Some(batch) => batch.clone(),
+            None => {
+                let path: Arc<str> = path.into();
+                let requests = Arc::new(Mutex::new(Vec::new()));
+                let batch = PendingBatch {
+                    client,
+                    path: Arc::clone(&path),
+                    requests: Arc::clone(&requests),
+                    sent: send_batch::<C>(path, requests).boxed().shared(),
+                };
+                pending.push(batch.clone());
+                batch
+            }
+        };
+        let mut requests = batch.requests.lock().expect("lock poisoned");
+        let index = requests.len() as u32;
+        requests.push(req);
+        drop(requests);
+        (batch, index)
+    }
+}
+
+/// Returns `Pending` once, after waking its task, so that the tasks that are already waiting to
+/// be polled run before it is ready.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// The prefix of a server function's path, which is everything before its last segment.
+fn prefix_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(prefix, _)| prefix)
+}
+
+async fn send_batch<C>(
+    path: Arc<str>,
+    requests: Arc<Mutex<Vec<BatchRequest>>>,
+) -> Result<Arc<BatchReader>, Arc<str>>
+where
+    C: Client<NoCustomError> + 'static,
+{
+    // let the other calls made in this tick join the batch: they are polled before this task
+    // is polled again
+    YieldNow(false).await;
+
+    // no more calls can join once the batch has been removed from the pending batches
+    PENDING
+        .lock()
+        .expect("lock poisoned")
+        .retain(|batch| !Arc::ptr_eq(&batch.requests, &requests));
+    let requests =
+        std::mem::take(&mut *requests.lock().expect("lock poisoned"));
+    let (senders, receivers) = (0..requests.len() as u32)
+        .map(|index| {
+            let (tx, rx) = oneshot::channel();
+            ((index, tx), (index, rx))
+        })
+        .unzip();
+    let body = BatchRequest::encode_all(&requests);
+
+    let req = C::Request::try_new_post_bytes(
+        &path,
+        BATCH_CONTENT_TYPE,
+        BATCH_CONTENT_TYPE,
+        body,
+    )
+    .map_err(|e| e.to_string())?;
+    let res = C::send(req).await.map_err(|e| e.to_string())?;
+    let status = res.status();
+    if !(200..300).contains(&status) {
+        let text = res.try_into_string().await.unwrap_or_default();
+        return Err(format!("{status} {text}").into());
+    }
+    let stream = res.try_into_stream().map_err(|e| e.to_string())?;
+    Ok(Arc::new(BatchReader::new(
+        Box::pin(stream),
+        senders,
+        receivers,
+    )))
+}
+
+type FrameStream =
+    Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync>>;
+
+/// Reads the results of a batch as they arrive, and hands each one to the call it belongs to.
+///
+/// There is no task that reads the response: instead, each call that is still waiting for its
+/// result takes turns reading the next frame, and sends the results that belong to the other
+/// calls to them.
+struct BatchReader {
+    stream: futures::lock::Mutex<(FrameStream, BytesMut)>,
+    senders: Mutex<HashMap<u32, oneshot::Sender<BatchResponse>>>,
+    receivers: Mutex<HashMap<u32, oneshot::Receiver<BatchResponse>>>,
+}
+
+impl BatchReader {
+    fn new(
+        stream: FrameStream,
+        senders: HashMap<u32, oneshot::Sender<BatchResponse>>,
+        receivers: HashMap<u32, oneshot::Receiver<BatchResponse>>,
+    ) -> Self {
+        Self {
+            stream: futures::lock::Mutex::new((stream, BytesMut::new())),
+            senders: Mutex::new(senders),
+            receivers: Mutex::new(receivers),
+        }
+    }
+
+    async fn result(&self, index: u32) -> Result<BatchResponse, Arc<str>> {
+        let ended = "the batch response ended before the result of this call";
+        let mut rx = self
+            .receivers
+            .lock()
+            .expect("lock poisoned")
+            .remove(&index)
+            .ok_or(ended)?;
+        loop {
+            let mut stream = match select(&mut rx, self.stream.lock()).await {
+                Either::Left((res, _)) => return res.map_err(|_| ended.into()),
+                Either::Right((stream, _)) => stream,
+            };
+            // another call may have read this result while we waited for the stream
+            if let Ok(Some(res)) = rx.try_recv() {
+                return Ok(res);
+            }
+
+            let (stream, buf) = &mut *stream;
+            let res = loop {
+                if let Some(frame) = next_frame(buf) {
+                    break BatchResponse::decode(frame);
+                }
+                match stream.next().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                    _ => break None,
+                }
+            };
+            let Some(res) = res else {
+                // the other calls are failed by dropping their senders
+                self.senders.lock().expect("lock poisoned").clear();
+                return Err(ended.into());
+            };
+            if res.index == index {
+                return Ok(res);
+            }
+            let tx = self
+                .senders
+                .lock()
+                .expect("lock poisoned")
+                .remove(&res.index);
+            if let Some(tx) = tx {
+                _ = tx.send(res);
+            }
+        }
+    }
+}
+
+/// Splits the next complete frame off the front of the buffer.
+fn next_frame(buf: &mut BytesMut) -> Option<Bytes> {
+    let len = u32::from_be_bytes(buf.get(..4)?.try_into().ok()?) as usize;
+    if buf.len() < 4 + len {
+        return None;
+    }
+    buf.advance(4);
+    Some(buf.split_to(len).freeze())
+}
+
+fn put_field(buf: &mut BytesMut, data: &[u8]) {
+    buf.put_u32(data.len() as u32);
+    buf.put_slice(data);
+}
+
+fn get_field(buf: &mut Bytes) -> Option<Bytes> {
+    if buf.remaining() < 4 {
+        return None;
+    }
+    let len = buf.get_u32() as usize;
+    (buf.remaining() >= len).then(|| buf.split_to(len))
+}
+
+fn get_string(buf: &mut Bytes) -> Option<String> {
+    String::from_utf8(get_field(buf)?.into()).ok()
+}
+
+/// Encodes the result of the call at `index` as a frame of the batch response.
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+async fn encode_result<Response>(index: usize, mut res: Response) -> Bytes
+where
+    Response: crate::response::Res<NoCustomError>,
+{
+    use crate::{error::ServerFnErrorSerde, response::Res};
+
+    let body = Res::<NoCustomError>::buffer_body(&mut res)
+        .await
+        .unwrap_or_else(|e| e.ser().unwrap_or_default().into());
+    BatchResponse::new(
+        index as u32,
+        Res::<NoCustomError>::status(&res),
+        Res::<NoCustomError>::headers(&res),
+        body,
+    )
+    .encode()
+}
+
+/// Runs each call of a batch request through the Axum server function registry.
+#[cfg(feature = "axum-no-default")]
+pub(crate) mod axum {
+    use super::{
+        encode_result, is_batch_path, BatchRequest, BATCH_CONTENT_TYPE,
+        MAX_CONCURRENT_CALLS,
+    };
+    use crate::{
+        error::{NoCustomError, ServerFnError},
+        middleware::Service,
+        request::Req,
+        response::Res,
+        ServerFnTraitObj,
+    };
+    use axum::body::Body;
+    use futures::StreamExt;
+    use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+    use std::{future::Future, pin::Pin};
+
+    /// The batch endpoint at `path`, to be added to the registry.
+    pub(crate) fn endpoint(
+        path: &'static str,
+    ) -> ServerFnTraitObj<Request<Body>, Response<Body>> {
+        ServerFnTraitObj::new(path, Method::POST, handle_batch, Vec::new)
+            .with_encodings(BATCH_CONTENT_TYPE, BATCH_CONTENT_TYPE)
+            .with_name("server_fn::batch::Batch")
+    }
+
+    fn handle_batch(
+        req: Request<Body>,
+    ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let path = parts.uri.path().to_string();
+            let body = Req::<NoCustomError>::try_into_bytes(
+                Request::from_parts(parts.clone(), body),
+            )
+            .await;
+            let requests = match body.and_then(BatchRequest::decode_all) {
+                Ok(requests) => requests,
+                Err(e) => {
+                    let mut res = Response::error_response(&path, &e);
+                    *res.status_mut() = StatusCode::BAD_REQUEST;
+                    return res;
+                }
+            };
+
+            let calls =
+                requests.into_iter().enumerate().map(move |(index, call)| {
+                    // each call is sent with the headers of the batch request
+                    let mut req = Request::from_parts(
+                        parts.clone(),
+                        Body::from(call.body().clone()),
+                    );
+                    *req.method_mut() = call.method().clone();
+                    let headers = req.headers_mut();
+                    headers.remove(header::CONTENT_LENGTH);
+                    for (name, value) in [
+                        (header::CONTENT_TYPE, call.content_type()),
+                        (header::ACCEPT, call.accepts()),
+                    ] {
+                        if let Ok(value) = HeaderValue::from_str(value) {
+                            headers.insert(name, value);
+                        }
+                    }
+                    let uri = call.uri().parse();
+                    async move {
+                        let res = match uri {
+                            Ok(uri) if !is_batch_path(call.path()) => {
+                                *req.uri_mut() = uri;
+                                run(call.path(), req).await
+                            }
+                            _ => not_found(call.path()),
+                        };
+                        encode_result(index, res).await
+                    }
+                });
+            let results = futures::stream::iter(calls)
+                .buffer_unordered(MAX_CONCURRENT_CALLS)
+                .map(Ok::<_, std::convert::Infallible>);
+
+            Response::builder()
+                .header(header::CONTENT_TYPE, BATCH_CONTENT_TYPE)
+                .body(Body::from_stream(results))
+                .expect("could not build Response")
+        })
+    }
+
+    async fn run(path: &str, req: Request<Body>) -> Response<Body> {
+        match crate::axum::get_server_fn_service(path, req.method().clone()) {
+            Some(mut service) => service.run(req).await,
+            None => not_found(path),
+        }
+    }
+
+    fn not_found(path: &str) -> Response<Body> {
+        let err = ServerFnError::<NoCustomError>::ServerError(format!(
+            "Could not find a server function at the route {path}."
+        ));
+        let mut res = Response::error_response(path, &err);
+        *res.status_mut() = StatusCode::NOT_FOUND;
+        res
+    }
+}
+
+/// Runs each call of a batch request through the Actix server function registry.
+#[cfg(feature = "actix")]
+pub(crate) mod actix {
+    use super::{
+        encode_result, is_batch_path, BatchRequest, BATCH_CONTENT_TYPE,
+        MAX_CONCURRENT_CALLS,
+    };
+    use crate::{
+        error::{NoCustomError, ServerFnError},
+        request::{
+            actix::{ActixRequest, CallHead},
+            Req,
+        },
+        response::{actix::ActixResponse, Res},
+        ServerFnTraitObj,
+    };
+    use actix_web::{
+        dev,
+        error::PayloadError,
+        http::{
+            header::{self, HeaderValue},
+            Method as ActixMethod,
+        },
+        web::Payload,
+        FromRequest, HttpRequest, HttpResponse,
+    };
+    use futures::StreamExt;
+    use http::Method;
+    use send_wrapper::SendWrapper;
+    use std::{future::Future, pin::Pin};
+
+    /// The batch endpoint at `path`, to be added to the registry.
+    pub(crate) fn endpoint(
+        path: &'static str,
+    ) -> ServerFnTraitObj<ActixRequest, ActixResponse> {
+        ServerFnTraitObj::new(path, Method::POST, handle_batch, Vec::new)
+            .with_encodings(BATCH_CONTENT_TYPE, BATCH_CONTENT_TYPE)
+            .with_name("server_fn::batch::Batch")
+    }
+
+    fn handle_batch(
+        req: ActixRequest,
+    ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
+        // Actix keeps the request on a single thread, like `ActixRequest` itself
+        Box::pin(SendWrapper::new(async move {
+            let batch = req.0 .0.clone();
+            let path = batch.path().to_string();
+            let body = Req::<NoCustomError>::try_into_bytes(req).await;
+            let requests = match body.and_then(BatchRequest::decode_all) {
+                Ok(requests) => requests,
+                Err(e) => {
+                    let mut res = ActixResponse::error_response(&path, &e);
+                    _ = Res::<NoCustomError>::set_status(&mut res, 400);
+                    return res;
+                }
+            };
+
+            let calls =
+                requests.into_iter().enumerate().map(move |(index, call)| {
+                    let req = call_request(&batch, &call);
+                    async move {
+                        let res = match req {
+                            Some(req) if !is_batch_path(call.path()) => {
+                                run(call.path(), req).await
+                            }
+                            _ => not_found(call.path()),
+                        };
+                        Ok::<_, std::convert::Infallible>(
+                            encode_result(index, res).await,
+                        )
+                    }
+                });
+            let results = futures::stream::iter(calls)
+                .buffer_unordered(MAX_CONCURRENT_CALLS);
+
+            ActixResponse::from(
+                HttpResponse::Ok()
+                    .insert_header((header::CONTENT_TYPE, BATCH_CONTENT_TYPE))
+                    .streaming(results),
+            )
+        }))
+    }
+
+    /// Builds the request for a call, which shares the `HttpRequest` of the batch request, but
+    /// has its own method, URI and body, and the headers of the batch request with the call's
+    /// content type.
+    fn call_request(
+        batch: &HttpRequest,
+        call: &BatchRequest,
+    ) -> Option<ActixRequest> {
+        let method =
+            ActixMethod::from_bytes(call.method().as_str().as_bytes()).ok()?;
+        let uri = call.uri().parse().ok()?;
+        let mut headers = batch.headers().clone();
+        headers.remove(header::CONTENT_LENGTH);
+        for (name, value) in [
+            (header::CONTENT_TYPE, call.content_type()),
+            (header::ACCEPT, call.accepts()),
+        ] {
+            headers.insert(name, HeaderValue::from_str(value).ok()?);
+        }
+
+        let body = call.body().clone();
+        let mut payload: dev::Payload = dev::Payload::Stream {
+            payload: Box::pin(futures::stream::once(async move {
+                Ok::<_, PayloadError>(body)
+            })),
+        };
+        let payload = Payload::from_request(batch, &mut payload)
+            .into_inner()
+            .ok()?;
+        Some(ActixRequest(
+            SendWrapper::new((batch.clone(), payload)),
+            Some(CallHead {
+                method,
+                uri,
+                headers,
+            }),
+        ))
+    }
+
+    async fn run(path: &str, req: ActixRequest) -> ActixResponse {
+        let method = req.method().clone();
+        match crate::actix::get_server_fn_service(path, &method) {
+            Some(mut service) => service.0.run(req).await,
+            None => not_found(path),
+        }
+    }
+
+    fn not_found(path: &str) -> ActixResponse {
+        let err = ServerFnError::<NoCustomError>::ServerError(format!(
+            "Could not find a server function at the route {path}."
+        ));
+        let mut res = ActixResponse::error_response(path, &err);
+        _ = Res::<NoCustomError>::set_status(&mut res, 404);
+        res
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use actix_web::{test::TestRequest, web::Data};
+        use bytes::Bytes;
+
+        #[test]
+        fn calls_share_the_batch_request() {
+            let batch = TestRequest::post()
+                .uri("/api/_batch")
+                .insert_header((header::CONTENT_TYPE, BATCH_CONTENT_TYPE))
+                .insert_header(("Authorization", "Bearer token"))
+                .app_data(Data::new(42u32))
+                .to_http_request();
+            let call = BatchRequest::new(
+                Method::GET,
+                "/api/list_posts",
+                "application/json",
+                "application/x-www-form-urlencoded",
+                "page=2",
+                Bytes::new(),
+            );
+
+            let req = call_request(&batch, &call).unwrap();
+            assert_eq!(req.method(), ActixMethod::GET);
+            assert_eq!(req.uri(), "/api/list_posts?page=2");
+            assert_eq!(
+                req.headers().get(header::CONTENT_TYPE).unwrap(),
+                "application/x-www-form-urlencoded"
+            );
+            assert_eq!(
+                req.headers().get("Authorization").unwrap(),
+                "Bearer token"
+            );
+            let (req, _) = req.take();
+            assert_eq!(*req.app_data::<Data<u32>>().unwrap().get_ref(), 42);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn call(path: &str, body: &'static str) -> BatchRequest {
+        BatchRequest::new(
+            Method::POST,
+            path,
+            "application/json",
+            "application/json",
+            "",
+            Bytes::from_static(body.as_bytes()),
+        )
+    }
+
+    #[test]
+    fn batch_paths_follow_the_prefix() {
+        assert_eq!(batch_path(prefix_of("/api/get_user123")), "/api/_batch");
+        assert_eq!(batch_path(prefix_of("/rpc/v2/get_user")), "/rpc/v2/_batch");
+        assert!(is_batch_path("/rpc/_batch"));
+        assert!(!is_batch_path("/api/get_user123"));
+    }
+
+    #[test]
+    fn requests_round_trip() {
+        let get = BatchRequest::new(
+            Method::GET,
+            "/api/list_posts",
+            "application/json",
+            "application/x-www-form-urlencoded",
+            "page=2",
+            Bytes::new(),
+        );
+        let requests = [call("/api/get_user", "{\"id\":1}"), get];
+
+        let decoded =
+            BatchRequest::decode_all(BatchRequest::encode_all(&requests))
+                .unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].method(), Method::POST);
+        assert_eq!(decoded[0].uri(), "/api/get_user");
+        assert_eq!(decoded[0].body(), "{\"id\":1}");
+        assert_eq!(decoded[1].method(), Method::GET);
+        assert_eq!(decoded[1].uri(), "/api/list_posts?page=2");
+        assert_eq!(
+            decoded[1].content_type(),
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(decoded[1].accepts(), "application/json");
+    }
+
+    #[test]
+    fn invalid_requests_are_rejected() {
+        let body = BatchRequest::encode_all(&[call("/api/get_user", "{}")]);
+        assert!(BatchRequest::decode_all(body.slice(..body.len() - 1)).is_err());
+
+        let full = vec![call("/api/get_user", "{}"); MAX_BATCH_CALLS];
+        assert!(
+            BatchRequest::decode_all(BatchRequest::encode_all(&full)).is_ok()
+        );
+        let over = vec![call("/api/get_user", "{}"); MAX_BATCH_CALLS + 1];
+        assert!(matches!(
+            BatchRequest::decode_all(BatchRequest::encode_all(&over)),
+            Err(ServerFnError::Args(_))
+        ));
+    }
+
+    #[test]
+    fn responses_round_trip_across_chunks() {
+        let res = BatchResponse::new(
+            3,
+            404,
+            vec![("Content-Type".into(), "application/json".into())],
+            Bytes::from_static(b"{\"error\":true}"),
+        );
+        let encoded = res.encode();
+
+        // the frame is only split off once all of it has arrived
+        let mut buf = BytesMut::from(&encoded[..5]);
+        assert!(next_frame(&mut buf).is_none());
+        buf.extend_from_slice(&encoded[5..]);
+        let decoded =
+            BatchResponse::decode(next_frame(&mut buf).unwrap()).unwrap();
+        assert!(buf.is_empty());
+
+        assert_eq!(decoded.index, 3);
+        assert_eq!(ClientRes::<NoCustomError>::status(&decoded), 404);
+        assert_eq!(
+            ClientRes::<NoCustomError>::header(&decoded, "content-type")
+                .as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(decoded.body, "{\"error\":true}");
+    }
+
+    #[test]
+    fn results_are_handed_to_their_calls() {
+        let (senders, receivers) = (0..3)
+            .map(|index| {
+                let (tx, rx) = oneshot::channel();
+                ((index, tx), (index, rx))
+            })
+            .unzip();
+        // the results arrive out of order, with two of them in one chunk
+        let frame = |index: u32| {
+            BatchResponse::new(index, 200, Vec::new(), index.to_string().into())
+                .encode()
+        };
+        let chunks =
+            vec![Ok([frame(2), frame(0)].concat().into()), Ok(frame(1))];
+        let reader = BatchReader::new(
+            Box::pin(futures::stream::iter(chunks)),
+            senders,
+            receivers,
+        );
+
+        for index in 0..3 {
+            let res = block_on(reader.result(index)).unwrap();
+            assert_eq!(res.body, index.to_string());
+        }
+    }
+
+    #[cfg(feature = "generic")]
+    #[test]
+    fn calls_made_together_share_a_batch() {
+        use http::{Request, Response};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static SENT: AtomicUsize = AtomicUsize::new(0);
+
+        /// Answers each call in a batch with its own body, and waits without yielding.
+        struct EchoClient;
+
+        impl Client<NoCustomError> for EchoClient {
+            type Request = Request<Bytes>;
+            type Response = Response<Bytes>;
+
+            async fn send(
+                req: Request<Bytes>,
+            ) -> Result<Response<Bytes>, ServerFnError> {
+                SENT.fetch_add(1, Ordering::SeqCst);
+                let calls = BatchRequest::decode_all(req.into_body())?;
+                let frames = calls.iter().enumerate().map(|(index, call)| {
+                    BatchResponse::new(
+                        index as u32,
+                        200,
+                        Vec::new(),
+                        call.body().clone(),
+                    )
+                    .encode()
+                });
+                Ok(Response::new(frames.collect::<Vec<_>>().concat().into()))
+            }
+
+            async fn sleep(_duration: Duration) {}
+        }
+
+        let (a, b) = block_on(futures::future::join(
+            <Batch<EchoClient> as Client<NoCustomError>>::send(call(
+                "/api/get_user",
+                "1",
+            )),
+            <Batch<EchoClient> as Client<NoCustomError>>::send(call(
+                "/api/get_user",
+                "2",
+            )),
+        ));
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!((a.body.as_ref(), b.body.as_ref()), (&b"1"[..], &b"2"[..]));
+        assert_eq!(SENT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn missing_results_fail_their_calls() {
+        let (senders, receivers) = (0..2)
+            .map(|index| {
+                let (tx, rx) = oneshot::channel();
+                ((index, tx), (index, rx))
+            })
+            .unzip();
+        let chunks =
+            vec![Ok(
+                BatchResponse::new(1, 200, Vec::new(), Bytes::new()).encode()
+            )];
+        let reader = BatchReader::new(
+            Box::pin(futures::stream::iter(chunks)),
+            senders,
+            receivers,
+        );
+
+        assert!(block_on(reader.result(0)).is_err());
+        assert!(block_on(reader.result(1)).is_ok());
+    }
+}
@@ -1,7 +1,13 @@
-use crate::{error::ServerFnError, request::ClientReq, response::ClientRes};
-use std::{future::Future, sync::OnceLock};
+use crate::{
+    codec::SseMessage, error::ServerFnError, request::ClientReq,
+    response::ClientRes,
+};
+use bytes::Bytes;
+use futures::{Sink, Stream};
+use std::{future::Future, sync::OnceLock, time::Duration};
 
 static ROOT_URL: OnceLock<&'static str> = OnceLock::new();
+static CALL_OPTIONS: OnceLock<CallOptions> = OnceLock::new();
 
 /// Set the root server URL that all server function paths are relative to for the client.
 ///
@@ -15,6 +21,70 @@ pub fn get_server_url() -> &'static str {
     ROOT_URL.get().copied().unwrap_or("")
 }
 
+/// Set the default [`CallOptions`] for every server function called from the client.
+///
+/// Individual server functions can override these with the `timeout` and `retries`
+/// arguments to the `#[server]` macro.
+pub fn set_call_options(options: CallOptions) {
+    CALL_OPTIONS.set(options).unwrap();
+}
+
+/// Returns the default [`CallOptions`] for all server functions.
+pub fn get_call_options() -> CallOptions {
+    CALL_OPTIONS.get().copied().unwrap_or_default()
+}
+
+/// Controls how a server function call is made by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallOptions {
+    /// How long to wait for the server to respond before failing with
+    /// [`ServerFnError::Timeout`]. Defaults to no timeout.
+    pub timeout: Option<Duration>,
+    /// How many times to retry a request that failed because of a network error, a timeout,
+    /// or a `429`, `502`, `503` or `504` response. Defaults to `0`.
+    ///
+    /// Only requests that use the `GET` method are retried, as they are expected to be
+    /// idempotent.
+    pub retries: u32,
+    /// How long to wait before the first retry. The delay is doubled for each following
+    /// retry. Defaults to 100ms.
+    pub backoff: Duration,
+    /// The longest delay between two retries. Defaults to 10s.
+    pub max_backoff: Duration,
+}
+
+impl Default for CallOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            retries: 0,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl CallOptions {
+    /// Sets the timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the number of retries.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// The delay before the given retry, starting from `0`.
+    pub(crate) fn backoff_for(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
 /// A client defines a pair of request/response types and the logic to send
 /// and receive them.
 ///
@@ -23,14 +93,85 @@ pub fn get_server_url() -> &'static str {
 /// yourself, unless you’re trying to use an alternative HTTP crate on the client side.
 pub trait Client<CustErr> {
     /// The type of a request sent by this client.
-    type Request: ClientReq<CustErr> + Send;
+    type Request: ClientReq<CustErr> + Send + 'static;
     /// The type of a response received by this client.
-    type Response: ClientRes<CustErr> + Send;
+    type Response: ClientRes<CustErr> + Send + 'static;
 
     /// Sends the request and receives a response.
     fn send(
         req: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+
+    /// Waits for the given duration before a request is retried.
+    ///
+    /// This has no default, as waiting needs a timer from the client's async runtime: a
+    /// native client can use its runtime's `sleep`, and a client for `wasm32` must use a
+    /// browser timer such as `setTimeout`. Clients that wrap `BrowserClient` or
+    /// `ReqwestClient` can delegate to their implementation.
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Opens a websocket connection to the server function at the given path.
+    ///
+    /// Returns a stream of incoming frames and a sink for outgoing frames. Data is sent
+    /// as binary frames (`Ok(_)`), and errors as text frames (`Err(_)`).
+    ///
+    /// By default, this returns an error, as not every client supports websockets.
+    #[allow(clippy::type_complexity)]
+    fn open_websocket(
+        path: &str,
+    ) -> impl Future<
+        Output = Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            ),
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        let path = path.to_string();
+        async move {
+            Err::<
+                (
+                    futures::stream::Empty<Result<Bytes, Bytes>>,
+                    futures::sink::Drain<Result<Bytes, Bytes>>,
+                ),
+                _,
+            >(ServerFnError::Request(format!(
+                "This client does not support websockets, so the server \
+                 function at {path} cannot be called."
+            )))
+        }
+    }
+
+    /// Opens an [`EventSource`] for a request to a server function with the
+    /// [`EventStream`](crate::codec::EventStream) output encoding, returning the events it
+    /// receives. An error (`Err(_)`) is returned once if the connection fails, and is not
+    /// reestablished.
+    ///
+    /// By default, this returns `None`, and the request is sent with [`send`](Client::send)
+    /// instead, with the events being read from the body of the response.
+    ///
+    /// [`EventSource`]: https://developer.mozilla.org/en-US/docs/Web/API/EventSource
+    #[allow(clippy::type_complexity)]
+    fn open_event_source(
+        req: &Self::Request,
+    ) -> Option<impl Stream<Item = Result<SseMessage, String>> + Send + 'static>
+    {
+        _ = req;
+        None::<futures::stream::Empty<_>>
+    }
+}
+
+/// Converts the URL of a server function into the URL used to open a websocket.
+#[allow(dead_code)] // not used by every client
+fn websocket_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        url.to_string()
+    }
 }
 
 #[cfg(feature = "browser")]
@@ -38,14 +179,28 @@ pub trait Client<CustErr> {
 pub mod browser {
     use super::Client;
     use crate::{
+        client::get_server_url,
+        codec::{SseMessage, END_EVENT, ERROR_EVENT, MESSAGE_EVENT},
         error::ServerFnError,
-        request::browser::{BrowserRequest, RequestInner},
-        response::browser::BrowserResponse,
+        middleware::csrf::{token_from_cookies, CSRF_HEADER},
+        request::browser::{abort_error, BrowserRequest, RequestInner},
+        response::browser::{BrowserResponse, ResponseInner},
     };
+    use bytes::Bytes;
+    use futures::{Sink, Stream};
+    use gloo_net::http::{Headers, Method, Response};
     use send_wrapper::SendWrapper;
-    use std::future::Future;
+    use std::{
+        cell::RefCell, collections::HashMap, future::Future, time::Duration,
+    };
 
-    /// Implements [`Client`] for a `fetch` request in the browser.    
+    /// Implements [`Client`] for a `fetch` request in the browser.
+    ///
+    /// Responses to server functions that declare `cache` are kept in an in-memory cache
+    /// according to their `Cache-Control`, `ETag` and `Vary` headers: a fresh response is
+    /// reused without making a request, and a stale response with an `ETag` is revalidated
+    /// with `If-None-Match`. Responses marked `no-store`, `no-cache` or `private` are not
+    /// stored. The cache can be emptied with [`clear_response_cache`].
     pub struct BrowserClient;
 
     impl<CustErr> Client<CustErr> for BrowserClient {
@@ -60,33 +215,423 @@ pub mod browser {
                 let req = req.0.take();
                 let RequestInner {
                     request,
-                    mut abort_ctrl,
+                    abort_ctrl,
+                    timeout,
+                    upload,
+                    cache,
                 } = req;
-                let res = request
-                    .send()
-                    .await
-                    .map(|res| BrowserResponse(SendWrapper::new(res)))
-                    .map_err(|e| ServerFnError::Request(e.to_string()));
-
-                // at this point, the future has successfully resolved without being dropped, so we
-                // can prevent the `AbortController` from firing
-                if let Some(ctrl) = abort_ctrl.as_mut() {
-                    ctrl.prevent_cancellation();
+
+                let cache_key = (cache && request.method() == Method::GET)
+                    .then(|| request.url());
+                if let Some(url) = &cache_key {
+                    match cached_response(url, &request.headers()) {
+                        Some(Ok(response)) => {
+                            return Ok(BrowserResponse(SendWrapper::new(
+                                ResponseInner {
+                                    response,
+                                    abort_ctrl: None,
+                                    timer: None,
+                                },
+                            )))
+                        }
+                        Some(Err(etag)) => {
+                            request.headers().set("If-None-Match", &etag)
+                        }
+                        None => {}
+                    }
+                }
+
+                if request.method() != Method::GET {
+                    if let Some(token) = csrf_token() {
+                        request.headers().set(CSRF_HEADER, &token);
+                    }
+                }
+
+                if let Some(upload) = upload {
+                    let response = upload.send(&request, timeout).await?;
+                    return Ok(BrowserResponse(SendWrapper::new(
+                        ResponseInner {
+                            response,
+                            abort_ctrl: None,
+                            timer: None,
+                        },
+                    )));
                 }
-                res
+
+                let timer = abort_ctrl
+                    .as_ref()
+                    .zip(timeout)
+                    .and_then(|(ctrl, timeout)| ctrl.abort_after(timeout));
+                let res = match cache_key {
+                    Some(url) => {
+                        let request_headers = request.headers();
+                        match request.send().await {
+                            Ok(response) => {
+                                cache_response(&url, &request_headers, response)
+                                    .await
+                            }
+                            Err(e) => Err(e),
+                        }
+                    }
+                    None => request.send().await,
+                };
+                match res {
+                    // the `AbortController` is kept alive by the response, so that the request
+                    // is cancelled if the response is dropped before its body has been read
+                    Ok(response) => {
+                        Ok(BrowserResponse(SendWrapper::new(ResponseInner {
+                            response,
+                            abort_ctrl,
+                            timer,
+                        })))
+                    }
+                    Err(e) => Err(abort_error(&e, timer.as_ref())
+                        .unwrap_or_else(|| {
+                            ServerFnError::Request(e.to_string())
+                        })),
+                }
+            })
+        }
+
+        fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+            SendWrapper::new(async move {
+                let Some(window) = web_sys::window() else {
+                    return;
+                };
+                let promise = js_sys::Promise::new(&mut |resolve, _| {
+                    let millis =
+                        duration.as_millis().try_into().unwrap_or(i32::MAX);
+                    if window
+                        .set_timeout_with_callback_and_timeout_and_arguments_0(
+                            &resolve, millis,
+                        )
+                        .is_err()
+                    {
+                        _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+                    }
+                });
+                _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+            })
+        }
+
+        fn open_websocket(
+            path: &str,
+        ) -> impl Future<
+            Output = Result<
+                (
+                    impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                    impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+                ),
+                ServerFnError<CustErr>,
+            >,
+        > + Send {
+            let url = websocket_url(path);
+            SendWrapper::new(async move {
+                use futures::{channel::mpsc, SinkExt, StreamExt};
+                use gloo_net::websocket::{futures::WebSocket, Message};
+
+                let websocket = WebSocket::open(&url)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (ws_sink, ws_stream) = websocket.split();
+
+                // the browser `WebSocket` is !Send, so outgoing frames are sent through a
+                // channel and forwarded by a local task
+                let (outgoing_tx, outgoing_rx) =
+                    mpsc::channel::<Result<Bytes, Bytes>>(2048);
+                wasm_bindgen_futures::spawn_local(async move {
+                    let mut ws_sink = ws_sink;
+                    let mut frames = outgoing_rx.map(|frame| {
+                        Ok(match frame {
+                            Ok(data) => Message::Bytes(data.to_vec()),
+                            Err(err) => Message::Text(
+                                String::from_utf8_lossy(&err).into_owned(),
+                            ),
+                        })
+                    });
+                    let _ = ws_sink.send_all(&mut frames).await;
+                });
+
+                let incoming = ws_stream
+                    .take_while(|msg| std::future::ready(msg.is_ok()))
+                    .filter_map(|msg| {
+                        std::future::ready(match msg {
+                            Ok(Message::Bytes(data)) => Some(Ok(data.into())),
+                            Ok(Message::Text(err)) => Some(Err(err.into())),
+                            Err(_) => None,
+                        })
+                    });
+
+                Ok((SendWrapper::new(incoming), outgoing_tx))
             })
         }
+
+        fn open_event_source(
+            req: &Self::Request,
+        ) -> Option<
+            impl Stream<Item = Result<SseMessage, String>> + Send + 'static,
+        > {
+            use futures::StreamExt;
+            use gloo_net::eventsource::futures::EventSource;
+
+            // an `EventSource` can only make `GET` requests
+            if req.method() != Method::GET {
+                return None;
+            }
+            let mut source = EventSource::new(&req.url()).ok()?;
+            let subscriptions = [MESSAGE_EVENT, ERROR_EVENT, END_EVENT]
+                .into_iter()
+                .map(|event| source.subscribe(event).ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            // each subscription reports the connection failing, so the stream ends after the
+            // first error
+            let messages = futures::stream::select_all(subscriptions).scan(
+                false,
+                move |failed, message| {
+                    // the `EventSource` is closed when the stream is dropped
+                    let _ = &source;
+                    if *failed {
+                        return std::future::ready(None);
+                    }
+                    std::future::ready(Some(match message {
+                        Ok((event, message)) => {
+                            let id = message.last_event_id();
+                            Ok(SseMessage {
+                                id: (!id.is_empty()).then_some(id),
+                                event: (event != MESSAGE_EVENT)
+                                    .then_some(event),
+                                data: message
+                                    .data()
+                                    .as_string()
+                                    .unwrap_or_default(),
+                            })
+                        }
+                        Err(e) => {
+                            *failed = true;
+                            Err(e.to_string())
+                        }
+                    }))
+                },
+            );
+            Some(SendWrapper::new(messages))
+        }
+    }
+
+    /// Returns the CSRF token that the server stored in a cookie while rendering the page, if any.
+    ///
+    /// [`BrowserClient`] sends this token back in a header with every request that is not a
+    /// `GET` request, so that it passes the server’s
+    /// [`CsrfLayer`](crate::middleware::csrf::CsrfLayer).
+    pub fn csrf_token() -> Option<String> {
+        let document =
+            js_sys::Reflect::get(&js_sys::global(), &"document".into()).ok()?;
+        let cookies = js_sys::Reflect::get(&document, &"cookie".into())
+            .ok()?
+            .as_string()?;
+        token_from_cookies(&cookies).map(str::to_string)
+    }
+
+    thread_local! {
+        // the browser is single-threaded, so the cache is kept per thread
+        static RESPONSE_CACHE: RefCell<HashMap<String, CachedResponse>> =
+            Default::default();
+    }
+
+    /// Removes every response from the in-memory cache used by [`BrowserClient`].
+    pub fn clear_response_cache() {
+        RESPONSE_CACHE.with_borrow_mut(HashMap::clear);
+    }
+
+    struct CachedResponse {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+        etag: Option<String>,
+        /// The request headers named by `Vary`, and their values when the response was stored.
+        vary: Vec<(String, Option<String>)>,
+        // in milliseconds since the epoch
+        fresh_until: f64,
+    }
+
+    impl CachedResponse {
+        fn to_response(&self) -> Result<Response, gloo_net::Error> {
+            let headers = Headers::new();
+            for (name, value) in &self.headers {
+                headers.append(name, value);
+            }
+            let mut body = self.body.clone();
+            Response::builder()
+                .status(self.status)
+                .headers(headers)
+                .body(Some(body.as_mut_slice()))
+        }
+    }
+
+    /// Returns the cached response for this URL if it is still fresh or, if it is stale, the
+    /// `ETag` with which it can be revalidated.
+    ///
+    /// Nothing is returned if the response was stored for a request with different values for
+    /// the headers it varies by.
+    fn cached_response(
+        url: &str,
+        request_headers: &Headers,
+    ) -> Option<Result<Response, String>> {
+        RESPONSE_CACHE.with_borrow(|cache| {
+            let cached = cache.get(url)?;
+            if cached
+                .vary
+                .iter()
+                .any(|(name, value)| request_headers.get(name) != *value)
+            {
+                return None;
+            }
+            if cached.fresh_until > js_sys::Date::now() {
+                cached.to_response().ok().map(Ok)
+            } else {
+                cached.etag.clone().map(Err)
+            }
+        })
+    }
+
+    /// Stores a response in the cache if its headers allow it, or returns the cached response
+    /// if the server says it has not been modified.
+    async fn cache_response(
+        url: &str,
+        request_headers: &Headers,
+        response: Response,
+    ) -> Result<Response, gloo_net::Error> {
+        let headers = response.headers();
+        let max_age = freshness(headers.get("Cache-Control").as_deref());
+        match response.status() {
+            304 => RESPONSE_CACHE
+                .with_borrow_mut(|cache| {
+                    let cached = cache.get_mut(url)?;
+                    cached.fresh_until =
+                        js_sys::Date::now() + max_age.unwrap_or_default();
+                    Some(cached.to_response())
+                })
+                .unwrap_or(Ok(response)),
+            200 => {
+                let etag = headers.get("ETag");
+                let Some(max_age) = max_age else {
+                    return Ok(response);
+                };
+                if etag.is_none() && max_age <= 0.0 {
+                    return Ok(response);
+                }
+                let Some(vary) = vary(headers.get("Vary").as_deref()) else {
+                    return Ok(response);
+                };
+                let cached = CachedResponse {
+                    status: 200,
+                    headers: headers.entries().collect(),
+                    body: response.binary().await?,
+                    etag,
+                    vary: vary
+                        .into_iter()
+                        .map(|name| {
+                            let value = request_headers.get(&name);
+                            (name, value)
+                        })
+                        .collect(),
+                    fresh_until: js_sys::Date::now() + max_age,
+                };
+                let response = cached.to_response();
+                RESPONSE_CACHE.with_borrow_mut(|cache| {
+                    cache.insert(url.to_string(), cached)
+                });
+                response
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// How long a response can be reused without revalidating it, in milliseconds, or `None`
+    /// if it should not be stored at all.
+    ///
+    /// Responses that must be revalidated on every use (`no-cache`) or that are specific to
+    /// one user (`private`) are not stored, as this cache does not know who the user is.
+    fn freshness(cache_control: Option<&str>) -> Option<f64> {
+        let mut max_age = 0.0;
+        for directive in cache_control.unwrap_or_default().split(',') {
+            let directive = directive.trim();
+            let name = directive.split('=').next().unwrap_or_default();
+            if ["no-store", "no-cache", "private"]
+                .iter()
+                .any(|skipped| name.eq_ignore_ascii_case(skipped))
+            {
+                return None;
+            } else if let Some(seconds) = directive.strip_prefix("max-age=") {
+                max_age = seconds.parse::<f64>().unwrap_or_default() * 1000.0;
+            }
+        }
+        Some(max_age)
+    }
+
+    /// The names of the request headers that a response varies by, or `None` if it varies by
+    /// something other than request headers (`*`), so it cannot be reused.
+    fn vary(vary: Option<&str>) -> Option<Vec<String>> {
+        let mut names = Vec::new();
+        for name in vary.unwrap_or_default().split(',') {
+            let name = name.trim();
+            if name == "*" {
+                return None;
+            } else if !name.is_empty() {
+                names.push(name.to_ascii_lowercase());
+            }
+        }
+        Some(names)
+    }
+
+    /// Resolves a server function path against the server URL or, if none has been set,
+    /// the current page, and converts it into a websocket URL.
+    fn websocket_url(path: &str) -> String {
+        let server_url = get_server_url();
+        let base = if server_url.is_empty() {
+            web_sys::window()
+                .and_then(|window| window.location().origin().ok())
+                .unwrap_or_default()
+        } else {
+            server_url.to_string()
+        };
+        super::websocket_url(&format!("{base}{path}"))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{freshness, vary};
+
+        #[test]
+        fn only_stores_shared_responses() {
+            assert_eq!(freshness(Some("public, max-age=60")), Some(60_000.0));
+            assert_eq!(freshness(None), Some(0.0));
+            assert_eq!(freshness(Some("max-age=60, no-store")), None);
+            assert_eq!(freshness(Some("no-cache")), None);
+            assert_eq!(freshness(Some("Private, max-age=60")), None);
+            assert_eq!(freshness(Some(r#"private="Set-Cookie""#)), None);
+        }
+
+        #[test]
+        fn reads_the_headers_a_response_varies_by() {
+            assert_eq!(vary(None), Some(vec![]));
+            assert_eq!(
+                vary(Some("Accept-Language, Cookie")),
+                Some(vec!["accept-language".to_string(), "cookie".to_string()])
+            );
+            assert_eq!(vary(Some("Accept, *")), None);
+        }
     }
 }
 
 #[cfg(feature = "reqwest")]
 /// Implements [`Client`] for a request made by [`reqwest`].
 pub mod reqwest {
-    use super::Client;
+    use super::{get_server_url, websocket_url, Client};
     use crate::{error::ServerFnError, request::reqwest::CLIENT};
-    use futures::TryFutureExt;
+    use bytes::Bytes;
+    use futures::{Sink, SinkExt, Stream, StreamExt, TryFutureExt};
     use reqwest::{Request, Response};
-    use std::future::Future;
+    use std::{future::Future, time::Duration};
 
     /// Implements [`Client`] for a request made by [`reqwest`].
     pub struct ReqwestClient;
@@ -99,9 +644,224 @@ pub mod reqwest {
             req: Self::Request,
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
-            CLIENT
-                .execute(req)
-                .map_err(|e| ServerFnError::Request(e.to_string()))
+            CLIENT.execute(req).map_err(|e| {
+                if e.is_timeout() {
+                    ServerFnError::Timeout(e.to_string())
+                } else {
+                    ServerFnError::Request(e.to_string())
+                }
+            })
+        }
+
+        fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+            tokio::time::sleep(duration)
+        }
+
+        async fn open_websocket(
+            path: &str,
+        ) -> Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            ),
+            ServerFnError<CustErr>,
+        > {
+            use tokio_tungstenite::tungstenite::Message;
+
+            let url = websocket_url(&format!("{}{}", get_server_url(), path));
+            let (websocket, _) = tokio_tungstenite::connect_async(url)
+                .await
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+            let (ws_sink, ws_stream) = websocket.split();
+
+            // data is sent as binary frames, errors as text frames
+            let outgoing = ws_sink.with(|frame: Result<Bytes, Bytes>| {
+                std::future::ready(
+                    Ok::<_, tokio_tungstenite::tungstenite::Error>(
+                        match frame {
+                            Ok(data) => Message::Binary(data.to_vec()),
+                            Err(err) => Message::Text(
+                                String::from_utf8_lossy(&err).into_owned(),
+                            ),
+                        },
+                    ),
+                )
+            });
+            let incoming = ws_stream
+                .take_while(|msg| {
+                    std::future::ready(matches!(
+                        msg,
+                        Ok(msg) if !msg.is_close()
+                    ))
+                })
+                .filter_map(|msg| {
+                    std::future::ready(match msg {
+                        Ok(Message::Binary(data)) => Some(Ok(data.into())),
+                        Ok(Message::Text(err)) => Some(Err(err.into())),
+                        _ => None,
+                    })
+                });
+
+            Ok((incoming, outgoing))
         }
     }
 }
+
+#[cfg(all(test, feature = "generic", feature = "url"))]
+mod tests {
+    use super::*;
+    use crate::{
+        codec::{GetUrl, Json, PostUrl},
+        error::NoCustomError,
+        response::generic::Body,
+        ServerFn,
+    };
+    use futures::executor::block_on;
+    use http::{Request, Response};
+    use serde::{Deserialize, Serialize};
+    use std::{sync::Mutex, time::Duration};
+
+    /// What the client responds with, and how many requests it sent.
+    #[derive(Default)]
+    struct Calls {
+        responses: Vec<Result<u16, ServerFnError>>,
+        sent: usize,
+    }
+
+    thread_local! {
+        // each test blocks on its call, so the client sleeps on the test's thread
+        static SLEPT: std::cell::RefCell<Vec<Duration>> = Default::default();
+    }
+
+    // each test uses its own server function path, as the tests run in parallel
+    static CALLS: Mutex<Vec<(&'static str, Calls)>> = Mutex::new(Vec::new());
+
+    fn with_calls<T>(path: &str, f: impl FnOnce(&mut Calls) -> T) -> T {
+        let mut calls = CALLS.lock().unwrap();
+        let index = match calls.iter().position(|(p, _)| *p == path) {
+            Some(index) => index,
+            None => {
+                let path = path.to_string().leak();
+                calls.push((path, Calls::default()));
+                calls.len() - 1
+            }
+        };
+        f(&mut calls[index].1)
+    }
+
+    /// Responds with the next of the responses set up for the path.
+    struct ScriptedClient;
+
+    impl Client<NoCustomError> for ScriptedClient {
+        type Request = Request<Bytes>;
+        type Response = Response<Bytes>;
+
+        async fn send(
+            req: Request<Bytes>,
+        ) -> Result<Response<Bytes>, ServerFnError> {
+            let status = with_calls(req.uri().path(), |calls| {
+                calls.sent += 1;
+                calls.responses.remove(0)
+            })?;
+            let mut res = Response::new(Bytes::from_static(b"\"done\""));
+            *res.status_mut() = http::StatusCode::from_u16(status).unwrap();
+            Ok(res)
+        }
+
+        async fn sleep(duration: Duration) {
+            SLEPT.with_borrow_mut(|slept| slept.push(duration));
+        }
+    }
+
+    macro_rules! scripted_server_fn {
+        ($name:ident, $path:literal, $encoding:ty) => {
+            #[derive(Serialize, Deserialize)]
+            struct $name;
+
+            impl ServerFn for $name {
+                const PATH: &'static str = $path;
+                type Client = ScriptedClient;
+                type ServerRequest = Request<Bytes>;
+                type ServerResponse = Response<Body>;
+                type Output = String;
+                type InputEncoding = $encoding;
+                type OutputEncoding = Json;
+                type Error = NoCustomError;
+
+                fn call_options() -> CallOptions {
+                    CallOptions::default().retries(3)
+                }
+
+                async fn run_body(self) -> Result<String, ServerFnError> {
+                    unreachable!()
+                }
+            }
+        };
+    }
+
+    scripted_server_fn!(RetriedGet, "/api/retried_get", GetUrl);
+    scripted_server_fn!(TimedOutGet, "/api/timed_out_get", GetUrl);
+    scripted_server_fn!(FailedGet, "/api/failed_get", GetUrl);
+    scripted_server_fn!(NotRetriedPost, "/api/not_retried_post", PostUrl);
+
+    fn script(path: &str, responses: Vec<Result<u16, ServerFnError>>) {
+        with_calls(path, |calls| calls.responses = responses);
+    }
+
+    #[test]
+    fn retries_get_requests_with_backoff() {
+        script(RetriedGet::PATH, vec![Ok(503), Ok(429), Ok(200)]);
+        assert_eq!(block_on(RetriedGet.run_on_client()), Ok("done".into()));
+        with_calls(RetriedGet::PATH, |calls| assert_eq!(calls.sent, 3));
+        assert_eq!(
+            SLEPT.take(),
+            [Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[test]
+    fn gives_up_after_the_last_retry() {
+        script(
+            TimedOutGet::PATH,
+            (0..4)
+                .map(|_| Err(ServerFnError::Timeout("too slow".into())))
+                .collect(),
+        );
+        assert_eq!(
+            block_on(TimedOutGet.run_on_client()),
+            Err(ServerFnError::Timeout("too slow".into()))
+        );
+        with_calls(TimedOutGet::PATH, |calls| assert_eq!(calls.sent, 4));
+    }
+
+    #[test]
+    fn does_not_retry_other_errors() {
+        script(
+            FailedGet::PATH,
+            vec![Err(ServerFnError::Cancelled("aborted".into())), Ok(200)],
+        );
+        assert_eq!(
+            block_on(FailedGet.run_on_client()),
+            Err(ServerFnError::Cancelled("aborted".into()))
+        );
+        with_calls(FailedGet::PATH, |calls| assert_eq!(calls.sent, 1));
+    }
+
+    #[test]
+    fn does_not_retry_post_requests() {
+        script(NotRetriedPost::PATH, vec![Ok(503), Ok(200)]);
+        assert!(block_on(NotRetriedPost.run_on_client()).is_err());
+        with_calls(NotRetriedPost::PATH, |calls| assert_eq!(calls.sent, 1));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let options = CallOptions {
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            ..CallOptions::default()
+        };
+        let delays = (0..5).map(|retry| options.backoff_for(retry).as_secs());
+        assert_eq!(delays.collect::<Vec<_>>(), [1, 2, 4, 5, 5]);
+    }
+}
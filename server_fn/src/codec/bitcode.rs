@@ -0,0 +1,190 @@
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bitcode::{DecodeOwned, Encode};
+use bytes::Bytes;
+use http::Method;
+use std::convert::Infallible;
+
+/// A codec for [`bitcode`], a compact binary format that is fast to encode and decode.
+///
+/// The arguments and the output of the server function must implement [`bitcode::Encode`] and
+/// [`bitcode::Decode`], which can be derived. When `Bitcode` is used as the input encoding of
+/// the `#[server]` macro, these are derived for the arguments automatically.
+pub struct Bitcode;
+
+impl Encoding for Bitcode {
+    const CONTENT_TYPE: &'static str = "application/x-bitcode";
+    const METHOD: Method = Method::POST;
+}
+
+impl<T> Encodes<T> for Bitcode
+where
+    T: Encode,
+{
+    type Error = Infallible;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        Ok(Bytes::from(bitcode::encode(value)))
+    }
+}
+
+impl<T> Decodes<T> for Bitcode
+where
+    T: DecodeOwned,
+{
+    type Error = bitcode::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        bitcode::decode(&bytes)
+    }
+}
+
+impl<T, Request, Err> IntoReq<Bitcode, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Encode,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Bitcode::CONTENT_TYPE,
+            Bytes::from(bitcode::encode(&self)),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Bitcode, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: DecodeOwned,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        bitcode::decode(&data).map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<T, Response, Err> IntoRes<Bitcode, Response, Err> for T
+where
+    Response: Res<Err>,
+    T: Encode + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        Response::try_from_bytes(
+            Bitcode::CONTENT_TYPE,
+            Bytes::from(bitcode::encode(&self)),
+        )
+    }
+}
+
+impl<T, Response, Err> FromRes<Bitcode, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: DecodeOwned,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        bitcode::decode(&data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::Bitcode;
+    use crate::{
+        codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes},
+        error::NoCustomError,
+        response::generic::Body,
+    };
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::{header::CONTENT_TYPE, Request, Response};
+
+    #[derive(Debug, Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
+    struct Point {
+        x: i32,
+        y: i32,
+        label: String,
+        data: Vec<u8>,
+    }
+
+    fn point() -> Point {
+        Point {
+            x: -4,
+            y: 1 << 20,
+            label: "origin".into(),
+            data: vec![0, 1, 2, 255],
+        }
+    }
+
+    #[test]
+    fn request_round_trip() {
+        let req: Request<Bytes> = <Point as IntoReq<
+            Bitcode,
+            Request<Bytes>,
+            NoCustomError,
+        >>::into_req(
+            point(),
+            "/api/point",
+            Bitcode::CONTENT_TYPE,
+        )
+        .unwrap();
+        assert_eq!(req.method(), Bitcode::METHOD);
+        assert_eq!(req.headers()[CONTENT_TYPE], Bitcode::CONTENT_TYPE);
+
+        let decoded: Point = block_on(<Point as FromReq<
+            Bitcode,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req))
+        .unwrap();
+        assert_eq!(decoded, point());
+    }
+
+    #[test]
+    fn response_round_trip() {
+        let res: Response<Body> = block_on(<Point as IntoRes<
+            Bitcode,
+            Response<Body>,
+            NoCustomError,
+        >>::into_res(point()))
+        .unwrap();
+        assert_eq!(res.headers()[CONTENT_TYPE], Bitcode::CONTENT_TYPE);
+        let res = res.map(|body| match body {
+            Body::Sync(data) => data,
+            Body::Async(_) => panic!("expected a buffered body"),
+        });
+
+        let decoded: Point = block_on(<Point as FromRes<
+            Bitcode,
+            Response<Bytes>,
+            NoCustomError,
+        >>::from_res(res))
+        .unwrap();
+        assert_eq!(decoded, point());
+    }
+
+    #[test]
+    fn invalid_request_is_an_args_error() {
+        let req = Request::post("/api/point")
+            .header(CONTENT_TYPE, Bitcode::CONTENT_TYPE)
+            .body(Bytes::from_static(&[0xff]))
+            .unwrap();
+        let result: Result<Point, _> = block_on(<Point as FromReq<
+            Bitcode,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req));
+        assert!(matches!(result, Err(crate::ServerFnError::Args(_))));
+    }
+}
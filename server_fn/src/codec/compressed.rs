@@ -0,0 +1,676 @@
+use super::{Decodes, Encodes, Encoding, FromReq, IntoReq};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+};
+use bytes::Bytes;
+use http::Method;
+use std::{
+    io::{self, Read},
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Bodies smaller than this are sent uncompressed, as compressing them would save little or
+/// even make them larger.
+pub const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// The default for the largest size, in bytes, that a compressed body may decompress to.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+static MAX_DECOMPRESSED_SIZE: AtomicUsize =
+    AtomicUsize::new(DEFAULT_MAX_DECOMPRESSED_SIZE);
+
+/// Sets the largest size, in bytes, that a compressed request or response body may decompress
+/// to. Larger bodies are rejected, so that a small body cannot exhaust the memory of the
+/// server by decompressing to a huge one.
+///
+/// This defaults to [`DEFAULT_MAX_DECOMPRESSED_SIZE`].
+pub fn set_max_decompressed_size(limit: usize) {
+    MAX_DECOMPRESSED_SIZE.store(limit, Ordering::Relaxed);
+}
+
+/// Returns the largest size, in bytes, that a compressed body may decompress to.
+pub fn max_decompressed_size() -> usize {
+    MAX_DECOMPRESSED_SIZE.load(Ordering::Relaxed)
+}
+
+/// A compression algorithm that can be used for the body of a request or response, as named
+/// in the `Content-Encoding` and `Accept-Encoding` headers.
+///
+/// Each algorithm is only available if the feature with the same name is enabled (`gzip`,
+/// `brotli`, or `zstd`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// `gzip`, which every client and server supports.
+    Gzip,
+    /// Brotli (`br`), which usually compresses text better than `gzip`.
+    Brotli,
+    /// Zstandard (`zstd`), which is fast to compress and decompress.
+    Zstd,
+}
+
+impl Compression {
+    /// Every algorithm, in the order in which they are preferred.
+    pub const ALL: [Compression; 3] =
+        [Compression::Brotli, Compression::Zstd, Compression::Gzip];
+
+    /// The name of the algorithm, as used in the `Content-Encoding` header.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Brotli => "br",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Finds the algorithm with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL.into_iter().find(|compression| {
+            name.eq_ignore_ascii_case(compression.name())
+                || (*compression == Compression::Gzip
+                    && name.eq_ignore_ascii_case("x-gzip"))
+        })
+    }
+
+    /// Whether the feature for this algorithm is enabled.
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Compression::Gzip => cfg!(feature = "gzip"),
+            Compression::Brotli => cfg!(feature = "brotli"),
+            Compression::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// The algorithms that are enabled, in the order in which they are preferred.
+    pub fn enabled() -> impl DoubleEndedIterator<Item = Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|compression| compression.is_enabled())
+    }
+
+    /// The value of an `Accept-Encoding` header that lists every enabled algorithm.
+    pub fn accept_encoding() -> String {
+        Self::enabled()
+            .map(Self::name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The algorithm used to compress request bodies: `gzip` if it is enabled, as every server
+    /// can be expected to support it, or otherwise the preferred enabled algorithm.
+    pub fn for_requests() -> Option<Self> {
+        if Compression::Gzip.is_enabled() {
+            Some(Compression::Gzip)
+        } else {
+            Self::enabled().next()
+        }
+    }
+
+    /// Chooses the enabled algorithm that is preferred by the value of an `Accept-Encoding`
+    /// header, taking its quality values into account.
+    pub fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let mut wildcard = None;
+        let mut listed = Vec::new();
+        for item in accept_encoding.split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or_default().trim();
+            let quality = parts
+                .find_map(|param| {
+                    let (key, value) = param.split_once('=')?;
+                    key.trim()
+                        .eq_ignore_ascii_case("q")
+                        .then(|| value.trim().parse::<f32>().ok())
+                        .flatten()
+                })
+                .unwrap_or(1.0);
+            if name == "*" {
+                wildcard = Some(quality);
+            } else if let Some(compression) = Self::from_name(name) {
+                listed.push((compression, quality));
+            }
+        }
+
+        let quality = |compression: Self| {
+            listed
+                .iter()
+                .find(|(listed, _)| *listed == compression)
+                .map(|(_, quality)| *quality)
+                .or(wildcard)
+                .unwrap_or(0.0)
+        };
+        // `max_by` returns the last of several equal elements, so go from least to most preferred
+        Self::enabled()
+            .rev()
+            .map(|compression| (compression, quality(compression)))
+            .filter(|(_, quality)| *quality > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(compression, _)| compression)
+    }
+
+    /// Compresses the data with this algorithm.
+    pub fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                use std::io::Write;
+
+                let mut encoder = flate2::write::GzEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Compression::Brotli => {
+                use std::io::Write;
+
+                let mut encoder =
+                    brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                encoder.write_all(data)?;
+                Ok(encoder.into_inner())
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::encode_all(data, 3),
+            #[cfg(not(all(
+                feature = "gzip",
+                feature = "brotli",
+                feature = "zstd"
+            )))]
+            _ => Err(self.not_enabled()),
+        }
+    }
+
+    /// Decompresses data that was compressed with this algorithm, failing if it decompresses
+    /// to more than `limit` bytes.
+    pub fn decompress(self, data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+        let decoder: Box<dyn Read + '_> = match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Box::new(flate2::read::GzDecoder::new(data)),
+            #[cfg(feature = "brotli")]
+            Compression::Brotli => {
+                Box::new(brotli::Decompressor::new(data, 4096))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(zstd::stream::Decoder::new(data)?),
+            #[cfg(not(all(
+                feature = "gzip",
+                feature = "brotli",
+                feature = "zstd"
+            )))]
+            _ => return Err(self.not_enabled()),
+        };
+
+        // read one byte more than the limit, to tell whether it was exceeded
+        let mut decoded = Vec::new();
+        decoder.take(limit as u64 + 1).read_to_end(&mut decoded)?;
+        if decoded.len() > limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the body decompresses to more than {limit} bytes"),
+            ));
+        }
+        Ok(decoded)
+    }
+
+    #[cfg(not(all(feature = "gzip", feature = "brotli", feature = "zstd")))]
+    fn not_enabled(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} compression is not enabled", self.name()),
+        )
+    }
+}
+
+/// Decompresses a body according to the value of its `Content-Encoding` header.
+fn decode_content(
+    content_encoding: Option<&str>,
+    data: Bytes,
+) -> io::Result<Bytes> {
+    let Some(content_encoding) = content_encoding
+        .map(str::trim)
+        .filter(|encoding| !encoding.eq_ignore_ascii_case("identity"))
+    else {
+        return Ok(data);
+    };
+    let compression =
+        Compression::from_name(content_encoding).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unsupported Content-Encoding: {content_encoding}"),
+            )
+        })?;
+    compression
+        .decompress(&data, max_decompressed_size())
+        .map(Bytes::from)
+}
+
+/// Wraps another encoding, compressing its bodies with `gzip`, Brotli, or Zstandard.
+///
+/// Any encoding that implements [`Encodes`] and [`Decodes`] can be wrapped for the input, like
+/// `Compressed<Json>` or `Compressed<Cbor>`. For the output, the built-in binary and JSON
+/// encodings can be wrapped (`Json`, `Cbor`, `MsgPack`, `Postcard`, `Rkyv`, `Protobuf`, and
+/// `Bitcode`). The algorithms that can be used are chosen with the `gzip`, `brotli`, and `zstd`
+/// features.
+///
+/// - Request bodies are compressed by the client (with `gzip`, if it is enabled), and the
+///   algorithm is named in the `Content-Encoding` header. Bodies smaller than
+///   [`MIN_COMPRESSED_SIZE`] are sent as they are.
+/// - Response bodies are compressed by the server with the algorithm that the client prefers
+///   in its `Accept-Encoding` header, using a
+///   [`CompressionLayer`](crate::middleware::compression::CompressionLayer). The `#[server]`
+///   macro adds this layer when `Compressed` is used as the output encoding.
+/// - Bodies that decompress to more than [`max_decompressed_size`] bytes are rejected, with
+///   [`ServerFnError::Args`] on the server.
+///
+/// ```rust,ignore
+/// #[server(input = Compressed<Json>, output = Compressed<Json>)]
+/// pub async fn save_document(document: Document) -> Result<Report, ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// Browsers decompress responses themselves, and do not allow setting the `Accept-Encoding`
+/// header, so in the browser the algorithms that can be used for responses are those that the
+/// browser supports.
+pub struct Compressed<E>(PhantomData<E>);
+
+impl<E> Encoding for Compressed<E>
+where
+    E: Encoding,
+{
+    const CONTENT_TYPE: &'static str = E::CONTENT_TYPE;
+    const METHOD: Method = Method::POST;
+}
+
+/// Single values are encoded without compression, as they are sent inside of a body that is
+/// compressed as a whole, if at all.
+impl<E, T> Encodes<T> for Compressed<E>
+where
+    E: Encodes<T>,
+{
+    type Error = E::Error;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        E::encode(value)
+    }
+}
+
+impl<E, T> Decodes<T> for Compressed<E>
+where
+    E: Decodes<T>,
+{
+    type Error = E::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        E::decode(bytes)
+    }
+}
+
+impl<E, T, Request, Err> IntoReq<Compressed<E>, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    E: Encodes<T>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        let data = E::encode(&self)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+        let compression = Compression::for_requests()
+            .filter(|_| data.len() >= MIN_COMPRESSED_SIZE);
+        let mut req = match compression {
+            Some(compression) => {
+                let data = compression
+                    .compress(&data)
+                    .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+                let mut req = Request::try_new_post_bytes(
+                    path,
+                    accepts,
+                    E::CONTENT_TYPE,
+                    Bytes::from(data),
+                )?;
+                req.try_set_header("Content-Encoding", compression.name())?;
+                req
+            }
+            None => Request::try_new_post_bytes(
+                path,
+                accepts,
+                E::CONTENT_TYPE,
+                data,
+            )?,
+        };
+        let accept_encoding = Compression::accept_encoding();
+        if !accept_encoding.is_empty() {
+            // responses can still be received uncompressed by clients that cannot set it
+            _ = req.try_set_header("Accept-Encoding", &accept_encoding);
+        }
+        Ok(req)
+    }
+}
+
+impl<E, T, Request, Err> FromReq<Compressed<E>, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    E: Decodes<T>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let content_encoding = req
+            .header("Content-Encoding")
+            .map(|value| value.into_owned());
+        let data = req.try_into_bytes().await?;
+        let data = decode_content(content_encoding.as_deref(), data)
+            .map_err(|e| ServerFnError::Args(e.to_string()))?;
+        E::decode(data).map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+// Outputs are implemented for each encoding, rather than for any `E: Encodes<T>`, so that they
+// do not overlap with the implementations for `WithResponse<T>`.
+#[cfg(any(
+    feature = "json",
+    feature = "cbor",
+    feature = "msgpack",
+    feature = "postcard",
+    feature = "rkyv",
+    feature = "protobuf",
+    feature = "bitcode"
+))]
+mod output {
+    use super::{decode_content, Compressed};
+    use crate::{
+        codec::{Decodes, Encodes, FromRes, IntoRes},
+        error::ServerFnError,
+        response::{ClientRes, Res},
+    };
+
+    macro_rules! compressed_output {
+        ($($feature:literal => $encoding:ident),* $(,)?) => {$(
+            #[cfg(feature = $feature)]
+            impl<T, Response, Err>
+                IntoRes<Compressed<crate::codec::$encoding>, Response, Err> for T
+            where
+                Response: Res<Err>,
+                crate::codec::$encoding: Encodes<T>,
+                T: Send,
+            {
+                async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+                    encode_response::<crate::codec::$encoding, T, Response, Err>(
+                        &self,
+                    )
+                }
+            }
+
+            #[cfg(feature = $feature)]
+            impl<T, Response, Err>
+                FromRes<Compressed<crate::codec::$encoding>, Response, Err> for T
+            where
+                Response: ClientRes<Err> + Send,
+                crate::codec::$encoding: Decodes<T>,
+            {
+                async fn from_res(
+                    res: Response,
+                ) -> Result<Self, ServerFnError<Err>> {
+                    decode_response::<crate::codec::$encoding, T, Response, Err>(
+                        res,
+                    )
+                    .await
+                }
+            }
+        )*};
+    }
+
+    compressed_output! {
+        "json" => Json,
+        "cbor" => Cbor,
+        "msgpack" => MsgPack,
+        "postcard" => Postcard,
+        "rkyv" => Rkyv,
+        "protobuf" => Protobuf,
+        "bitcode" => Bitcode,
+    }
+
+    fn encode_response<E, T, Response, Err>(
+        value: &T,
+    ) -> Result<Response, ServerFnError<Err>>
+    where
+        E: Encodes<T>,
+        Response: Res<Err>,
+    {
+        // the body is compressed by the `CompressionLayer`, which knows what the client accepts
+        let data = E::encode(value)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+        Response::try_from_bytes(E::CONTENT_TYPE, data)
+    }
+
+    async fn decode_response<E, T, Response, Err>(
+        res: Response,
+    ) -> Result<T, ServerFnError<Err>>
+    where
+        E: Decodes<T>,
+        Response: ClientRes<Err>,
+    {
+        let content_encoding = (!res.is_body_decoded())
+            .then(|| res.header("Content-Encoding"))
+            .flatten();
+        let data = res.try_into_bytes().await?;
+        let data = decode_content(content_encoding.as_deref(), data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        E::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "generic", feature = "json"))]
+mod tests {
+    use super::{
+        Compressed, Compression, DEFAULT_MAX_DECOMPRESSED_SIZE,
+        MIN_COMPRESSED_SIZE,
+    };
+    use crate::{
+        codec::{FromReq, FromRes, IntoReq, IntoRes, Json},
+        error::NoCustomError,
+        middleware::{
+            compression::CompressionLayer, BoxedService, Layer, Service,
+        },
+        request::Req,
+        response::{generic::Body, Res},
+        ServerFnError,
+    };
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::{Request, Response};
+    use serde::{Deserialize, Serialize};
+    use std::{future::Future, pin::Pin};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Document {
+        title: String,
+        paragraphs: Vec<String>,
+    }
+
+    fn document(paragraphs: usize) -> Document {
+        Document {
+            title: "Compression".into(),
+            paragraphs: vec!["All work and no play.".into(); paragraphs],
+        }
+    }
+
+    fn into_req(doc: Document) -> Request<Bytes> {
+        <Document as IntoReq<Compressed<Json>, Request<Bytes>, NoCustomError>>::into_req(
+            doc, "/api/doc", "application/json",
+        )
+        .unwrap()
+    }
+
+    fn from_req(req: Request<Bytes>) -> Result<Document, ServerFnError> {
+        block_on(<Document as FromReq<
+            Compressed<Json>,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req))
+    }
+
+    struct Respond(Document);
+
+    impl Service<Request<Bytes>, Response<Body>> for Respond {
+        fn run(
+            &mut self,
+            _req: Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let doc = self.0.clone();
+            Box::pin(async move {
+                <Document as IntoRes<
+                    Compressed<Json>,
+                    Response<Body>,
+                    NoCustomError,
+                >>::into_res(doc)
+                .await
+                .unwrap()
+            })
+        }
+    }
+
+    fn respond(doc: Document, accept_encoding: &str) -> Response<Bytes> {
+        let mut service =
+            CompressionLayer.layer(BoxedService::new(Respond(doc)));
+        let req = Request::builder()
+            .header("Accept-Encoding", accept_encoding)
+            .body(Bytes::new())
+            .unwrap();
+        let mut res = block_on(service.0.run(req));
+        let body =
+            block_on(Res::<NoCustomError>::buffer_body(&mut res)).unwrap();
+        let (parts, _) = res.into_parts();
+        Response::from_parts(parts, body)
+    }
+
+    fn from_res(res: Response<Bytes>) -> Result<Document, ServerFnError> {
+        block_on(<Document as FromRes<
+            Compressed<Json>,
+            Response<Bytes>,
+            NoCustomError,
+        >>::from_res(res))
+    }
+
+    #[test]
+    fn negotiates_by_quality_and_preference() {
+        let enabled = |compression: Compression| {
+            Some(compression).filter(|c| c.is_enabled())
+        };
+        assert_eq!(Compression::negotiate("identity"), None);
+        assert_eq!(Compression::negotiate("gzip"), enabled(Compression::Gzip));
+        assert_eq!(Compression::negotiate("gzip;q=0, identity"), None);
+        if Compression::Gzip.is_enabled() && Compression::Brotli.is_enabled() {
+            assert_eq!(
+                Compression::negotiate("gzip, deflate, br"),
+                Some(Compression::Brotli)
+            );
+            assert_eq!(
+                Compression::negotiate("gzip;q=1.0, br;q=0.5"),
+                Some(Compression::Gzip)
+            );
+            assert_eq!(
+                Compression::negotiate("*;q=0.1, br;q=0"),
+                Compression::enabled().find(|c| *c != Compression::Brotli)
+            );
+        }
+    }
+
+    #[test]
+    fn small_request_is_not_compressed() {
+        let req = into_req(document(1));
+        assert!(req.body().len() < MIN_COMPRESSED_SIZE);
+        assert_eq!(
+            Req::<NoCustomError>::header(&req, "Content-Encoding"),
+            None
+        );
+        assert_eq!(from_req(req).unwrap(), document(1));
+    }
+
+    #[test]
+    fn large_request_round_trip() {
+        let req = into_req(document(1000));
+        let compression = Compression::for_requests();
+        assert_eq!(
+            Req::<NoCustomError>::header(&req, "Content-Encoding").as_deref(),
+            compression.map(Compression::name)
+        );
+        if compression.is_some() {
+            let uncompressed = serde_json::to_vec(&document(1000)).unwrap();
+            assert!(req.body().len() < uncompressed.len() / 10);
+        }
+        assert_eq!(from_req(req).unwrap(), document(1000));
+    }
+
+    #[test]
+    fn response_round_trip_with_each_algorithm() {
+        for compression in Compression::enabled() {
+            let res = respond(document(1000), compression.name());
+            assert_eq!(
+                res.headers()
+                    .get("Content-Encoding")
+                    .and_then(|value| value.to_str().ok()),
+                Some(compression.name())
+            );
+            assert_eq!(
+                res.headers()
+                    .get("Vary")
+                    .and_then(|value| value.to_str().ok()),
+                Some("Accept-Encoding")
+            );
+            assert_eq!(from_res(res).unwrap(), document(1000));
+        }
+    }
+
+    #[test]
+    fn response_is_not_compressed_unless_accepted() {
+        let res = respond(document(1000), "identity");
+        assert!(res.headers().get("Content-Encoding").is_none());
+        assert_eq!(from_res(res).unwrap(), document(1000));
+    }
+
+    #[test]
+    fn unsupported_content_encoding_is_an_args_error() {
+        let mut req = into_req(document(1));
+        req.headers_mut()
+            .insert("Content-Encoding", "compress".parse().unwrap());
+        assert!(matches!(from_req(req), Err(ServerFnError::Args(_))));
+    }
+
+    #[test]
+    fn decompression_is_limited() {
+        let data = vec![0; 10_000];
+        for compression in Compression::enabled() {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(
+                compression.decompress(&compressed, 10_000).unwrap(),
+                data
+            );
+            let err = compression.decompress(&compressed, 9_999).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn oversized_request_is_an_args_error() {
+        let Some(compression) = Compression::for_requests() else {
+            return;
+        };
+        // a small body that decompresses to more than the limit
+        let body = compression
+            .compress(&vec![b' '; DEFAULT_MAX_DECOMPRESSED_SIZE + 1])
+            .unwrap();
+        assert!(body.len() < DEFAULT_MAX_DECOMPRESSED_SIZE / 100);
+        let req = Request::post("/api/doc")
+            .header("Content-Type", "application/json")
+            .header("Content-Encoding", compression.name())
+            .body(Bytes::from(body))
+            .unwrap();
+        assert!(matches!(from_req(req), Err(ServerFnError::Args(_))));
+    }
+}
@@ -0,0 +1,580 @@
+use super::{Encoding, FromRes, IntoRes};
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    redirect,
+    request::Req,
+    response::{ClientRes, Res},
+    ServerFn,
+};
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    future::ready,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+/// The header in which a reconnecting client sends the id of the last event it received.
+pub const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// The type of an event that carries an item of the stream.
+pub(crate) const MESSAGE_EVENT: &str = "message";
+
+/// The type of an event that carries an error yielded by the stream.
+pub(crate) const ERROR_EVENT: &str = "server_fn_error";
+
+/// The type of the event that is sent once the stream has ended, so that the client does not
+/// reconnect.
+pub(crate) const END_EVENT: &str = "server_fn_end";
+
+/// An encoding that sends a stream of typed data as [server-sent events].
+///
+/// A server function that uses this as its output encoding should return an [`SseStream`].
+/// Each item is serialized as JSON and sent as a separate event, which the browser and proxies
+/// pass on as soon as it arrives, unlike a raw chunked body such as [`StreamingText`](super::StreamingText).
+///
+/// In the browser, the stream is consumed with an [`EventSource`], which reconnects
+/// automatically if the connection is lost. `EventSource` can only make `GET` requests, so
+/// this is usually combined with the [`GetUrl`](super::GetUrl) input encoding; with other
+/// input encodings, or with other clients, the events are read from the body of a normal
+/// request, and the stream ends if the connection is lost.
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl, output = EventStream)]
+/// async fn countdown(from: u32) -> Result<SseStream<u32>, ServerFnError> {
+///     Ok(futures::stream::iter((0..=from).rev()).into())
+/// }
+/// ```
+///
+/// ## Resuming a stream
+///
+/// Every event is sent with an id. When the `EventSource` reconnects, it sends the id of the
+/// last event it received in the [`LAST_EVENT_ID`] header, and the server function is run
+/// again. Events without an explicit id are numbered by their position in the stream, and
+/// those that the client has already received are skipped, so a server function that produces
+/// the same events each time it is run does not need to do anything to be resumed.
+///
+/// Events created with [`SseEvent::with_id`] are never skipped. A server function that assigns
+/// its own ids should read the `Last-Event-ID` header itself, and continue from that event.
+///
+/// [server-sent events]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+/// [`EventSource`]: https://developer.mozilla.org/en-US/docs/Web/API/EventSource
+pub struct EventStream;
+
+impl Encoding for EventStream {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::GET;
+}
+
+/// An item of an [`SseStream`], along with the id of the event that carries it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent<T> {
+    /// The id of the event, or `None` to number it by its position in the stream.
+    pub id: Option<String>,
+    /// The item.
+    pub data: T,
+}
+
+impl<T> SseEvent<T> {
+    /// Creates an event that is numbered by its position in the stream.
+    pub fn new(data: T) -> Self {
+        Self { id: None, data }
+    }
+
+    /// Creates an event with the given id.
+    pub fn with_id(id: impl Into<String>, data: T) -> Self {
+        Self {
+            id: Some(id.into()),
+            data,
+        }
+    }
+}
+
+/// A stream of typed data, which is sent as server-sent events with the [`EventStream`] encoding.
+///
+/// On the client, this yields each item as it arrives. The ids of the events can be read with
+/// [`into_events`](SseStream::into_events).
+pub struct SseStream<T, CustErr = NoCustomError> {
+    #[allow(clippy::type_complexity)]
+    events: Pin<
+        Box<
+            dyn Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>>
+                + Send,
+        >,
+    >,
+    // the `Last-Event-ID` of the request, which is only set on the server
+    last_event_id: Option<String>,
+}
+
+impl<T, CustErr> Debug for SseStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SseStream").finish()
+    }
+}
+
+impl<T, CustErr> SseStream<T, CustErr> {
+    /// Creates a new `SseStream` from the given stream of items.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self
+    where
+        T: 'static,
+    {
+        Self::from_events(value.map(|item| item.map(SseEvent::new)))
+    }
+
+    /// Creates a new `SseStream` from the given stream of events.
+    pub fn from_events(
+        value: impl Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self {
+            events: Box::pin(value),
+            last_event_id: None,
+        }
+    }
+
+    /// Consumes the wrapper, returning a stream of the events with their ids.
+    pub fn into_events(
+        self,
+    ) -> impl Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>> + Send
+    {
+        self.events
+    }
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for SseStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self::new(value.map(Ok))
+    }
+}
+
+impl<T, CustErr> Stream for SseStream<T, CustErr> {
+    type Item = Result<T, ServerFnError<CustErr>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.events
+            .as_mut()
+            .poll_next(cx)
+            .map(|item| item.map(|event| event.map(|event| event.data)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.events.size_hint()
+    }
+}
+
+/// A server-sent event as it is sent over the wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseMessage {
+    /// The id of the event, if it has one.
+    pub id: Option<String>,
+    /// The type of the event, or `None` for the default `message` type.
+    pub event: Option<String>,
+    /// The data of the event.
+    pub data: String,
+}
+
+impl SseMessage {
+    /// Writes the message in the `text/event-stream` format.
+    ///
+    /// Line breaks are removed from the event type and the id, as they would end the field
+    /// early and let the rest of the value be read as other fields. Null characters are also
+    /// removed from the id, as clients ignore ids that contain them.
+    fn to_bytes(&self) -> Bytes {
+        let mut out = String::new();
+        if let Some(event) = &self.event {
+            let event = event.replace(['\r', '\n'], "");
+            out.push_str(&format!("event: {event}\n"));
+        }
+        if let Some(id) = &self.id {
+            let id = id.replace(['\r', '\n', '\0'], "");
+            out.push_str(&format!("id: {id}\n"));
+        }
+        // each line of the data is sent as its own field, whichever line break ends it
+        for line in self.data.replace("\r\n", "\n").split(['\r', '\n']) {
+            out.push_str(&format!("data: {line}\n"));
+        }
+        out.push('\n');
+        Bytes::from(out)
+    }
+}
+
+/// Reads [`SseMessage`]s from the body of a `text/event-stream` response, as its chunks arrive.
+#[derive(Debug, Default)]
+struct SseParser {
+    buf: BytesMut,
+    message: SseMessage,
+    has_data: bool,
+    // whether a `\r` ended the last line, in which case a `\n` that follows it is ignored
+    after_cr: bool,
+}
+
+impl SseParser {
+    /// Adds a chunk of the body, returning the messages it completes.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        let mut messages = Vec::new();
+        for &byte in chunk {
+            let after_cr = std::mem::take(&mut self.after_cr);
+            match byte {
+                b'\n' if after_cr => {}
+                b'\r' | b'\n' => {
+                    self.after_cr = byte == b'\r';
+                    let line = self.buf.split();
+                    messages.extend(self.line(&String::from_utf8_lossy(&line)));
+                }
+                _ => self.buf.extend_from_slice(&[byte]),
+            }
+        }
+        messages
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseMessage> {
+        if line.is_empty() {
+            let message = std::mem::take(&mut self.message);
+            return std::mem::take(&mut self.has_data).then_some(message);
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => {
+                (field, value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.message.event = Some(value.to_string()),
+            "id" if !value.contains('\0') => {
+                self.message.id = Some(value.to_string())
+            }
+            "data" => {
+                if self.has_data {
+                    self.message.data.push('\n');
+                }
+                self.message.data.push_str(value);
+                self.has_data = true;
+            }
+            // comments, `retry`, and unknown fields are ignored
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Parses a `text/event-stream` body into [`SseMessage`]s.
+fn parse_messages<CustErr: Send>(
+    body: impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send + 'static,
+) -> impl Stream<Item = Result<SseMessage, ServerFnError<CustErr>>> + Send {
+    body.scan(SseParser::default(), |parser, chunk| {
+        ready(Some(match chunk {
+            Ok(chunk) => parser.feed(&chunk).into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        }))
+    })
+    .flat_map(futures::stream::iter)
+}
+
+impl<T, CustErr> SseStream<T, CustErr>
+where
+    T: DeserializeOwned + Send + 'static,
+    CustErr: FromStr + Display + Send + 'static,
+{
+    /// Creates a stream from the messages received by the client, which ends when the server
+    /// says that it has ended.
+    pub fn from_messages(
+        messages: impl Stream<Item = Result<SseMessage, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        let events = messages
+            .take_while(|message| {
+                ready(!matches!(
+                    message,
+                    Ok(SseMessage { event: Some(event), .. }) if event == END_EVENT
+                ))
+            })
+            .filter_map(|message| {
+                ready(match message {
+                    Err(e) => Some(Err(e)),
+                    Ok(SseMessage { event: Some(event), data, .. })
+                        if event == ERROR_EVENT =>
+                    {
+                        Some(Err(ServerFnError::de(&data)))
+                    }
+                    Ok(SseMessage { event: Some(event), .. })
+                        if event != MESSAGE_EVENT =>
+                    {
+                        None
+                    }
+                    Ok(SseMessage { id, data, .. }) => {
+                        Some(serde_json::from_str(&data).map_or_else(
+                            |e| {
+                                Err(ServerFnError::Deserialization(
+                                    e.to_string(),
+                                ))
+                            },
+                            |data| Ok(SseEvent { id, data }),
+                        ))
+                    }
+                })
+            });
+        Self::from_events(events)
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<EventStream, Response, CustErr>
+    for SseStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: FromStr + Display + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        // events that are numbered by their position are skipped if the client has already
+        // received them
+        let skip_through = self
+            .last_event_id
+            .as_deref()
+            .and_then(|id| id.parse::<u64>().ok());
+        let messages = self
+            .events
+            .zip(futures::stream::iter(0u64..))
+            .filter_map(move |(event, position)| {
+                ready(match event {
+                    Ok(SseEvent { id: None, .. })
+                        if skip_through
+                            .is_some_and(|last| position <= last) =>
+                    {
+                        None
+                    }
+                    Ok(SseEvent { id, data }) => Some(
+                        serde_json::to_string(&data)
+                            .map(|data| SseMessage {
+                                id: Some(
+                                    id.unwrap_or_else(|| position.to_string()),
+                                ),
+                                event: None,
+                                data,
+                            })
+                            .map_err(|e| {
+                                ServerFnError::<CustErr>::Serialization(
+                                    e.to_string(),
+                                )
+                            }),
+                    ),
+                    Err(e) => Some(Err(e)),
+                })
+            })
+            .map(|message| {
+                let message = message.unwrap_or_else(|err| SseMessage {
+                    id: None,
+                    event: Some(ERROR_EVENT.to_string()),
+                    data: err.ser().unwrap_or_else(|_| err.to_string()),
+                });
+                Ok(message.to_bytes())
+            })
+            .chain(futures::stream::once(ready(Ok(SseMessage {
+                id: None,
+                event: Some(END_EVENT.to_string()),
+                data: String::new(),
+            }
+            .to_bytes()))));
+
+        let mut res =
+            Response::try_from_stream(EventStream::CONTENT_TYPE, messages)?;
+        // proxies such as nginx buffer responses unless they are told not to
+        res.append_header("Cache-Control", "no-cache")?;
+        res.append_header("X-Accel-Buffering", "no")?;
+        Ok(res)
+    }
+}
+
+impl<CustErr, T, Response> FromRes<EventStream, Response, CustErr>
+    for SseStream<T, CustErr>
+where
+    Response: ClientRes<CustErr> + Send,
+    CustErr: FromStr + Display + Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let body = res.try_into_stream()?.map(|chunk| {
+            chunk.map_err(|e| {
+                e.map_custom_error(|e| {
+                    ServerFnError::ServerError(e.to_string())
+                })
+            })
+        });
+        Ok(Self::from_messages(parse_messages(body)))
+    }
+}
+
+/// Runs a server function that returns an [`SseStream`] on the server, resuming the stream
+/// from the `Last-Event-ID` of the request.
+#[doc(hidden)]
+pub async fn execute_event_stream_server<S, T>(
+    req: S::ServerRequest,
+) -> Result<S::ServerResponse, ServerFnError<S::Error>>
+where
+    S: ServerFn<
+        OutputEncoding = EventStream,
+        Output = SseStream<T, <S as ServerFn>::Error>,
+    >,
+    S::Error: Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    let last_event_id = req.header(LAST_EVENT_ID).map(|id| id.into_owned());
+    let this = S::from_req(req).await?;
+    let mut output = this.run_body().await?;
+    output.last_event_id = last_event_id;
+    IntoRes::<EventStream, _, _>::into_res(output).await
+}
+
+/// Calls a server function that returns an [`SseStream`] from the client.
+///
+/// If the client can open an `EventSource` for the request, the events are received through
+/// it. Otherwise, the request is sent as usual, and the events are read from the response.
+#[doc(hidden)]
+pub async fn run_event_stream_client<S, T>(
+    input: S,
+) -> Result<SseStream<T, S::Error>, ServerFnError<S::Error>>
+where
+    S: ServerFn<
+        OutputEncoding = EventStream,
+        Output = SseStream<T, <S as ServerFn>::Error>,
+    >,
+    S::Error: Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    let req = input.into_req(S::PATH, EventStream::CONTENT_TYPE)?;
+    match S::Client::open_event_source(&req) {
+        Some(messages) => Ok(SseStream::from_messages(
+            messages.map(|message| message.map_err(ServerFnError::Request)),
+        )),
+        None => {
+            S::run_on_client_with_req(req, redirect::REDIRECT_HOOK.get()).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn parse(chunks: &[&str]) -> Vec<SseMessage> {
+        let body = futures::stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| {
+                    Ok::<_, ServerFnError>(Bytes::from(chunk.to_string()))
+                })
+                .collect::<Vec<_>>(),
+        );
+        block_on(parse_messages(body).map(Result::unwrap).collect())
+    }
+
+    #[test]
+    fn parses_messages_split_across_chunks() {
+        let messages = parse(&[
+            ": a comment\nid: 1\nda",
+            "ta: {\"a\":1}\n\r\nevent: other\r",
+            "\ndata: first\ndata:second\nretry: 10\n\ndata\n\n",
+        ]);
+        assert_eq!(
+            messages,
+            vec![
+                SseMessage {
+                    id: Some("1".into()),
+                    event: None,
+                    data: "{\"a\":1}".into(),
+                },
+                SseMessage {
+                    id: None,
+                    event: Some("other".into()),
+                    data: "first\nsecond".into(),
+                },
+                SseMessage {
+                    id: None,
+                    event: None,
+                    data: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_incomplete_messages() {
+        assert_eq!(parse(&["event: other\n\ndata: cut off"]), vec![]);
+    }
+
+    #[test]
+    fn writes_multiline_data() {
+        let message = SseMessage {
+            id: Some("7".into()),
+            event: Some("other".into()),
+            data: "a\nb".into(),
+        };
+        assert_eq!(
+            message.to_bytes(),
+            "event: other\nid: 7\ndata: a\ndata: b\n\n"
+        );
+        let mut parser = SseParser::default();
+        assert_eq!(parser.feed(&message.to_bytes()), vec![message]);
+    }
+
+    #[test]
+    fn decodes_items_and_errors_until_the_end() {
+        let messages = parse(&[
+            "id: 0\ndata: 1\n\n",
+            "event: server_fn_error\ndata: ServerError|oops\n\n",
+            "id: 2\ndata: 3\n\n",
+            "event: server_fn_end\ndata:\n\n",
+            "id: 3\ndata: 4\n\n",
+        ]);
+        let stream = SseStream::<u32>::from_messages(futures::stream::iter(
+            messages.into_iter().map(Ok),
+        ));
+        let events = block_on(stream.into_events().collect::<Vec<_>>());
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], Ok(SseEvent::with_id("0", 1)));
+        assert_eq!(
+            events[1],
+            Err(ServerFnError::ServerError("oops".to_string()))
+        );
+        assert_eq!(events[2], Ok(SseEvent::with_id("2", 3)));
+    }
+
+    #[test]
+    fn strips_line_breaks_from_fields() {
+        let message = SseMessage {
+            id: Some("1\ndata: injected\0".into()),
+            event: Some("other\r\nid: 2".into()),
+            data: "a\r\nb\rc".into(),
+        };
+        assert_eq!(
+            message.to_bytes(),
+            "event: otherid: 2\nid: 1data: injected\ndata: a\ndata: b\ndata: c\n\n"
+        );
+        let mut parser = SseParser::default();
+        assert_eq!(
+            parser.feed(&message.to_bytes()),
+            vec![SseMessage {
+                id: Some("1data: injected".into()),
+                event: Some("otherid: 2".into()),
+                data: "a\nb\nc".into(),
+            }]
+        );
+    }
+}
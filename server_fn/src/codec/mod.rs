@@ -54,12 +54,38 @@ mod postcard;
 #[cfg(feature = "postcard")]
 pub use postcard::*;
 
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::*;
+
+#[cfg(feature = "bitcode")]
+mod bitcode;
+#[cfg(feature = "bitcode")]
+pub use self::bitcode::*;
+
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+mod compressed;
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+pub use compressed::*;
+
+mod event_stream;
+pub use event_stream::*;
+
 mod stream;
 use crate::error::ServerFnError;
+use bytes::Bytes;
 use futures::Future;
 use http::Method;
+use std::fmt::Display;
 pub use stream::*;
 
+mod websocket;
+pub use websocket::*;
+
+mod with_response;
+pub use with_response::*;
+
 /// Serializes a data type into an HTTP request, on the client.
 ///
 /// Implementations use the methods of the [`ClientReq`](crate::request::ClientReq) trait to
@@ -217,3 +243,27 @@ pub trait Encoding {
     /// This should be `POST` in most cases.
     const METHOD: Method;
 }
+
+/// Serializes a single value into bytes using a particular encoding.
+///
+/// This is used by encodings that send more than one value over a single connection,
+/// like [`Websocket`], to encode each individual frame.
+pub trait Encodes<T>: Encoding {
+    /// The error that can occur while encoding the value.
+    type Error: Display;
+
+    /// Attempts to encode the value into bytes.
+    fn encode(value: &T) -> Result<Bytes, Self::Error>;
+}
+
+/// Deserializes a single value from bytes using a particular encoding.
+///
+/// This is used by encodings that send more than one value over a single connection,
+/// like [`Websocket`], to decode each individual frame.
+pub trait Decodes<T>: Encoding {
+    /// The error that can occur while decoding the value.
+    type Error: Display;
+
+    /// Attempts to decode the value from bytes.
+    fn decode(bytes: Bytes) -> Result<T, Self::Error>;
+}
@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -16,6 +16,28 @@ impl Encoding for MsgPack {
     const METHOD: Method = Method::POST;
 }
 
+impl<T> Encodes<T> for MsgPack
+where
+    T: Serialize,
+{
+    type Error = rmp_serde::encode::Error;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        rmp_serde::to_vec(value).map(Bytes::from)
+    }
+}
+
+impl<T> Decodes<T> for MsgPack
+where
+    T: DeserializeOwned,
+{
+    type Error = rmp_serde::decode::Error;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        rmp_serde::from_slice(&bytes)
+    }
+}
+
 impl<T, Request, Err> IntoReq<MsgPack, Request, Err> for T
 where
     Request: ClientReq<Err>,
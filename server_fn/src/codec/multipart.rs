@@ -1,12 +1,21 @@
 use super::{Encoding, FromReq};
 use crate::{
     error::ServerFnError,
-    request::{browser::BrowserFormData, ClientReq, Req},
+    request::{browser::BrowserFormData, ClientReq, Req, UploadProgress},
     IntoReq,
 };
+use bytes::{BufMut, Bytes, BytesMut};
 use futures::StreamExt;
 use http::Method;
 use multer::Multipart;
+use std::{
+    any::Any,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use web_sys::FormData;
 
 /// Encodes multipart form data.
@@ -24,6 +33,8 @@ impl Encoding for MultipartFormData {
 pub enum MultipartData {
     /// `FormData` from the browser.
     Client(BrowserFormData),
+    /// A [`MultipartForm`] built on the client, which can be sent by any client.
+    Form(MultipartForm),
     /// Generic multipart form using [`multer`]. This implements [`Stream`](futures::Stream).
     Server(multer::Multipart<'static>),
 }
@@ -34,18 +45,19 @@ impl MultipartData {
     /// On the server side, this always returns `Some(_)`. On the client side, always returns `None`.
     pub fn into_inner(self) -> Option<Multipart<'static>> {
         match self {
-            MultipartData::Client(_) => None,
             MultipartData::Server(data) => Some(data),
+            MultipartData::Client(_) | MultipartData::Form(_) => None,
         }
     }
 
-    /// Extracts the inner form data on the client side.
+    /// Extracts the inner browser form data on the client side.
     ///
-    /// On the server side, this always returns `None`. On the client side, always returns `Some(_)`.
+    /// On the server side, this always returns `None`. On the client side, this returns `Some(_)`
+    /// unless the data was built as a [`MultipartForm`].
     pub fn into_client_data(self) -> Option<BrowserFormData> {
         match self {
             MultipartData::Client(data) => Some(data),
-            MultipartData::Server(_) => None,
+            MultipartData::Server(_) | MultipartData::Form(_) => None,
         }
     }
 }
@@ -56,9 +68,189 @@ impl From<FormData> for MultipartData {
     }
 }
 
+impl From<MultipartForm> for MultipartData {
+    fn from(value: MultipartForm) -> Self {
+        MultipartData::Form(value)
+    }
+}
+
+/// A multipart form that can be sent by any client, unlike the browser’s `FormData`.
+///
+/// This makes it possible to call server functions that take [`MultipartData`] from native
+/// clients and from tests, and to follow the progress of the upload:
+///
+/// ```rust,ignore
+/// let form = MultipartForm::new()
+///     .text("title", "Holiday")
+///     .file("photo", "beach.jpg", "image/jpeg", bytes)
+///     .on_progress(|progress| println!("{:.0}%", progress.fraction() * 100.0));
+/// upload_photo(form.into()).await?;
+/// ```
+#[derive(Default)]
+pub struct MultipartForm {
+    parts: Vec<Part>,
+    on_progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+}
+
+#[derive(Debug)]
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Bytes,
+}
+
+impl fmt::Debug for MultipartForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartForm")
+            .field("parts", &self.parts)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl MultipartForm {
+    /// Creates an empty form.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a text field.
+    pub fn text(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: Bytes::from(value.into()),
+        });
+        self
+    }
+
+    /// Adds a file, with its file name and its content type (like `"image/png"`).
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type.into()),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Calls `on_progress` as the form is uploaded.
+    ///
+    /// It is called once before the upload starts, then each time more of the body has been
+    /// sent, if the client can report it.
+    pub fn on_progress(
+        mut self,
+        on_progress: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Encodes the form, and returns its content type (with the boundary) and its body.
+    fn encode(&self) -> (String, Bytes) {
+        let boundary = self.boundary();
+        let mut body = BytesMut::new();
+        for part in &self.parts {
+            body.put_slice(b"--");
+            body.put_slice(boundary.as_bytes());
+            body.put_slice(b"\r\nContent-Disposition: form-data; name=\"");
+            body.put_slice(escape_quoted(&part.name).as_bytes());
+            body.put_slice(b"\"");
+            if let Some(file_name) = &part.file_name {
+                body.put_slice(b"; filename=\"");
+                body.put_slice(escape_quoted(file_name).as_bytes());
+                body.put_slice(b"\"");
+            }
+            if let Some(content_type) = &part.content_type {
+                body.put_slice(b"\r\nContent-Type: ");
+                body.put_slice(content_type.as_bytes());
+            }
+            body.put_slice(b"\r\n\r\n");
+            body.put_slice(&part.data);
+            body.put_slice(b"\r\n");
+        }
+        body.put_slice(b"--");
+        body.put_slice(boundary.as_bytes());
+        body.put_slice(b"--\r\n");
+        (
+            format!("{}; boundary={boundary}", MultipartFormData::CONTENT_TYPE),
+            body.freeze(),
+        )
+    }
+
+    /// Returns a boundary that does not appear in any of the parts.
+    fn boundary(&self) -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        loop {
+            let seed = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let hash = self.parts.iter().fold(seed, |hash, part| {
+                xxhash_rust::xxh64::xxh64(&part.data, hash)
+            });
+            let boundary = format!("----ServerFnBoundary{hash:016x}");
+            if !self.parts.iter().any(|part| {
+                part.data
+                    .windows(boundary.len())
+                    .any(|window| window == boundary.as_bytes())
+            }) {
+                return boundary;
+            }
+        }
+    }
+
+    fn into_req<CustErr, Request>(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>>
+    where
+        Request: ClientReq<CustErr>,
+    {
+        let (content_type, body) = self.encode();
+        match self.on_progress {
+            None => {
+                Request::try_new_post_bytes(path, accepts, &content_type, body)
+            }
+            Some(on_progress) => {
+                let total = body.len() as u64;
+                on_progress(UploadProgress { sent: 0, total });
+                Request::try_new_post_bytes_with_progress(
+                    path,
+                    accepts,
+                    &content_type,
+                    body,
+                    Arc::new(move |sent| {
+                        on_progress(UploadProgress { sent, total })
+                    }),
+                )
+            }
+        }
+    }
+}
+
+/// Escapes a name for a quoted string in a `Content-Disposition` header, as browsers do.
+fn escape_quoted(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
 impl<CustErr, T, Request> IntoReq<MultipartFormData, Request, CustErr> for T
 where
-    Request: ClientReq<CustErr, FormData = BrowserFormData>,
+    Request: ClientReq<CustErr>,
+    Request::FormData: 'static,
     T: Into<MultipartData>,
 {
     fn into_req(
@@ -66,12 +258,29 @@ where
         path: &str,
         accepts: &str,
     ) -> Result<Request, ServerFnError<CustErr>> {
-        let multi = self.into();
-        Request::try_new_multipart(
-            path,
-            accepts,
-            multi.into_client_data().unwrap(),
-        )
+        match self.into() {
+            MultipartData::Form(form) => form.into_req(path, accepts),
+            MultipartData::Client(data) => {
+                // the browser's `FormData` can only be sent by a client that uses it as its
+                // own form data
+                let data: Box<dyn Any> = Box::new(data);
+                match data.downcast::<Request::FormData>() {
+                    Ok(data) => {
+                        Request::try_new_multipart(path, accepts, *data)
+                    }
+                    Err(_) => Err(ServerFnError::Request(
+                        "The browser's `FormData` can only be sent by the \
+                         browser client. Use a `MultipartForm` to upload \
+                         from other clients."
+                            .into(),
+                    )),
+                }
+            }
+            MultipartData::Server(_) => Err(ServerFnError::Request(
+                "Multipart data received by the server cannot be sent again."
+                    .into(),
+            )),
+        }
     }
 }
 
@@ -94,3 +303,121 @@ where
         Ok(MultipartData::Server(data).into())
     }
 }
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::error::NoCustomError;
+    use futures::executor::block_on;
+    use http::{header::CONTENT_TYPE, Request};
+    use std::sync::Mutex;
+
+    fn form() -> MultipartForm {
+        MultipartForm::new().text("title", "Holiday").file(
+            "photo",
+            "beach.jpg",
+            "image/jpeg",
+            &b"\xff\xd8\xff"[..],
+        )
+    }
+
+    fn into_req(form: MultipartForm) -> Request<Bytes> {
+        IntoReq::<MultipartFormData, _, NoCustomError>::into_req(
+            form,
+            "/api/upload_photo",
+            "application/json",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn encodes_the_wire_format() {
+        let (content_type, body) = form().encode();
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let mut expected = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"title\"\r\n\
+             \r\n\
+             Holiday\r\n\
+             --{boundary}\r\n\
+             Content-Disposition: form-data; name=\"photo\"; \
+             filename=\"beach.jpg\"\r\n\
+             Content-Type: image/jpeg\r\n\
+             \r\n"
+        )
+        .into_bytes();
+        expected.extend_from_slice(b"\xff\xd8\xff");
+        expected
+            .extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn escapes_names() {
+        let (_, body) = MultipartForm::new()
+            .file("a\"b", "line\r\nbreak.txt", "text/plain", "")
+            .encode();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body
+            .contains("name=\"a%22b\"; filename=\"line%0D%0Abreak.txt\"\r\n"));
+    }
+
+    #[test]
+    fn boundaries_differ_between_encodings() {
+        let form = form();
+        let boundary = form.boundary();
+        assert!(boundary.starts_with("----ServerFnBoundary"));
+        assert_ne!(form.boundary(), boundary);
+    }
+
+    #[test]
+    fn round_trips_through_the_server() {
+        let req = into_req(form());
+        assert!(req.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("multipart/form-data; boundary="));
+
+        let data = block_on(<MultipartData as FromReq<
+            MultipartFormData,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req))
+        .unwrap();
+        let mut data = data.into_inner().unwrap();
+        block_on(async {
+            let title = data.next_field().await.unwrap().unwrap();
+            assert_eq!(title.name(), Some("title"));
+            assert_eq!(title.file_name(), None);
+            assert_eq!(title.text().await.unwrap(), "Holiday");
+
+            let photo = data.next_field().await.unwrap().unwrap();
+            assert_eq!(photo.name(), Some("photo"));
+            assert_eq!(photo.file_name(), Some("beach.jpg"));
+            assert_eq!(
+                photo.content_type().map(|mime| mime.as_ref()),
+                Some("image/jpeg")
+            );
+            assert_eq!(photo.bytes().await.unwrap(), &b"\xff\xd8\xff"[..]);
+
+            assert!(data.next_field().await.unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn reports_progress() {
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let req = into_req(form().on_progress({
+            let progress = Arc::clone(&progress);
+            move |update: UploadProgress| {
+                progress.lock().unwrap().push((update.sent, update.total))
+            }
+        }));
+
+        // a client that cannot report progress sends the whole body at once
+        let total = req.body().len() as u64;
+        assert_eq!(*progress.lock().unwrap(), [(0, total), (total, total)]);
+    }
+}
@@ -0,0 +1,206 @@
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+use prost::Message;
+use std::convert::Infallible;
+
+/// A codec for Protocol Buffers, using [`prost`].
+///
+/// The arguments and the output of the server function must be [`Message`]s, which are usually
+/// generated from `.proto` files with `prost-build`, or derived with `#[derive(prost::Message)]`.
+///
+/// To use `Protobuf` as the input encoding of the `#[server]` macro, derive `Message` for the
+/// arguments and tag each of them:
+///
+/// ```rust,ignore
+/// #[server(input = Protobuf, input_derive = (Clone, PartialEq, prost::Message))]
+/// pub async fn add_point(
+///     #[prost(int32, tag = "1")] x: i32,
+///     #[prost(int32, tag = "2")] y: i32,
+/// ) -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+pub struct Protobuf;
+
+impl Encoding for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf";
+    const METHOD: Method = Method::POST;
+}
+
+impl<T> Encodes<T> for Protobuf
+where
+    T: Message,
+{
+    type Error = Infallible;
+
+    fn encode(value: &T) -> Result<Bytes, Self::Error> {
+        Ok(Bytes::from(value.encode_to_vec()))
+    }
+}
+
+impl<T> Decodes<T> for Protobuf
+where
+    T: Message + Default,
+{
+    type Error = prost::DecodeError;
+
+    fn decode(bytes: Bytes) -> Result<T, Self::Error> {
+        T::decode(bytes)
+    }
+}
+
+impl<T, Request, Err> IntoReq<Protobuf, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Message,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(self.encode_to_vec()),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Protobuf, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: Message + Default,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        T::decode(data).map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<T, Response, Err> IntoRes<Protobuf, Response, Err> for T
+where
+    Response: Res<Err>,
+    T: Message + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        Response::try_from_bytes(
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(self.encode_to_vec()),
+        )
+    }
+}
+
+impl<T, Response, Err> FromRes<Protobuf, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: Message + Default,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        T::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::Protobuf;
+    use crate::{
+        codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes},
+        error::NoCustomError,
+        response::generic::Body,
+    };
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::{header::CONTENT_TYPE, Request, Response};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Point {
+        #[prost(int32, tag = "1")]
+        x: i32,
+        #[prost(int32, tag = "2")]
+        y: i32,
+        #[prost(string, tag = "3")]
+        label: String,
+        #[prost(bytes = "vec", tag = "4")]
+        data: Vec<u8>,
+    }
+
+    fn point() -> Point {
+        Point {
+            x: -4,
+            y: 1 << 20,
+            label: "origin".into(),
+            data: vec![0, 1, 2, 255],
+        }
+    }
+
+    #[test]
+    fn request_round_trip() {
+        let req: Request<Bytes> = <Point as IntoReq<
+            Protobuf,
+            Request<Bytes>,
+            NoCustomError,
+        >>::into_req(
+            point(),
+            "/api/point",
+            Protobuf::CONTENT_TYPE,
+        )
+        .unwrap();
+        assert_eq!(req.method(), Protobuf::METHOD);
+        assert_eq!(req.headers()[CONTENT_TYPE], Protobuf::CONTENT_TYPE);
+
+        let decoded: Point = block_on(<Point as FromReq<
+            Protobuf,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req))
+        .unwrap();
+        assert_eq!(decoded, point());
+    }
+
+    #[test]
+    fn response_round_trip() {
+        let res: Response<Body> = block_on(<Point as IntoRes<
+            Protobuf,
+            Response<Body>,
+            NoCustomError,
+        >>::into_res(point()))
+        .unwrap();
+        assert_eq!(res.headers()[CONTENT_TYPE], Protobuf::CONTENT_TYPE);
+        let res = res.map(|body| match body {
+            Body::Sync(data) => data,
+            Body::Async(_) => panic!("expected a buffered body"),
+        });
+
+        let decoded: Point = block_on(<Point as FromRes<
+            Protobuf,
+            Response<Bytes>,
+            NoCustomError,
+        >>::from_res(res))
+        .unwrap();
+        assert_eq!(decoded, point());
+    }
+
+    #[test]
+    fn invalid_request_is_an_args_error() {
+        let req = Request::post("/api/point")
+            .header(CONTENT_TYPE, Protobuf::CONTENT_TYPE)
+            .body(Bytes::from_static(&[0xff, 0xff, 0xff]))
+            .unwrap();
+        let result: Result<Point, _> = block_on(<Point as FromReq<
+            Protobuf,
+            Request<Bytes>,
+            NoCustomError,
+        >>::from_req(req));
+        assert!(matches!(result, Err(crate::ServerFnError::Args(_))));
+    }
+}
@@ -0,0 +1,507 @@
+use super::{Decodes, Encodes, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    request::{ClientReq, Req, WebsocketReq},
+    response::{ClientRes, Res},
+    ServerFn,
+};
+use bytes::Bytes;
+use futures::{FutureExt, SinkExt, Stream, StreamExt};
+use http::Method;
+use std::{
+    fmt::{Debug, Display},
+    future::ready,
+    marker::PhantomData,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+/// An encoding that opens a bidirectional websocket connection to the server function.
+///
+/// A server function that uses this as its input encoding should take a single argument of
+/// type [`BoxedStream`], and return a [`BoxedStream`]. Each item in either direction is
+/// sent as a separate frame, which is encoded with `InputEncoding` (for items sent by the
+/// client) or `OutputEncoding` (for items sent by the server).
+///
+/// ```rust,ignore
+/// #[server(input = Websocket<Json, Json>)]
+/// async fn uppercase(
+///     input: BoxedStream<String>,
+/// ) -> Result<BoxedStream<String>, ServerFnError> {
+///     Ok(BoxedStream::new(
+///         input.map(|item| item.map(|value| value.to_uppercase())),
+///     ))
+/// }
+/// ```
+///
+/// Errors yielded by either stream are sent to the other side as a serialized
+/// [`ServerFnError`], rather than closing the connection.
+///
+/// On the server, this is supported by the `actix` feature, and by the `axum-websocket` feature
+/// (or the `websocket` feature of `leptos_axum`) for Axum.
+pub struct Websocket<InputEncoding, OutputEncoding>(
+    PhantomData<fn() -> (InputEncoding, OutputEncoding)>,
+);
+
+impl<InputEncoding, OutputEncoding> Encoding
+    for Websocket<InputEncoding, OutputEncoding>
+{
+    const CONTENT_TYPE: &'static str = "application/websocket";
+    const METHOD: Method = Method::GET;
+}
+
+/// A stream of typed data, which is sent or received over a [`Websocket`].
+pub struct BoxedStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> Debug for BoxedStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BoxedStream").finish()
+    }
+}
+
+impl<T, CustErr> BoxedStream<T, CustErr> {
+    /// Creates a new `BoxedStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning the inner stream.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<T, CustErr> Stream for BoxedStream<T, CustErr> {
+    type Item = Result<T, ServerFnError<CustErr>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Encodes an item as a frame: data as `Ok(_)`, errors as `Err(_)`.
+fn encode_frame<E, T, CustErr>(
+    item: Result<T, ServerFnError<CustErr>>,
+) -> Result<Bytes, Bytes>
+where
+    E: Encodes<T>,
+    CustErr: FromStr + Display,
+{
+    let err = match item {
+        Ok(value) => match E::encode(&value) {
+            Ok(data) => return Ok(data),
+            Err(e) => ServerFnError::Serialization(e.to_string()),
+        },
+        Err(e) => e,
+    };
+    Err(Bytes::from(err.ser().unwrap_or_else(|_| err.to_string())))
+}
+
+/// Decodes a frame that was encoded with [`encode_frame`].
+fn decode_frame<E, T, CustErr>(
+    frame: Result<Bytes, Bytes>,
+) -> Result<T, ServerFnError<CustErr>>
+where
+    E: Decodes<T>,
+    CustErr: FromStr + Display,
+{
+    match frame {
+        Ok(data) => E::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+        Err(err) => Err(ServerFnError::de(&String::from_utf8_lossy(&err))),
+    }
+}
+
+/// Runs a websocket server function on the server.
+///
+/// This upgrades the request, spawns a task that runs the server function and sends
+/// its output to the client, and returns the response that completes the handshake.
+#[doc(hidden)]
+pub async fn run_websocket_server<S, In, Out, InputEncoding, OutputEncoding>(
+    req: S::ServerRequest,
+) -> S::ServerResponse
+where
+    S: ServerFn<
+            InputEncoding = Websocket<InputEncoding, OutputEncoding>,
+            Output = BoxedStream<Out, <S as ServerFn>::Error>,
+        > + From<BoxedStream<In, <S as ServerFn>::Error>>
+        + 'static,
+    S::ServerRequest: WebsocketReq<
+        S::Error,
+        WebsocketResponse = <S as ServerFn>::ServerResponse,
+    >,
+    S::Error: Send + 'static,
+    InputEncoding: Decodes<In> + 'static,
+    OutputEncoding: Encodes<Out> + 'static,
+    In: Send + 'static,
+    Out: Send + 'static,
+{
+    let (incoming, outgoing, response) = match req.try_into_websocket().await {
+        Ok(parts) => parts,
+        Err(e) => return S::error_response(&e),
+    };
+
+    let input = BoxedStream::new(
+        incoming.map(decode_frame::<InputEncoding, In, S::Error>),
+    );
+    S::ServerRequest::spawn(async move {
+        let output = match S::from(input).run_body().await {
+            Ok(output) => output,
+            Err(e) => BoxedStream::new(futures::stream::once(ready(Err(e)))),
+        };
+        let _ = output
+            .map(|item| Ok(encode_frame::<OutputEncoding, _, _>(item)))
+            .forward(outgoing)
+            .await;
+    });
+
+    response
+}
+
+/// Calls a websocket server function from the client.
+///
+/// The input stream is sent to the server while the output stream is being polled, so
+/// the output stream should be polled even if you are only interested in sending data.
+#[doc(hidden)]
+pub async fn run_websocket_client<S, In, Out, InputEncoding, OutputEncoding>(
+    input: S,
+) -> Result<BoxedStream<Out, S::Error>, ServerFnError<S::Error>>
+where
+    S: ServerFn<
+            InputEncoding = Websocket<InputEncoding, OutputEncoding>,
+            Output = BoxedStream<Out, <S as ServerFn>::Error>,
+        > + Into<BoxedStream<In, <S as ServerFn>::Error>>
+        + 'static,
+    S::Error: Send + 'static,
+    InputEncoding: Encodes<In> + 'static,
+    OutputEncoding: Decodes<Out> + 'static,
+    In: Send + 'static,
+    Out: Send + 'static,
+{
+    let (incoming, outgoing) = S::Client::open_websocket(S::PATH).await?;
+    let input: BoxedStream<In, S::Error> = input.into();
+
+    enum Frame<T> {
+        Item(T),
+        Done,
+    }
+
+    // unlike `forward`, `send_all` does not close the connection once the input
+    // has been sent, as the server may still be sending output
+    let send_input = async move {
+        let mut outgoing = Box::pin(outgoing);
+        let mut frames =
+            input.map(|item| Ok(encode_frame::<InputEncoding, _, _>(item)));
+        let _ = outgoing.send_all(&mut frames).await;
+    }
+    .into_stream()
+    .filter_map(|_| ready(None));
+    let output = incoming
+        .map(|frame| {
+            Frame::Item(decode_frame::<OutputEncoding, Out, S::Error>(frame))
+        })
+        .chain(futures::stream::once(ready(Frame::Done)));
+
+    // polling the output also drives the input, and the output ends as soon as
+    // the server closes the connection
+    let output = futures::stream::select(output, send_input)
+        .take_while(|frame| ready(matches!(frame, Frame::Item(_))))
+        .filter_map(|frame| {
+            ready(match frame {
+                Frame::Item(item) => Some(item),
+                Frame::Done => None,
+            })
+        });
+    Ok(BoxedStream::new(output))
+}
+
+fn http_unsupported<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request(
+        "Websocket server functions can only be called over a websocket \
+         connection."
+            .into(),
+    )
+}
+
+impl<InputEncoding, OutputEncoding, T, Request, CustErr>
+    IntoReq<Websocket<InputEncoding, OutputEncoding>, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(http_unsupported())
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Request, CustErr>
+    FromReq<Websocket<InputEncoding, OutputEncoding>, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send,
+{
+    async fn from_req(_req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        Err(http_unsupported())
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Response, CustErr>
+    IntoRes<Websocket<InputEncoding, OutputEncoding>, Response, CustErr>
+    for BoxedStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Err(http_unsupported())
+    }
+}
+
+impl<InputEncoding, OutputEncoding, T, Response, CustErr>
+    FromRes<Websocket<InputEncoding, OutputEncoding>, Response, CustErr>
+    for BoxedStream<T, CustErr>
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(_res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        Err(http_unsupported())
+    }
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::{codec::Json, response::generic::Body};
+    use futures::{channel::mpsc, executor::block_on, Sink};
+    use http::{Request, Response};
+    use std::{borrow::Cow, future::Future};
+
+    #[test]
+    fn frames_round_trip() {
+        let frame = encode_frame::<Json, _, NoCustomError>(Ok(vec![1, 2]));
+        assert_eq!(frame, Ok(Bytes::from_static(b"[1,2]")));
+        assert_eq!(
+            decode_frame::<Json, Vec<u8>, NoCustomError>(frame),
+            Ok(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn errors_are_sent_as_error_frames() {
+        let err = ServerFnError::<NoCustomError>::ServerError("oops".into());
+        let frame = encode_frame::<Json, u8, _>(Err(err.clone()));
+        assert!(frame.is_err());
+        assert_eq!(decode_frame::<Json, u8, NoCustomError>(frame), Err(err));
+
+        // data that cannot be decoded is an error, rather than closing the stream
+        assert!(matches!(
+            decode_frame::<Json, u8, NoCustomError>(Ok(Bytes::from_static(
+                b"not json"
+            ))),
+            Err(ServerFnError::Deserialization(_))
+        ));
+    }
+
+    /// A request that is connected to a client through in-memory channels.
+    struct MemoryReq {
+        incoming: mpsc::Receiver<Result<Bytes, Bytes>>,
+        outgoing: mpsc::Sender<Result<Bytes, Bytes>>,
+    }
+
+    impl Req<NoCustomError> for MemoryReq {
+        fn as_query(&self) -> Option<&str> {
+            None
+        }
+
+        fn to_content_type(&self) -> Option<Cow<'_, str>> {
+            None
+        }
+
+        fn accepts(&self) -> Option<Cow<'_, str>> {
+            None
+        }
+
+        fn referer(&self) -> Option<Cow<'_, str>> {
+            None
+        }
+
+        fn header(&self, _name: &str) -> Option<Cow<'_, str>> {
+            None
+        }
+
+        async fn try_into_bytes(self) -> Result<Bytes, ServerFnError> {
+            unreachable!()
+        }
+
+        async fn try_into_string(self) -> Result<String, ServerFnError> {
+            unreachable!()
+        }
+
+        fn try_into_stream(
+            self,
+        ) -> Result<
+            impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+            ServerFnError,
+        > {
+            Ok(futures::stream::empty())
+        }
+    }
+
+    impl WebsocketReq<NoCustomError> for MemoryReq {
+        type WebsocketResponse = Response<Body>;
+
+        async fn try_into_websocket(
+            self,
+        ) -> Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+                Self::WebsocketResponse,
+            ),
+            ServerFnError,
+        > {
+            Ok((
+                self.incoming,
+                self.outgoing,
+                Response::new(Body::Sync(Bytes::new())),
+            ))
+        }
+
+        fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+            std::thread::spawn(move || block_on(future));
+        }
+    }
+
+    /// A client that runs the server function in another thread of the same process.
+    struct MemoryClient;
+
+    impl Client<NoCustomError> for MemoryClient {
+        type Request = Request<Bytes>;
+        type Response = Response<Bytes>;
+
+        async fn send(
+            _req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError> {
+            Err(http_unsupported())
+        }
+
+        async fn sleep(_duration: std::time::Duration) {}
+
+        async fn open_websocket(
+            _path: &str,
+        ) -> Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            ),
+            ServerFnError,
+        > {
+            let (client_tx, server_rx) = mpsc::channel(8);
+            let (server_tx, client_rx) = mpsc::channel(8);
+            let req = MemoryReq {
+                incoming: server_rx,
+                outgoing: server_tx,
+            };
+            run_websocket_server::<Shout, String, String, Json, Json>(req)
+                .await;
+            Ok((client_rx, client_tx))
+        }
+    }
+
+    /// Uppercases each message, and fails on empty ones.
+    struct Shout(BoxedStream<String>);
+
+    impl From<BoxedStream<String>> for Shout {
+        fn from(input: BoxedStream<String>) -> Self {
+            Self(input)
+        }
+    }
+
+    impl From<Shout> for BoxedStream<String> {
+        fn from(input: Shout) -> Self {
+            input.0
+        }
+    }
+
+    impl ServerFn for Shout {
+        const PATH: &'static str = "/api/shout";
+        type Client = MemoryClient;
+        type ServerRequest = MemoryReq;
+        type ServerResponse = Response<Body>;
+        type Output = BoxedStream<String>;
+        type InputEncoding = Websocket<Json, Json>;
+        type OutputEncoding = Websocket<Json, Json>;
+        type Error = NoCustomError;
+
+        async fn run_body(self) -> Result<Self::Output, ServerFnError> {
+            Ok(BoxedStream::new(self.0.map(|item| match item {
+                Ok(value) if value.is_empty() => {
+                    Err(ServerFnError::ServerError("empty".into()))
+                }
+                Ok(value) => Ok(value.to_uppercase()),
+                Err(e) => Err(e),
+            })))
+        }
+    }
+
+    #[test]
+    fn streams_in_both_directions() {
+        let input = BoxedStream::new(futures::stream::iter([
+            Ok("hello".to_string()),
+            Ok(String::new()),
+            Err(ServerFnError::Args("bad input".into())),
+            Ok("world".to_string()),
+        ]));
+        let output = block_on(async {
+            run_websocket_client::<Shout, String, String, Json, Json>(Shout(
+                input,
+            ))
+            .await
+            .unwrap()
+            .take(4)
+            .collect::<Vec<_>>()
+            .await
+        });
+        assert_eq!(
+            output,
+            vec![
+                Ok("HELLO".to_string()),
+                Err(ServerFnError::ServerError("empty".into())),
+                Err(ServerFnError::Args("bad input".into())),
+                Ok("WORLD".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cannot_be_called_over_http() {
+        let req: Result<Request<Bytes>, _> = <Shout as IntoReq<
+            Websocket<Json, Json>,
+            _,
+            NoCustomError,
+        >>::into_req(
+            Shout(BoxedStream::new(futures::stream::empty())),
+            Shout::PATH,
+            "",
+        );
+        assert!(matches!(req, Err(ServerFnError::Request(_))));
+    }
+}
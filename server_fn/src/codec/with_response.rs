@@ -0,0 +1,149 @@
+use super::{FromRes, IntoRes};
+use crate::{
+    error::ServerFnError,
+    response::{ClientRes, Res},
+};
+use std::fmt::Display;
+
+/// Wraps the output of a server function with a status code and headers that should be
+/// added to its response.
+///
+/// This works with every output encoding, and on every server backend, so the same server
+/// function can set its response metadata without reaching into framework-specific types.
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn create_todo(
+///     title: String,
+/// ) -> Result<WithResponse<usize>, ServerFnError> {
+///     let id = todo!();
+///     Ok(WithResponse::new(id)
+///         .status(201)
+///         .header("Location", format!("/todos/{id}"))
+///         .cookie("last_created=1; Path=/"))
+/// }
+/// ```
+///
+/// The status code and headers are only applied to successful responses: errors are always
+/// returned as a [`ServerFnError`]. On the client, only the status code of the response is
+/// available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithResponse<T> {
+    value: T,
+    status: Option<u16>,
+    headers: Vec<(String, String)>,
+}
+
+impl<T> WithResponse<T> {
+    /// Wraps the given value, without changing its response.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            status: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Sets the status code of the response.
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Appends a header to the response.
+    pub fn header(mut self, name: impl Display, value: impl Display) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds a cookie to the response, as a `Set-Cookie` header.
+    ///
+    /// The cookie should be in the same format as the value of the `Set-Cookie` header,
+    /// e.g., `"name=value; Path=/; HttpOnly"`.
+    pub fn cookie(self, cookie: impl Display) -> Self {
+        self.header(http::header::SET_COOKIE, cookie)
+    }
+
+    /// The status code of the response, if one has been set.
+    ///
+    /// On the client, this is the status code with which the response was received.
+    pub fn status_code(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// The headers that will be added to the response.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// A reference to the wrapped value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Consumes the wrapper, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for WithResponse<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<Enc, T, Response, CustErr> IntoRes<Enc, Response, CustErr>
+    for WithResponse<T>
+where
+    T: IntoRes<Enc, Response, CustErr> + Send,
+    Response: Res<CustErr> + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let mut res = self.value.into_res().await?;
+        if let Some(status) = self.status {
+            res.set_status(status)?;
+        }
+        for (name, value) in &self.headers {
+            res.append_header(name, value)?;
+        }
+        Ok(res)
+    }
+}
+
+impl<Enc, T, Response, CustErr> FromRes<Enc, Response, CustErr>
+    for WithResponse<T>
+where
+    T: FromRes<Enc, Response, CustErr>,
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let status = res.status();
+        let value = T::from_res(res).await?;
+        Ok(WithResponse::new(value).status(status))
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<T> schemars::JsonSchema for WithResponse<T>
+where
+    T: schemars::JsonSchema,
+{
+    fn is_referenceable() -> bool {
+        T::is_referenceable()
+    }
+
+    fn schema_name() -> String {
+        T::schema_name()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        T::schema_id()
+    }
+
+    fn json_schema(
+        gen: &mut schemars::gen::SchemaGenerator,
+    ) -> schemars::schema::Schema {
+        T::json_schema(gen)
+    }
+}
@@ -1,3 +1,7 @@
+use crate::{
+    codec::{Decodes, Encodes},
+    response::{ClientRes, Res},
+};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt,
@@ -52,6 +56,20 @@ impl FromStr for NoCustomError {
     }
 }
 
+impl ErrorStatus for NoCustomError {}
+
+/// Provides a hint for the HTTP status code that should be used when a custom error type is
+/// sent back to the client.
+///
+/// This is used by [structured errors](structured_error_response). The default
+/// implementation uses `500 Internal Server Error`.
+pub trait ErrorStatus {
+    /// The HTTP status code for this error.
+    fn status_code(&self) -> u16 {
+        500
+    }
+}
+
 /// Wraps some error type, which may implement any of [`Error`](trait@std::error::Error), [`Clone`], or
 /// [`Display`].
 #[derive(Debug)]
@@ -160,6 +178,10 @@ pub enum ServerFnError<E = NoCustomError> {
     Args(String),
     /// Occurs on the server if there's a missing argument.
     MissingArg(String),
+    /// Occurs on the client if the server did not respond before the request timed out.
+    Timeout(String),
+    /// Occurs on the client if the request was aborted before it completed.
+    Cancelled(String),
 }
 
 impl ServerFnError<NoCustomError> {
@@ -169,6 +191,50 @@ impl ServerFnError<NoCustomError> {
     }
 }
 
+impl<CustErr> ServerFnError<CustErr> {
+    /// Converts the custom error type of this error, using the given function to convert a
+    /// [`WrappedServerError`](ServerFnError::WrappedServerError). All other variants are
+    /// unchanged.
+    pub fn map_custom_error<E>(
+        self,
+        f: impl FnOnce(CustErr) -> ServerFnError<E>,
+    ) -> ServerFnError<E> {
+        match self {
+            ServerFnError::WrappedServerError(e) => f(e),
+            ServerFnError::Registration(e) => ServerFnError::Registration(e),
+            ServerFnError::Request(e) => ServerFnError::Request(e),
+            ServerFnError::Response(e) => ServerFnError::Response(e),
+            ServerFnError::ServerError(e) => ServerFnError::ServerError(e),
+            ServerFnError::Deserialization(e) => {
+                ServerFnError::Deserialization(e)
+            }
+            ServerFnError::Serialization(e) => ServerFnError::Serialization(e),
+            ServerFnError::Args(e) => ServerFnError::Args(e),
+            ServerFnError::MissingArg(e) => ServerFnError::MissingArg(e),
+            ServerFnError::Timeout(e) => ServerFnError::Timeout(e),
+            ServerFnError::Cancelled(e) => ServerFnError::Cancelled(e),
+        }
+    }
+}
+
+impl<CustErr> ServerFnError<CustErr>
+where
+    CustErr: ErrorStatus,
+{
+    /// A hint for the HTTP status code that should be used when sending this error to the client.
+    ///
+    /// Errors in the arguments sent by the client use `400 Bad Request`, custom errors use
+    /// their [`ErrorStatus`] implementation, and all other errors use
+    /// `500 Internal Server Error`.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ServerFnError::WrappedServerError(e) => e.status_code(),
+            ServerFnError::Args(_) | ServerFnError::MissingArg(_) => 400,
+            _ => 500,
+        }
+    }
+}
+
 impl<CustErr> From<CustErr> for ServerFnError<CustErr> {
     fn from(value: CustErr) -> Self {
         ServerFnError::WrappedServerError(value)
@@ -206,6 +272,10 @@ where
                     "error deserializing server function arguments: {s}"
                 ),
                 ServerFnError::MissingArg(s) => format!("missing argument {s}"),
+                ServerFnError::Timeout(s) =>
+                    format!("server function request timed out: {s}"),
+                ServerFnError::Cancelled(s) =>
+                    format!("server function request was cancelled: {s}"),
                 ServerFnError::Response(s) =>
                     format!("error generating HTTP response: {s}"),
                 ServerFnError::WrappedServerError(e) => format!("{e}"),
@@ -231,11 +301,13 @@ pub trait ServerFnErrorSerde: Sized {
     fn de(data: &str) -> Self;
 }
 
-impl<CustErr> ServerFnErrorSerde for ServerFnError<CustErr>
+impl<CustErr> ServerFnError<CustErr>
 where
-    CustErr: FromStr + Display,
+    CustErr: Display,
 {
-    fn ser(&self) -> Result<String, std::fmt::Error> {
+    /// Serializes the error in the format read by [`ServerFnErrorSerde::de`], which only
+    /// requires the custom error type to implement [`Display`].
+    pub(crate) fn ser_text(&self) -> Result<String, std::fmt::Error> {
         let mut buf = String::new();
         match self {
             ServerFnError::WrappedServerError(e) => {
@@ -259,9 +331,20 @@ where
             ServerFnError::MissingArg(e) => {
                 write!(&mut buf, "MissingArg|{e}")
             }
+            ServerFnError::Timeout(e) => write!(&mut buf, "Timeout|{e}"),
+            ServerFnError::Cancelled(e) => write!(&mut buf, "Cancelled|{e}"),
         }?;
         Ok(buf)
     }
+}
+
+impl<CustErr> ServerFnErrorSerde for ServerFnError<CustErr>
+where
+    CustErr: FromStr + Display,
+{
+    fn ser(&self) -> Result<String, std::fmt::Error> {
+        self.ser_text()
+    }
 
     fn de(data: &str) -> Self {
         data.split_once('|')
@@ -288,6 +371,8 @@ where
                 "MissingArg" => {
                     Some(ServerFnError::MissingArg(data.to_string()))
                 }
+                "Timeout" => Some(ServerFnError::Timeout(data.to_string())),
+                "Cancelled" => Some(ServerFnError::Cancelled(data.to_string())),
                 _ => None,
             })
             .unwrap_or_else(|| {
@@ -298,6 +383,79 @@ where
     }
 }
 
+/// Converts an error into a response using the structured error format.
+///
+/// Unlike [`Res::error_response`], which flattens the error into a string with
+/// [`ServerFnErrorSerde`], this serializes the whole [`ServerFnError`] with the encoding `Enc`
+/// (usually the output encoding of the server function), so custom error types keep their
+/// structure and messages can contain any characters. The response uses the status code given
+/// by [`ServerFnError::status_code`].
+///
+/// This is used by server functions with `structured_errors = true`, and can be read on the
+/// client with [`decode_structured_error`].
+pub fn structured_error_response<Enc, CustErr, Response>(
+    path: &str,
+    err: &ServerFnError<CustErr>,
+) -> Response
+where
+    Enc: Encodes<ServerFnError<CustErr>>,
+    CustErr: ErrorStatus,
+    Response: Res<CustErr>,
+{
+    let encoded = Enc::encode(err)
+        .map(|data| (err.status_code(), data))
+        .or_else(|e| {
+            // an error that cannot be encoded is replaced by one that can, as the client
+            // expects every error from this server function to use `Enc`
+            Enc::encode(&ServerFnError::Serialization(e.to_string()))
+                .map(|data| (500, data))
+        });
+    match encoded {
+        Ok((status, data)) => Response::encoded_error_response(
+            path,
+            status,
+            Enc::CONTENT_TYPE,
+            data,
+        ),
+        Err(e) => Response::error_response(
+            path,
+            &ServerFnError::Serialization(e.to_string()),
+        ),
+    }
+}
+
+/// Reads an error from a response that was created by [`structured_error_response`].
+///
+/// Error responses that do not use the encoding `Enc` (for example, errors returned by
+/// middleware with [`Res::error_response`]) are read as a [`ServerFnError`] without a custom
+/// error, as the custom error type is only ever sent with `Enc`.
+pub async fn decode_structured_error<Enc, CustErr, Response>(
+    res: Response,
+) -> ServerFnError<CustErr>
+where
+    Enc: Decodes<ServerFnError<CustErr>>,
+    Response: ClientRes<CustErr>,
+{
+    let structured = res
+        .header(http::header::CONTENT_TYPE.as_str())
+        .is_some_and(|content_type| content_type == Enc::CONTENT_TYPE);
+    let data = match res.try_into_bytes().await {
+        Ok(data) => data,
+        Err(e) => return e,
+    };
+    if structured {
+        Enc::decode(data).unwrap_or_else(|e| {
+            ServerFnError::Deserialization(format!(
+                "Could not deserialize error: {e}"
+            ))
+        })
+    } else {
+        let text = String::from_utf8_lossy(&data);
+        ServerFnError::<NoCustomError>::de(&text)
+            .map_custom_error(|_| ServerFnError::ServerError(text.into_owned()))
+    }
+}
+
 impl<E> std::error::Error for ServerFnError<E>
 where
     E: std::error::Error + 'static,
@@ -349,6 +507,12 @@ pub enum ServerFnErrorErr<E = NoCustomError> {
     /// Occurs on the server if there is an error creating an HTTP response.
     #[error("error creating response {0}")]
     Response(String),
+    /// Occurs on the client if the server did not respond before the request timed out.
+    #[error("server function request timed out: {0}")]
+    Timeout(String),
+    /// Occurs on the client if the request was aborted before it completed.
+    #[error("server function request was cancelled: {0}")]
+    Cancelled(String),
 }
 
 impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
@@ -375,6 +539,10 @@ impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
                 ServerFnErrorErr::WrappedServerError(value)
             }
             ServerFnError::Response(value) => ServerFnErrorErr::Response(value),
+            ServerFnError::Timeout(value) => ServerFnErrorErr::Timeout(value),
+            ServerFnError::Cancelled(value) => {
+                ServerFnErrorErr::Cancelled(value)
+            }
         }
     }
 }
@@ -461,3 +629,103 @@ impl<CustErr> From<ServerFnUrlError<CustErr>> for ServerFnErrorErr<CustErr> {
         error.error.into()
     }
 }
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::{codec::Json, response::generic::Body};
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::Response;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    enum AppError {
+        NotFound { id: u32 },
+        Invalid(String),
+    }
+
+    impl Display for AppError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl ErrorStatus for AppError {
+        fn status_code(&self) -> u16 {
+            match self {
+                AppError::NotFound { .. } => 404,
+                AppError::Invalid(_) => 422,
+            }
+        }
+    }
+
+    fn into_client_res(res: Response<Body>) -> Response<Bytes> {
+        res.map(|body| match body {
+            Body::Sync(data) => data,
+            Body::Async(_) => panic!("expected a buffered body"),
+        })
+    }
+
+    fn round_trip<Enc>(
+        err: ServerFnError<AppError>,
+    ) -> (u16, ServerFnError<AppError>)
+    where
+        Enc:
+            Encodes<ServerFnError<AppError>> + Decodes<ServerFnError<AppError>>,
+    {
+        let res: Response<Body> =
+            structured_error_response::<Enc, _, _>("/api/test", &err);
+        let status = res.status().as_u16();
+        let decoded = block_on(decode_structured_error::<Enc, _, _>(
+            into_client_res(res),
+        ));
+        (status, decoded)
+    }
+
+    #[test]
+    fn structured_errors_round_trip() {
+        let (status, err) = round_trip::<Json>(
+            ServerFnError::WrappedServerError(AppError::NotFound { id: 7 }),
+        );
+        assert_eq!(status, 404);
+        assert_eq!(
+            err,
+            ServerFnError::WrappedServerError(AppError::NotFound { id: 7 })
+        );
+
+        // messages are not flattened, so they can contain the `|` separator
+        let (status, err) = round_trip::<Json>(
+            ServerFnError::WrappedServerError(AppError::Invalid("a|b".into())),
+        );
+        assert_eq!(status, 422);
+        assert_eq!(
+            err,
+            ServerFnError::WrappedServerError(AppError::Invalid("a|b".into()))
+        );
+
+        let (status, err) =
+            round_trip::<Json>(ServerFnError::Args("x|y\nz".into()));
+        assert_eq!(status, 400);
+        assert_eq!(err, ServerFnError::Args("x|y\nz".into()));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn structured_errors_round_trip_with_cbor() {
+        use crate::codec::Cbor;
+
+        let err =
+            ServerFnError::WrappedServerError(AppError::Invalid("a|b".into()));
+        assert_eq!(round_trip::<Cbor>(err.clone()), (422, err));
+    }
+
+    #[test]
+    fn unstructured_errors_are_still_decoded() {
+        let err = ServerFnError::<NoCustomError>::ServerError("oops".into());
+        let res: Response<Body> = Res::error_response("/api/test", &err);
+        let decoded = block_on(decode_structured_error::<Json, _, _>(
+            into_client_res(res),
+        ));
+        assert_eq!(decoded, err);
+    }
+}
@@ -105,14 +105,29 @@ pub mod codec;
 #[macro_use]
 /// Error types and utilities.
 pub mod error;
-/// Types to add server middleware to a server function.
+/// Batching several server function calls into a single request.
+pub mod batch;
+#[cfg(feature = "ssr")]
+/// Manifests of the paths of the registered server functions, which can be compared between
+/// releases.
+pub mod manifest;
+/// Types to add middleware to a server function, on the server or the client.
 pub mod middleware;
+#[cfg(feature = "openapi")]
+/// Generates an OpenAPI document describing the registered server functions.
+pub mod openapi;
 /// Utilities to allow client-side redirects.
 pub mod redirect;
+#[cfg(feature = "ssr")]
+/// Lists the registered server functions.
+pub mod registry;
 /// Types and traits for  for HTTP requests.
 pub mod request;
 /// Types and traits for HTTP responses.
 pub mod response;
+#[cfg(all(feature = "ssr", feature = "generic"))]
+/// Calls server functions in the same process, for tests.
+pub mod testing;
 
 #[cfg(feature = "actix")]
 #[doc(hidden)]
@@ -126,7 +141,10 @@ pub use ::bytes as bytes_export;
 #[cfg(feature = "generic")]
 #[doc(hidden)]
 pub use ::http as http_export;
-use client::Client;
+#[doc(hidden)]
+#[cfg(feature = "bitcode")]
+pub use bitcode;
+use client::{CallOptions, Client};
 use codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
 #[doc(hidden)]
 pub use const_format;
@@ -136,13 +154,16 @@ use error::ServerFnErrorSerde;
 #[cfg(feature = "form-redirects")]
 use error::ServerFnUrlError;
 use http::Method;
-use middleware::{Layer, Service};
+use middleware::{client::ClientMiddlewareSet, Layer, Service};
 use once_cell::sync::Lazy;
 use redirect::RedirectHook;
-use request::Req;
+use request::{ClientReq, Req};
 use response::{ClientRes, Res};
 #[cfg(feature = "rkyv")]
 pub use rkyv;
+#[cfg(feature = "openapi")]
+#[doc(hidden)]
+pub use schemars;
 #[doc(hidden)]
 pub use serde;
 #[doc(hidden)]
@@ -194,6 +215,17 @@ where
     /// A unique path for the server function’s API endpoint, relative to the host, including its prefix.
     const PATH: &'static str;
 
+    /// The version of the server function’s API, which is part of its path, if it has one.
+    ///
+    /// This is set with the `version` argument to the `#[server]` macro.
+    const VERSION: Option<u32> = None;
+
+    /// Whether the client may cache the responses of this server function.
+    ///
+    /// This is set with the `cache` argument to the `#[server]` macro, unless the output is
+    /// streamed.
+    const CACHE: bool = false;
+
     /// The type of the HTTP client that will send the request from the client side.
     ///
     /// For example, this might be `gloo-net` in the browser, or `reqwest` for a desktop app.
@@ -237,6 +269,23 @@ where
         Vec::new()
     }
 
+    /// Middleware that should be applied to this server function on the client, in addition
+    /// to any [global client middleware](middleware::client::add_client_middleware).
+    #[allow(clippy::type_complexity)]
+    fn client_middlewares() -> ClientMiddlewareSet<
+        <Self::Client as Client<Self::Error>>::Request,
+        <Self::Client as Client<Self::Error>>::Response,
+    > {
+        Vec::new()
+    }
+
+    /// The timeout and retry policy used when this server function is called from the client.
+    ///
+    /// Defaults to the options set with [`client::set_call_options`].
+    fn call_options() -> CallOptions {
+        client::get_call_options()
+    }
+
     /// The body of the server function. This will only run on the server.
     fn run_body(
         self,
@@ -263,12 +312,7 @@ where
             let (mut res, err) = Self::execute_on_server(req)
                 .await
                 .map(|res| (res, None))
-                .unwrap_or_else(|e| {
-                    (
-                        Self::ServerResponse::error_response(Self::PATH, &e),
-                        Some(e),
-                    )
-                });
+                .unwrap_or_else(|e| (Self::error_response(&e), Some(e)));
 
             // if it accepts HTML, we'll redirect to the Referer
             #[cfg(feature = "form-redirects")]
@@ -316,16 +360,57 @@ where
     ) -> impl Future<Output = Result<Self::Output, ServerFnError<Self::Error>>> + Send
     {
         async move {
-            let res = Self::Client::send(req).await?;
+            let options = Self::call_options();
+            let mut req = req;
+            if let Some(timeout) = options.timeout {
+                req.set_timeout(timeout);
+            }
+            if Self::CACHE {
+                req.enable_cache();
+            }
+
+            // only requests that are expected to be idempotent are retried
+            let retries = if Self::InputEncoding::METHOD == Method::GET {
+                options.retries
+            } else {
+                0
+            };
+            let mut retry = 0;
+            let res = loop {
+                // the result is dropped before waiting to retry, as its error type may not be `Send`
+                req = {
+                    let next = if retry < retries {
+                        req.try_clone_request()
+                    } else {
+                        None
+                    };
+                    let res = middleware::client::send::<Self>(req).await;
+                    let should_retry = match &res {
+                        Ok(res) => {
+                            matches!(res.status(), 429 | 502 | 503 | 504)
+                        }
+                        Err(
+                            ServerFnError::Request(_)
+                            | ServerFnError::Timeout(_),
+                        ) => true,
+                        Err(_) => false,
+                    };
+                    match next {
+                        Some(next) if should_retry => next,
+                        _ => break res?,
+                    }
+                };
+                Self::Client::sleep(options.backoff_for(retry)).await;
+                retry += 1;
+            };
 
             let status = res.status();
             let location = res.location();
             let has_redirect_header = res.has_redirect();
 
-            // if it returns an error status, deserialize the error using FromStr
+            // if it returns an error status, deserialize the error
             let res = if (400..=599).contains(&status) {
-                let text = res.try_into_string().await?;
-                Err(ServerFnError::<Self::Error>::de(&text))
+                Err(Self::decode_error(res).await)
             } else {
                 // otherwise, deserialize the body as is
                 Ok(Self::Output::from_res(res).await)
@@ -341,6 +426,35 @@ where
         }
     }
 
+    /// Converts an error returned by the server function into a response.
+    ///
+    /// By default, this uses [`Res::error_response`]. Server functions with
+    /// `structured_errors = true` use [`error::structured_error_response`] instead.
+    #[doc(hidden)]
+    fn error_response(
+        err: &ServerFnError<Self::Error>,
+    ) -> Self::ServerResponse {
+        Self::ServerResponse::error_response(Self::PATH, err)
+    }
+
+    /// Reads the error from a response with an error status code, on the client.
+    ///
+    /// This should match the format used by [`error_response`](ServerFn::error_response).
+    #[doc(hidden)]
+    fn decode_error<Response>(
+        res: Response,
+    ) -> impl Future<Output = ServerFnError<Self::Error>> + Send
+    where
+        Response: ClientRes<Self::Error> + Send,
+    {
+        async move {
+            match res.try_into_string().await {
+                Ok(text) => ServerFnError::de(&text),
+                Err(e) => e,
+            }
+        }
+    }
+
     /// Runs the server function (on the server), bubbling up an `Err(_)` after any stage.
     #[doc(hidden)]
     fn execute_on_server(
@@ -385,6 +499,10 @@ pub type MiddlewareSet<Req, Res> = Vec<Arc<dyn Layer<Req, Res>>>;
 pub struct ServerFnTraitObj<Req, Res> {
     path: &'static str,
     method: Method,
+    input_content_type: &'static str,
+    output_content_type: &'static str,
+    name: &'static str,
+    version: Option<u32>,
     handler: fn(Req) -> Pin<Box<dyn Future<Output = Res> + Send>>,
     middleware: fn() -> MiddlewareSet<Req, Res>,
 }
@@ -400,11 +518,38 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
         Self {
             path,
             method,
+            input_content_type: "",
+            output_content_type: "",
+            name: "",
+            version: None,
             handler,
             middleware,
         }
     }
 
+    /// Sets the content types of the encodings of the server function’s arguments and output.
+    pub const fn with_encodings(
+        mut self,
+        input_content_type: &'static str,
+        output_content_type: &'static str,
+    ) -> Self {
+        self.input_content_type = input_content_type;
+        self.output_content_type = output_content_type;
+        self
+    }
+
+    /// Sets the name of the server function’s type, including its module path.
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Sets the version of the server function’s API.
+    pub const fn with_version(mut self, version: Option<u32>) -> Self {
+        self.version = version;
+        self
+    }
+
     /// The path of the server function.
     pub fn path(&self) -> &'static str {
         self.path
@@ -415,6 +560,26 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
         self.method.clone()
     }
 
+    /// The content type of the encoding of the server function’s arguments, if it is known.
+    pub fn input_content_type(&self) -> &'static str {
+        self.input_content_type
+    }
+
+    /// The content type of the encoding of the server function’s output, if it is known.
+    pub fn output_content_type(&self) -> &'static str {
+        self.output_content_type
+    }
+
+    /// The name of the server function’s type, including its module path, if it is known.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The version of the server function’s API, if it has one.
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
     /// The handler for this server function.
     pub fn handler(&self, req: Req) -> impl Future<Output = Res> + Send {
         (self.handler)(req)
@@ -442,6 +607,10 @@ impl<Req, Res> Clone for ServerFnTraitObj<Req, Res> {
         Self {
             path: self.path,
             method: self.method.clone(),
+            input_content_type: self.input_content_type,
+            output_content_type: self.output_content_type,
+            name: self.name,
+            version: self.version,
             handler: self.handler,
             middleware: self.middleware,
         }
@@ -468,6 +637,7 @@ impl<Req: 'static, Res: 'static> inventory::Collect
 pub mod axum {
     use crate::{
         middleware::{BoxedService, Service},
+        registry::{self, DuplicatePath},
         Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
     };
     use axum::body::Body;
@@ -495,7 +665,24 @@ pub mod axum {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_encodings(
+                T::InputEncoding::CONTENT_TYPE,
+                T::OutputEncoding::CONTENT_TYPE,
+            )
+            .with_name(std::any::type_name::<T>())
+            .with_version(T::VERSION),
+        );
+    }
+
+    /// Registers the endpoint that [`Batch`](crate::batch::Batch) clients send batched calls to,
+    /// for server functions with the given prefix (by default, `/api`).
+    pub fn register_batch_endpoint(prefix: &str) {
+        let path: &'static str =
+            Box::leak(crate::batch::batch_path(prefix).into_boxed_str());
+        REGISTERED_SERVER_FUNCTIONS.insert(
+            (path.into(), Method::POST),
+            crate::batch::axum::endpoint(path),
         );
     }
 
@@ -506,6 +693,24 @@ pub mod axum {
             .map(|item| (item.path(), item.method()))
     }
 
+    /// Every registered server function, including those registered explicitly.
+    pub fn server_fns() -> Vec<ServerFnTraitObj<Request<Body>, Response<Body>>>
+    {
+        REGISTERED_SERVER_FUNCTIONS
+            .iter()
+            .map(|item| item.value().clone())
+            .collect()
+    }
+
+    /// Each path and method at which more than one server function was registered
+    /// automatically. Only one of them can be called.
+    pub fn duplicate_server_fn_paths() -> Vec<DuplicatePath> {
+        registry::duplicate_paths(registry::registered::<
+            Request<Body>,
+            Response<Body>,
+        >())
+    }
+
     /// An Axum handler that responds to a server function request.
     pub async fn handle_server_fn(req: Request<Body>) -> Response<Body> {
         let path = req.uri().path();
@@ -552,9 +757,11 @@ pub mod axum {
 #[cfg(feature = "actix")]
 pub mod actix {
     use crate::{
-        middleware::BoxedService, request::actix::ActixRequest,
-        response::actix::ActixResponse, Encoding, LazyServerFnMap, ServerFn,
-        ServerFnTraitObj,
+        middleware::BoxedService,
+        registry::{self, DuplicatePath},
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
     };
     use actix_web::{web::Payload, HttpRequest, HttpResponse};
     use http::Method;
@@ -583,7 +790,24 @@ pub mod actix {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_encodings(
+                T::InputEncoding::CONTENT_TYPE,
+                T::OutputEncoding::CONTENT_TYPE,
+            )
+            .with_name(std::any::type_name::<T>())
+            .with_version(T::VERSION),
+        );
+    }
+
+    /// Registers the endpoint that [`Batch`](crate::batch::Batch) clients send batched calls to,
+    /// for server functions with the given prefix (by default, `/api`).
+    pub fn register_batch_endpoint(prefix: &str) {
+        let path: &'static str =
+            Box::leak(crate::batch::batch_path(prefix).into_boxed_str());
+        REGISTERED_SERVER_FUNCTIONS.insert(
+            (path.into(), Method::POST),
+            crate::batch::actix::endpoint(path),
         );
     }
 
@@ -594,6 +818,23 @@ pub mod actix {
             .map(|item| (item.path(), item.method()))
     }
 
+    /// Every registered server function, including those registered explicitly.
+    pub fn server_fns() -> Vec<ServerFnTraitObj<ActixRequest, ActixResponse>> {
+        REGISTERED_SERVER_FUNCTIONS
+            .iter()
+            .map(|item| item.value().clone())
+            .collect()
+    }
+
+    /// Each path and method at which more than one server function was registered
+    /// automatically. Only one of them can be called.
+    pub fn duplicate_server_fn_paths() -> Vec<DuplicatePath> {
+        registry::duplicate_paths(registry::registered::<
+            ActixRequest,
+            ActixResponse,
+        >())
+    }
+
     /// An Actix handler that responds to a server function request.
     pub async fn handle_server_fn(
         req: HttpRequest,
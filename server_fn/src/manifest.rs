@@ -0,0 +1,360 @@
+use crate::{registry, ServerFnTraitObj};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// A list of the paths at which server functions are served, with the encodings they expect.
+///
+/// A client that was built against one release of an app can only call the server functions
+/// of the next release if their paths and encodings have not changed. The manifest of each
+/// release can be generated while it is built, for example in a test, and compared with the
+/// manifest of the previous release to find any changes that would break older clients:
+///
+/// ```rust,ignore
+/// use server_fn::manifest::Manifest;
+///
+/// #[test]
+/// fn server_fns_are_compatible() {
+///     let manifest = Manifest::registered::<Request<Body>, Response<Body>>();
+///     let previous = Manifest::from_json(include_str!("../server_fns.json")).unwrap();
+///     let breaking = previous
+///         .diff(&manifest)
+///         .into_iter()
+///         .filter(|change| change.is_breaking())
+///         .map(|change| change.to_string())
+///         .collect::<Vec<_>>();
+///     assert!(breaking.is_empty(), "{breaking:#?}");
+///     std::fs::write("server_fns.json", manifest.to_json()).unwrap();
+/// }
+/// ```
+///
+/// The default path of a server function contains a hash of the location at which it is
+/// defined, so it changes whenever the function moves. The `SERVER_FN_PATH_HASH` environment
+/// variable can be set to `module` (to hash only its module and name) or `name` (to hash only
+/// its name) while building to keep paths stable, or a path can be set with the `endpoint`
+/// argument to the `#[server]` macro.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The server functions, sorted by path and method.
+    pub server_fns: Vec<ManifestEntry>,
+}
+
+/// A server function in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// The name of the server function’s type, including its module path.
+    pub name: String,
+    /// The path at which the server function is served.
+    pub path: String,
+    /// The HTTP method the server function expects.
+    pub method: String,
+    /// The version of the server function’s API, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// The content type of the encoding of the server function’s arguments.
+    pub input: String,
+    /// The content type of the encoding of the server function’s output.
+    pub output: String,
+}
+
+impl ManifestEntry {
+    fn is_compatible_with(&self, other: &ManifestEntry) -> bool {
+        self.method == other.method
+            && self.input == other.input
+            && self.output == other.output
+    }
+}
+
+impl Manifest {
+    /// Lists these server functions.
+    pub fn new<'a, Req, Res>(
+        server_fns: impl IntoIterator<Item = &'a ServerFnTraitObj<Req, Res>>,
+    ) -> Self
+    where
+        Req: 'a,
+        Res: 'a,
+    {
+        let mut server_fns = server_fns
+            .into_iter()
+            .map(|server_fn| ManifestEntry {
+                name: server_fn.name().to_string(),
+                path: server_fn.path().to_string(),
+                method: server_fn.method().to_string(),
+                version: server_fn.version(),
+                input: server_fn.input_content_type().to_string(),
+                output: server_fn.output_content_type().to_string(),
+            })
+            .collect::<Vec<_>>();
+        server_fns.sort_by(|a, b| {
+            (&a.path, &a.method, &a.name).cmp(&(&b.path, &b.method, &b.name))
+        });
+        Self { server_fns }
+    }
+
+    /// Lists every server function that was registered automatically for this pair of request
+    /// and response types.
+    pub fn registered<Req, Res>() -> Self
+    where
+        Req: 'static,
+        Res: 'static,
+    {
+        Self::new(registry::registered::<Req, Res>())
+    }
+
+    /// Serializes the manifest as pretty-printed JSON, so that it can be committed and diffed.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            .expect("a manifest can always be serialized")
+    }
+
+    /// Deserializes a manifest from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Lists the changes from this manifest to a newer one.
+    ///
+    /// A server function that is served at a new path, but has the same name and version as one
+    /// that was removed, is reported as [`ManifestChange::Moved`].
+    pub fn diff(&self, newer: &Manifest) -> Vec<ManifestChange> {
+        let old_paths = self
+            .server_fns
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry))
+            .collect::<HashMap<_, _>>();
+        let new_paths = newer
+            .server_fns
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry))
+            .collect::<HashMap<_, _>>();
+        let added = newer
+            .server_fns
+            .iter()
+            .filter(|entry| !old_paths.contains_key(entry.path.as_str()))
+            .collect::<Vec<_>>();
+
+        let mut changes = Vec::new();
+        let mut moved_to = Vec::new();
+        for old in &self.server_fns {
+            match new_paths.get(old.path.as_str()) {
+                Some(new) if !old.is_compatible_with(new) => {
+                    changes.push(ManifestChange::Changed {
+                        from: old.clone(),
+                        to: (*new).clone(),
+                    })
+                }
+                Some(_) => {}
+                None => match added.iter().find(|new| {
+                    new.name == old.name && new.version == old.version
+                }) {
+                    Some(new) => {
+                        moved_to.push(new.path.as_str());
+                        changes.push(ManifestChange::Moved {
+                            from: old.clone(),
+                            to: (*new).clone(),
+                        })
+                    }
+                    None => changes.push(ManifestChange::Removed(old.clone())),
+                },
+            }
+        }
+        changes.extend(
+            added
+                .into_iter()
+                .filter(|entry| !moved_to.contains(&entry.path.as_str()))
+                .map(|entry| ManifestChange::Added(entry.clone())),
+        );
+        changes
+    }
+}
+
+/// A change between two [`Manifest`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestChange {
+    /// A server function is served at a path that did not exist before.
+    Added(ManifestEntry),
+    /// A server function is no longer served.
+    Removed(ManifestEntry),
+    /// A server function is served at a different path.
+    Moved {
+        /// The server function as it was.
+        from: ManifestEntry,
+        /// The server function as it is now.
+        to: ManifestEntry,
+    },
+    /// The server function at a path expects a different method or encodings.
+    Changed {
+        /// The server function as it was.
+        from: ManifestEntry,
+        /// The server function as it is now.
+        to: ManifestEntry,
+    },
+}
+
+impl ManifestChange {
+    /// Whether this change breaks clients that were built against the older manifest.
+    ///
+    /// Only adding a server function keeps every older client working.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, ManifestChange::Added(_))
+    }
+}
+
+impl fmt::Display for ManifestChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestChange::Added(entry) => {
+                write!(
+                    f,
+                    "added {} at {} {}",
+                    entry.name, entry.method, entry.path
+                )
+            }
+            ManifestChange::Removed(entry) => write!(
+                f,
+                "removed {} at {} {}",
+                entry.name, entry.method, entry.path
+            ),
+            ManifestChange::Moved { from, to } => write!(
+                f,
+                "moved {} from {} to {}",
+                from.name, from.path, to.path
+            ),
+            ManifestChange::Changed { from, to } => write!(
+                f,
+                "changed {} at {} from {} ({} -> {}) to {} ({} -> {})",
+                to.name,
+                to.path,
+                from.method,
+                from.input,
+                from.output,
+                to.method,
+                to.input,
+                to.output
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, path: &str) -> ManifestEntry {
+        ManifestEntry {
+            name: name.into(),
+            path: path.into(),
+            method: "POST".into(),
+            version: None,
+            input: "application/x-www-form-urlencoded".into(),
+            output: "application/json".into(),
+        }
+    }
+
+    fn manifest(
+        server_fns: impl IntoIterator<Item = ManifestEntry>,
+    ) -> Manifest {
+        Manifest {
+            server_fns: server_fns.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn unchanged_manifests_have_no_changes() {
+        let old = manifest([entry("app::GetUser", "/api/get_user1")]);
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn finds_added_and_removed_server_fns() {
+        let old = manifest([
+            entry("app::GetUser", "/api/get_user1"),
+            entry("app::DeleteUser", "/api/delete_user2"),
+        ]);
+        let new = manifest([
+            entry("app::GetUser", "/api/get_user1"),
+            entry("app::AddUser", "/api/add_user3"),
+        ]);
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes,
+            [
+                ManifestChange::Removed(entry(
+                    "app::DeleteUser",
+                    "/api/delete_user2"
+                )),
+                ManifestChange::Added(entry("app::AddUser", "/api/add_user3")),
+            ]
+        );
+        assert!(changes[0].is_breaking());
+        assert!(!changes[1].is_breaking());
+        assert_eq!(
+            changes[1].to_string(),
+            "added app::AddUser at POST /api/add_user3"
+        );
+    }
+
+    #[test]
+    fn finds_moved_server_fns() {
+        let old = manifest([entry("app::GetUser", "/api/get_user1")]);
+        let new = manifest([entry("app::GetUser", "/api/get_user9")]);
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes,
+            [ManifestChange::Moved {
+                from: entry("app::GetUser", "/api/get_user1"),
+                to: entry("app::GetUser", "/api/get_user9"),
+            }]
+        );
+        assert!(changes[0].is_breaking());
+        assert_eq!(
+            changes[0].to_string(),
+            "moved app::GetUser from /api/get_user1 to /api/get_user9"
+        );
+
+        // a new version at a new path is not a move, as the old version is gone
+        let mut v2 = entry("app::GetUser", "/api/v2/get_user1");
+        v2.version = Some(2);
+        let changes = old.diff(&manifest([v2.clone()]));
+        assert_eq!(
+            changes,
+            [
+                ManifestChange::Removed(entry(
+                    "app::GetUser",
+                    "/api/get_user1"
+                )),
+                ManifestChange::Added(v2),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_changed_encodings() {
+        let old = manifest([entry("app::GetUser", "/api/get_user1")]);
+        let mut changed = entry("app::GetUser", "/api/get_user1");
+        changed.method = "GET".into();
+        let changes = old.diff(&manifest([changed.clone()]));
+        assert_eq!(
+            changes,
+            [ManifestChange::Changed {
+                from: entry("app::GetUser", "/api/get_user1"),
+                to: changed,
+            }]
+        );
+        assert!(changes[0].is_breaking());
+
+        // renaming the type alone does not change what clients send
+        let renamed = manifest([entry("app::FetchUser", "/api/get_user1")]);
+        assert!(old.diff(&renamed).is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut versioned = entry("app::GetUser", "/api/v2/get_user1");
+        versioned.version = Some(2);
+        let manifest =
+            manifest([entry("app::AddUser", "/api/add_user3"), versioned]);
+        let json = manifest.to_json();
+        assert!(json.contains("\"version\": 2"));
+        assert_eq!(Manifest::from_json(&json).unwrap(), manifest);
+    }
+}
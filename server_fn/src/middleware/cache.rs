@@ -0,0 +1,203 @@
+use super::{BoxedService, Layer, Service};
+use crate::{error::NoCustomError, request::Req, response::Res};
+use std::{future::Future, marker::PhantomData, pin::Pin, sync::Arc};
+
+/// Server middleware that makes the responses of a server function cacheable.
+///
+/// Each successful response is given a `Cache-Control` header and an `ETag` computed over its
+/// encoded body. If the request carries an `If-None-Match` header that matches the `ETag`, the
+/// body is dropped and a `304 Not Modified` response is sent instead.
+///
+/// This is usually added with the `cache` argument to the `#[server]` macro, and is intended
+/// for server functions that use a `GET` encoding, like
+/// [`GetUrl`](crate::codec::GetUrl):
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl, cache = "public, max-age=60")]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// As the body has to be hashed, the whole response is buffered before it is sent.
+#[derive(Debug, Clone)]
+pub struct CacheLayer {
+    cache_control: Arc<str>,
+}
+
+impl CacheLayer {
+    /// Creates a layer that adds the given `Cache-Control` header to successful responses.
+    pub fn new(cache_control: impl AsRef<str>) -> Self {
+        Self {
+            cache_control: cache_control.as_ref().into(),
+        }
+    }
+}
+
+impl<Request, Response> Layer<Request, Response> for CacheLayer
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(CacheService {
+            inner,
+            cache_control: Arc::clone(&self.cache_control),
+            ty: PhantomData,
+        })
+    }
+}
+
+struct CacheService<Request, Response> {
+    inner: BoxedService<Request, Response>,
+    cache_control: Arc<str>,
+    ty: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for CacheService<Request, Response>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let if_none_match = Req::<NoCustomError>::header(&req, "If-None-Match")
+            .map(|value| value.into_owned());
+        let path = Req::<NoCustomError>::path(&req)
+            .unwrap_or_default()
+            .to_string();
+        let res = self.inner.0.run(req);
+        let cache_control = Arc::clone(&self.cache_control);
+        Box::pin(async move {
+            let mut res = res.await;
+            if !(200..300).contains(&Res::<NoCustomError>::status(&res)) {
+                return res;
+            }
+
+            let body = match Res::<NoCustomError>::buffer_body(&mut res).await {
+                Ok(body) => body,
+                Err(e) => return Response::error_response(&path, &e),
+            };
+            let etag =
+                format!("\"{:016x}\"", xxhash_rust::xxh64::xxh64(&body, 0));
+            if if_none_match
+                .as_deref()
+                .is_some_and(|tags| etag_matches(tags, &etag))
+            {
+                // if the body cannot be dropped, the whole response is sent instead
+                if Res::<NoCustomError>::clear_body(&mut res).is_ok() {
+                    _ = Res::<NoCustomError>::set_status(&mut res, 304);
+                }
+            }
+
+            let headers = [("ETag", &*etag), ("Cache-Control", &cache_control)];
+            for (name, value) in headers {
+                if let Err(e) =
+                    Res::<NoCustomError>::append_header(&mut res, name, value)
+                {
+                    return Response::error_response(&path, &e);
+                }
+            }
+            res
+        })
+    }
+}
+
+/// Whether an `If-None-Match` header matches the given `ETag`, using weak comparison.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::response::generic::Body;
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::{Request, Response, StatusCode};
+
+    /// Responds with a fixed status and body.
+    struct Fixed(StatusCode, &'static str);
+
+    impl Service<Request<Bytes>, Response<Body>> for Fixed {
+        fn run(
+            &mut self,
+            _req: Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let mut res = Response::new(Body::Sync(Bytes::from(self.1)));
+            *res.status_mut() = self.0;
+            Box::pin(async move { res })
+        }
+    }
+
+    fn run(status: StatusCode, if_none_match: Option<&str>) -> Response<Body> {
+        let mut service = CacheLayer::new("public, max-age=60")
+            .layer(BoxedService::new(Fixed(status, "[1,2,3]")));
+        let mut req = Request::builder().uri("/api/list_posts");
+        if let Some(tags) = if_none_match {
+            req = req.header("If-None-Match", tags);
+        }
+        block_on(service.0.run(req.body(Bytes::new()).unwrap()))
+    }
+
+    fn body(res: Response<Body>) -> Bytes {
+        match res.into_body() {
+            Body::Sync(bytes) => bytes,
+            Body::Async(_) => panic!("expected a buffered body"),
+        }
+    }
+
+    #[test]
+    fn etags_match_with_weak_comparison() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "\"abc\""));
+        assert!(!etag_matches("\"xyz\"", "\"abc\""));
+        assert!(!etag_matches("abc", "\"abc\""));
+    }
+
+    #[test]
+    fn successful_responses_are_tagged() {
+        let res = run(StatusCode::OK, None);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["Cache-Control"], "public, max-age=60");
+        assert!(res.headers()["ETag"].to_str().unwrap().starts_with('"'));
+        assert_eq!(body(res), "[1,2,3]");
+    }
+
+    #[test]
+    fn matching_requests_are_not_modified() {
+        let etag = run(StatusCode::OK, None).headers()["ETag"]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let res = run(StatusCode::OK, Some(&etag));
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()["ETag"], *etag);
+        assert!(body(res).is_empty());
+
+        let res = run(StatusCode::OK, Some("\"stale\""));
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res), "[1,2,3]");
+    }
+
+    #[test]
+    fn errors_are_left_alone() {
+        let res = run(StatusCode::INTERNAL_SERVER_ERROR, Some("*"));
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.headers().get("ETag").is_none());
+        assert!(res.headers().get("Cache-Control").is_none());
+        assert_eq!(body(res), "[1,2,3]");
+    }
+}
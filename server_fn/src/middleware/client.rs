@@ -0,0 +1,305 @@
+use crate::{client::Client, error::ServerFnError, ServerFn};
+use once_cell::sync::Lazy;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::{ready, Future},
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+/// The future returned by a [`ClientMiddleware`].
+pub type ClientFuture<T> =
+    Pin<Box<dyn Future<Output = Result<T, ServerFnError>> + Send>>;
+
+/// A list of client middleware that can be applied to a server function.
+pub type ClientMiddlewareSet<Req, Res> =
+    Vec<Arc<dyn ClientMiddleware<Req, Res>>>;
+
+/// Middleware that intercepts the requests sent by a server function [`Client`], and the
+/// responses it receives.
+///
+/// Client middleware can be added for every server function that uses a particular client
+/// with [`add_client_middleware`], or for a single server function with the
+/// `#[client_middleware]` attribute on a `#[server]` function. Global middleware runs before
+/// per-function middleware on the request, and after it on the response.
+///
+/// In most cases, it is easiest to create middleware from an async function with
+/// [`MapRequest`] or [`MapResponse`].
+///
+/// Errors returned by client middleware are [`ServerFnError`]s without a custom error type. If
+/// the server function has a custom error type, any
+/// [`WrappedServerError`](ServerFnError::WrappedServerError) is converted into a
+/// [`ServerFnError::ServerError`].
+pub trait ClientMiddleware<Req, Res>: Send + Sync + 'static {
+    /// Intercepts a request before it is sent.
+    fn on_request(&self, req: Req) -> ClientFuture<Req>
+    where
+        Req: Send + 'static,
+    {
+        Box::pin(ready(Ok(req)))
+    }
+
+    /// Intercepts the result of sending a request, including any error.
+    fn on_response(&self, res: Result<Res, ServerFnError>) -> ClientFuture<Res>
+    where
+        Res: Send + 'static,
+    {
+        Box::pin(ready(res))
+    }
+}
+
+/// Client middleware that runs an async function on each request before it is sent.
+///
+/// ```rust,ignore
+/// add_client_middleware(MapRequest(|req: BrowserRequest| async move {
+///     req.headers().append("X-Request-Id", &new_request_id());
+///     Ok(req)
+/// }));
+/// ```
+///
+/// In the browser, the future returned by the function must be `Send`; futures that hold
+/// JavaScript values across an `.await` can be wrapped in a `SendWrapper`.
+pub struct MapRequest<F>(pub F);
+
+impl<F, Fut, Req, Res> ClientMiddleware<Req, Res> for MapRequest<F>
+where
+    F: Fn(Req) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Req, ServerFnError>> + Send + 'static,
+{
+    fn on_request(&self, req: Req) -> ClientFuture<Req> {
+        Box::pin((self.0)(req))
+    }
+}
+
+/// Client middleware that runs an async function on each successful response.
+///
+/// Errors are passed through without calling the function.
+pub struct MapResponse<F>(pub F);
+
+impl<F, Fut, Req, Res> ClientMiddleware<Req, Res> for MapResponse<F>
+where
+    F: Fn(Res) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Res, ServerFnError>> + Send + 'static,
+{
+    fn on_response(&self, res: Result<Res, ServerFnError>) -> ClientFuture<Res>
+    where
+        Res: Send + 'static,
+    {
+        match res {
+            Ok(res) => Box::pin((self.0)(res)),
+            Err(e) => Box::pin(ready(Err(e))),
+        }
+    }
+}
+
+static GLOBAL_CLIENT_MIDDLEWARE: Lazy<
+    RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+> = Lazy::new(Default::default);
+
+/// Adds middleware that will be applied to every server function called with a client that
+/// uses these request and response types.
+///
+/// For example, middleware for `BrowserRequest` and `BrowserResponse` applies to every server
+/// function that uses the default [`BrowserClient`](crate::client::browser::BrowserClient).
+pub fn add_client_middleware<Req, Res>(
+    middleware: impl ClientMiddleware<Req, Res>,
+) where
+    Req: 'static,
+    Res: 'static,
+{
+    let mut global = GLOBAL_CLIENT_MIDDLEWARE
+        .write()
+        .unwrap_or_else(|e| e.into_inner());
+    global
+        .entry(TypeId::of::<(Req, Res)>())
+        .or_insert_with(|| Box::new(ClientMiddlewareSet::<Req, Res>::new()))
+        .downcast_mut::<ClientMiddlewareSet<Req, Res>>()
+        .expect("client middleware is stored by its request and response type")
+        .push(Arc::new(middleware));
+}
+
+/// Returns the global client middleware for these request and response types.
+fn global_client_middleware<Req, Res>() -> ClientMiddlewareSet<Req, Res>
+where
+    Req: 'static,
+    Res: 'static,
+{
+    GLOBAL_CLIENT_MIDDLEWARE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&TypeId::of::<(Req, Res)>())
+        .and_then(|set| set.downcast_ref::<ClientMiddlewareSet<Req, Res>>())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Sends a request for the server function `S`, applying any global and per-function
+/// client middleware.
+pub(crate) async fn send<S>(
+    req: <S::Client as Client<S::Error>>::Request,
+) -> Result<<S::Client as Client<S::Error>>::Response, ServerFnError<S::Error>>
+where
+    S: ServerFn,
+{
+    let mut middleware = global_client_middleware();
+    middleware.extend(S::client_middlewares());
+    if middleware.is_empty() {
+        return S::Client::send(req).await;
+    }
+
+    let from_custom = |e: ServerFnError<S::Error>| {
+        e.map_custom_error(|e| ServerFnError::ServerError(e.to_string()))
+    };
+    let into_custom = |e: ServerFnError| {
+        e.map_custom_error(|e| ServerFnError::ServerError(e.to_string()))
+    };
+
+    let mut req = req;
+    for middleware in &middleware {
+        req = middleware.on_request(req).await.map_err(into_custom)?;
+    }
+    let mut res = S::Client::send(req).await.map_err(from_custom);
+    for middleware in middleware.iter().rev() {
+        res = middleware.on_response(res).await;
+    }
+    res.map_err(into_custom)
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::{codec::Json, error::NoCustomError, response::generic::Body};
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http::{HeaderValue, Request, Response};
+    use serde::{Deserialize, Serialize};
+
+    const TRACE: &str = "x-trace";
+
+    fn trace_request(req: &mut Request<Bytes>, step: &'static str) {
+        req.headers_mut()
+            .append(TRACE, HeaderValue::from_static(step));
+    }
+
+    fn trace_response(res: &mut Response<Bytes>, step: &'static str) {
+        res.headers_mut()
+            .append(TRACE, HeaderValue::from_static(step));
+    }
+
+    fn trace(headers: &http::HeaderMap) -> Vec<&str> {
+        headers
+            .get_all(TRACE)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    /// Responds with the steps traced on the request, followed by its own step.
+    struct EchoClient;
+
+    impl Client<NoCustomError> for EchoClient {
+        type Request = Request<Bytes>;
+        type Response = Response<Bytes>;
+
+        async fn send(
+            req: Request<Bytes>,
+        ) -> Result<Response<Bytes>, ServerFnError> {
+            let mut res = Response::new(Bytes::new());
+            for step in req.headers().get_all(TRACE) {
+                res.headers_mut().append(TRACE, step.clone());
+            }
+            trace_response(&mut res, "send");
+            Ok(res)
+        }
+
+        async fn sleep(_duration: std::time::Duration) {}
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Traced;
+
+    impl ServerFn for Traced {
+        const PATH: &'static str = "/api/traced";
+        type Client = EchoClient;
+        type ServerRequest = Request<Bytes>;
+        type ServerResponse = Response<Body>;
+        type Output = ();
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        fn client_middlewares(
+        ) -> ClientMiddlewareSet<Request<Bytes>, Response<Bytes>> {
+            vec![
+                Arc::new(MapRequest(|mut req| async move {
+                    trace_request(&mut req, "fn request");
+                    Ok(req)
+                })),
+                Arc::new(MapResponse(|mut res| async move {
+                    trace_response(&mut res, "fn response");
+                    Ok(res)
+                })),
+            ]
+        }
+
+        async fn run_body(self) -> Result<(), ServerFnError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn global_middleware_wraps_per_function_middleware() {
+        // the global middleware only traces this test's server function, as it applies to
+        // every client that uses the generic backend
+        add_client_middleware::<_, Response<Bytes>>(MapRequest(
+            |mut req: Request<Bytes>| async move {
+                if req.uri().path() == Traced::PATH {
+                    trace_request(&mut req, "global request");
+                }
+                Ok(req)
+            },
+        ));
+        add_client_middleware::<Request<Bytes>, _>(MapResponse(
+            |mut res: Response<Bytes>| async move {
+                if res.headers().contains_key(TRACE) {
+                    trace_response(&mut res, "global response");
+                }
+                Ok(res)
+            },
+        ));
+
+        let req = Request::post(Traced::PATH).body(Bytes::new()).unwrap();
+        let res = block_on(send::<Traced>(req)).unwrap();
+        assert_eq!(
+            trace(res.headers()),
+            [
+                "global request",
+                "fn request",
+                "send",
+                "fn response",
+                "global response"
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_skip_map_response() {
+        let middleware = MapResponse(|_: Response<Bytes>| async move {
+            panic!("only called on successful responses")
+        });
+        let res = block_on(ClientMiddleware::<(), _>::on_response(
+            &middleware,
+            Err(ServerFnError::Request("offline".into())),
+        ));
+        assert!(matches!(res, Err(ServerFnError::Request(_))));
+
+        // errors returned by middleware stop the request from being sent
+        let middleware = MapRequest(|_: ()| async move {
+            Err(ServerFnError::Request("rejected".into()))
+        });
+        let req =
+            block_on(ClientMiddleware::<_, ()>::on_request(&middleware, ()));
+        assert!(matches!(req, Err(ServerFnError::Request(_))));
+    }
+}
@@ -0,0 +1,126 @@
+use super::{BoxedService, Layer, Service};
+use crate::{
+    codec::{Compression, MIN_COMPRESSED_SIZE},
+    error::{NoCustomError, ServerFnError},
+    request::Req,
+    response::Res,
+};
+use bytes::Bytes;
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+/// Server middleware that compresses the responses of a server function.
+///
+/// Each successful response is compressed with the algorithm that the client prefers in its
+/// `Accept-Encoding` header, out of those enabled with the `gzip`, `brotli`, and `zstd`
+/// features, and is given matching `Content-Encoding` and `Vary` headers. Responses are sent
+/// as they are if the client does not accept any of these algorithms, if they are smaller than
+/// [`MIN_COMPRESSED_SIZE`], or if they already have a `Content-Encoding`.
+///
+/// This is added by the `#[server]` macro when the output encoding is
+/// [`Compressed`](crate::codec::Compressed), which also decompresses the response on the
+/// client. Browsers decompress responses themselves, so it can also be added to server functions
+/// with other encodings that are only called from the browser:
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(server_fn::middleware::compression::CompressionLayer)]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// As the body has to be compressed as a whole, the whole response is buffered before it is
+/// sent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressionLayer;
+
+impl<Request, Response> Layer<Request, Response> for CompressionLayer
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(CompressionService {
+            inner,
+            ty: PhantomData,
+        })
+    }
+}
+
+struct CompressionService<Request, Response> {
+    inner: BoxedService<Request, Response>,
+    ty: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for CompressionService<Request, Response>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let compression = Req::<NoCustomError>::header(&req, "Accept-Encoding")
+            .and_then(|accept_encoding| {
+                Compression::negotiate(&accept_encoding)
+            });
+        let path = Req::<NoCustomError>::path(&req)
+            .unwrap_or_default()
+            .to_string();
+        let res = self.inner.0.run(req);
+        Box::pin(async move {
+            let mut res = res.await;
+            let Some(compression) = compression else {
+                return res;
+            };
+            let already_encoded = Res::<NoCustomError>::headers(&res)
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("Content-Encoding"));
+            if already_encoded
+                || !(200..300).contains(&Res::<NoCustomError>::status(&res))
+            {
+                return res;
+            }
+
+            let body = match Res::<NoCustomError>::buffer_body(&mut res).await {
+                Ok(body) => body,
+                Err(e) => return Response::error_response(&path, &e),
+            };
+            if body.len() < MIN_COMPRESSED_SIZE {
+                return res;
+            }
+            let compressed = match compression.compress(&body) {
+                Ok(compressed) => compressed,
+                Err(e) => {
+                    let err =
+                        ServerFnError::<NoCustomError>::Response(e.to_string());
+                    return Response::error_response(&path, &err);
+                }
+            };
+            // if the body cannot be replaced, the response is sent uncompressed
+            if Res::<NoCustomError>::set_body(&mut res, Bytes::from(compressed))
+                .is_err()
+            {
+                return res;
+            }
+
+            let headers = [
+                ("Content-Encoding", compression.name()),
+                ("Vary", "Accept-Encoding"),
+            ];
+            for (name, value) in headers {
+                if let Err(e) =
+                    Res::<NoCustomError>::append_header(&mut res, name, value)
+                {
+                    return Response::error_response(&path, &e);
+                }
+            }
+            res
+        })
+    }
+}
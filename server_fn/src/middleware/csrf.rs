@@ -0,0 +1,359 @@
+use super::{BoxedService, Layer, Service};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    request::Req,
+    response::Res,
+};
+use bytes::Bytes;
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+/// The name of the cookie that holds the CSRF token.
+pub const CSRF_COOKIE: &str = "leptos_csrf";
+
+/// The name of the header in which the client sends the CSRF token back.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// The name of the form field in which a form sends the CSRF token back.
+pub const CSRF_FIELD: &str = "_csrf";
+
+/// Finds the CSRF token in the value of a `Cookie` header (or of `document.cookie`).
+pub fn token_from_cookies(cookies: &str) -> Option<&str> {
+    cookies.split(';').find_map(|cookie| {
+        let (name, value) = cookie.trim().split_once('=')?;
+        (name == CSRF_COOKIE && !value.is_empty()).then_some(value)
+    })
+}
+
+/// A request that can be checked by a [`CsrfLayer`].
+pub trait CsrfReq: Req<NoCustomError> + Sized + Send + 'static {
+    /// Whether this request uses a method that should not change any state, like `GET`.
+    fn is_safe_method(&self) -> bool;
+
+    /// Reads the whole body, and returns it along with a request that still has the same body.
+    fn read_body(
+        self,
+    ) -> impl Future<Output = Result<(Self, Bytes), ServerFnError>> + Send;
+}
+
+/// Server middleware that protects a server function against cross-site request forgery.
+///
+/// This uses the double-submit cookie pattern: when the page is rendered, a random token is
+/// stored in the [`CSRF_COOKIE`] cookie, which only pages on the same site can read. Each
+/// call must send the same token back, either in the [`CSRF_HEADER`] header (which the
+/// browser client adds automatically) or in the [`CSRF_FIELD`] field of a URL-encoded form
+/// (which `ActionForm` and `MultiActionForm` add automatically). Calls without a matching
+/// token are rejected with a `403 Forbidden` response.
+///
+/// Calls with safe methods like `GET` are not checked, as they should not change any state.
+///
+/// This is usually added with the `csrf` argument to the `#[server]` macro:
+///
+/// ```rust,ignore
+/// #[server(csrf = true)]
+/// pub async fn delete_account() -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsrfLayer;
+
+impl<Request, Response> Layer<Request, Response> for CsrfLayer
+where
+    Request: CsrfReq,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(CsrfService {
+            inner: Arc::new(Mutex::new(inner)),
+            ty: PhantomData,
+        })
+    }
+}
+
+struct CsrfService<Request, Response> {
+    // shared, so that it can be called after the body has been read
+    inner: Arc<Mutex<BoxedService<Request, Response>>>,
+    ty: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for CsrfService<Request, Response>
+where
+    Request: CsrfReq,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        if req.is_safe_method() {
+            return self.inner.lock().expect("lock poisoned").0.run(req);
+        }
+        let path = Req::path(&req).unwrap_or_default().to_string();
+
+        let Some(token) = req
+            .header("Cookie")
+            .and_then(|cookies| token_from_cookies(&cookies).map(Into::into))
+        else {
+            return Box::pin(
+                async move { forbidden(&path, "missing CSRF cookie") },
+            );
+        };
+        let token: String = token;
+        if req
+            .header(CSRF_HEADER)
+            .is_some_and(|header| tokens_match(&header, &token))
+        {
+            return self.inner.lock().expect("lock poisoned").0.run(req);
+        }
+
+        let is_form = req.to_content_type().is_some_and(|content_type| {
+            content_type.starts_with("application/x-www-form-urlencoded")
+        });
+        if !is_form {
+            return Box::pin(
+                async move { forbidden(&path, "missing CSRF token") },
+            );
+        }
+        let inner = Arc::clone(&self.inner);
+        Box::pin(async move {
+            let (req, body) = match req.read_body().await {
+                Ok(read) => read,
+                Err(e) => return forbidden(&path, &e.to_string()),
+            };
+            let valid =
+                url::form_urlencoded::parse(&body).any(|(name, value)| {
+                    name == CSRF_FIELD && tokens_match(&value, &token)
+                });
+            if valid {
+                let res = inner.lock().expect("lock poisoned").0.run(req);
+                res.await
+            } else {
+                forbidden(&path, "missing CSRF token")
+            }
+        })
+    }
+}
+
+/// Compares two tokens in constant time, so that their contents cannot be guessed by timing.
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn forbidden<Response>(path: &str, message: &str) -> Response
+where
+    Response: Res<NoCustomError>,
+{
+    let err = ServerFnError::<NoCustomError>::ServerError(format!(
+        "CSRF check failed: {message}"
+    ));
+    let mut res = Response::error_response(path, &err);
+    _ = Res::<NoCustomError>::set_status(&mut res, 403);
+    res
+}
+
+#[cfg(any(feature = "axum-no-default", feature = "actix"))]
+fn body_error(e: impl std::fmt::Display) -> ServerFnError {
+    ServerFnError::Deserialization(e.to_string())
+}
+
+#[cfg(feature = "generic")]
+mod generic {
+    use super::CsrfReq;
+    use crate::error::ServerFnError;
+    use bytes::Bytes;
+    use http::{Method, Request};
+
+    impl CsrfReq for Request<Bytes> {
+        fn is_safe_method(&self) -> bool {
+            matches!(
+                *self.method(),
+                Method::GET | Method::HEAD | Method::OPTIONS
+            )
+        }
+
+        async fn read_body(self) -> Result<(Self, Bytes), ServerFnError> {
+            let body = self.body().clone();
+            Ok((self, body))
+        }
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+mod axum {
+    use super::{body_error, CsrfReq};
+    use crate::error::ServerFnError;
+    use axum::body::Body;
+    use bytes::Bytes;
+    use http::{Method, Request};
+    use http_body_util::BodyExt;
+
+    impl CsrfReq for Request<Body> {
+        fn is_safe_method(&self) -> bool {
+            matches!(
+                *self.method(),
+                Method::GET | Method::HEAD | Method::OPTIONS
+            )
+        }
+
+        async fn read_body(self) -> Result<(Self, Bytes), ServerFnError> {
+            let (parts, body) = self.into_parts();
+            let body = body.collect().await.map_err(body_error)?.to_bytes();
+            Ok((Request::from_parts(parts, Body::from(body.clone())), body))
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::{body_error, CsrfReq};
+    use crate::{error::ServerFnError, request::actix::ActixRequest};
+    use actix_web::{
+        dev, error::PayloadError, http::Method, web::Payload, FromRequest,
+    };
+    use bytes::Bytes;
+    use futures::stream;
+    use send_wrapper::SendWrapper;
+    use std::future::Future;
+
+    impl CsrfReq for ActixRequest {
+        fn is_safe_method(&self) -> bool {
+            matches!(
+                *self.method(),
+                Method::GET | Method::HEAD | Method::OPTIONS
+            )
+        }
+
+        fn read_body(
+            self,
+        ) -> impl Future<Output = Result<(Self, Bytes), ServerFnError>> + Send
+        {
+            // Actix keeps the request on a single thread, so this will never panic
+            SendWrapper::new(async move {
+                let ActixRequest(req, call) = self;
+                let (req, payload) = req.take();
+                let body = payload.to_bytes().await.map_err(body_error)?;
+                let chunk = body.clone();
+                let mut payload: dev::Payload = dev::Payload::Stream {
+                    payload: Box::pin(stream::once(async move {
+                        Ok::<_, PayloadError>(chunk)
+                    })),
+                };
+                let payload = Payload::from_request(&req, &mut payload)
+                    .await
+                    .map_err(body_error)?;
+                Ok((ActixRequest(SendWrapper::new((req, payload)), call), body))
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::response::generic::Body;
+    use futures::executor::block_on;
+    use http::{Request, Response, StatusCode};
+
+    const PATH: &str = "/api/delete_account";
+
+    /// Responds with `200 OK`, and the body of the request.
+    struct Echo;
+
+    impl Service<Request<Bytes>, Response<Body>> for Echo {
+        fn run(
+            &mut self,
+            req: Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let res = Response::new(Body::Sync(req.into_body()));
+            Box::pin(async move { res })
+        }
+    }
+
+    fn status(req: http::request::Builder, body: &'static str) -> StatusCode {
+        let mut service = Layer::<Request<Bytes>, Response<Body>>::layer(
+            &CsrfLayer,
+            BoxedService::new(Echo),
+        );
+        let req = req.body(Bytes::from_static(body.as_bytes())).unwrap();
+        block_on(service.0.run(req)).status()
+    }
+
+    fn post() -> http::request::Builder {
+        Request::post(PATH)
+            .header("Cookie", format!("theme=dark; {CSRF_COOKIE}=secret"))
+    }
+
+    #[test]
+    fn finds_the_token_in_cookies() {
+        let cookies = format!("theme=dark; {CSRF_COOKIE}=secret; lang=en");
+        assert_eq!(token_from_cookies(&cookies), Some("secret"));
+        assert_eq!(token_from_cookies("theme=dark"), None);
+        assert_eq!(token_from_cookies(&format!("{CSRF_COOKIE}=")), None);
+        assert_eq!(token_from_cookies(&format!("x{CSRF_COOKIE}=secret")), None);
+    }
+
+    #[test]
+    fn tokens_match_exactly() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("", "secret"));
+    }
+
+    #[test]
+    fn safe_methods_are_not_checked() {
+        assert_eq!(status(Request::get(PATH), ""), StatusCode::OK);
+    }
+
+    #[test]
+    fn calls_need_a_cookie_and_a_matching_token() {
+        assert_eq!(
+            status(Request::post(PATH).header(CSRF_HEADER, "secret"), ""),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(post(), ""), StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(post().header(CSRF_HEADER, "guess"), ""),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(post().header(CSRF_HEADER, "secret"), ""),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn forms_can_send_the_token_in_a_field() {
+        let form = || {
+            post().header("Content-Type", "application/x-www-form-urlencoded")
+        };
+        assert_eq!(status(form(), "title=hi&_csrf=secret"), StatusCode::OK);
+        assert_eq!(
+            status(form(), "title=hi&_csrf=guess"),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(form(), "title=hi"), StatusCode::FORBIDDEN);
+
+        // only URL-encoded forms are read
+        assert_eq!(
+            status(
+                post().header("Content-Type", "application/json"),
+                "{\"_csrf\":\"secret\"}"
+            ),
+            StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use super::{BoxedService, Layer, Service};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    request::Req,
+    response::Res,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// The number of buckets a [`RateLimitLayer`] keeps before it forgets the ones that are full,
+/// and then the ones that were used least recently.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Extracts the key that a [`RateLimitLayer`] uses to decide which bucket a request draws from.
+///
+/// This is implemented for any `Fn(&Request) -> Option<String>`, so that requests can be keyed
+/// by anything the request type exposes, like the client’s IP address or a session cookie.
+/// Requests for which the key is `None` all draw from one shared bucket.
+pub trait RateLimitKey<Request>: Send + Sync + 'static {
+    /// Returns the key for this request.
+    fn key(&self, req: &Request) -> Option<String>;
+}
+
+impl<Request, F> RateLimitKey<Request> for F
+where
+    F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+{
+    fn key(&self, req: &Request) -> Option<String> {
+        self(req)
+    }
+}
+
+/// Limits all requests together, with a single bucket.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+impl<Request> RateLimitKey<Request> for Global {
+    fn key(&self, _req: &Request) -> Option<String> {
+        Some(String::new())
+    }
+}
+
+/// Keys requests by the value of a header, like `X-Forwarded-For` or `Authorization`.
+///
+/// Requests without this header all draw from one shared bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Header(pub &'static str);
+
+impl<Request> RateLimitKey<Request> for Header
+where
+    Request: Req<NoCustomError>,
+{
+    fn key(&self, req: &Request) -> Option<String> {
+        Req::<NoCustomError>::header(req, self.0)
+            .map(|value| value.into_owned())
+    }
+}
+
+/// Server middleware that limits how often a server function can be called, using a token
+/// bucket for each key.
+///
+/// Each bucket holds up to `requests` tokens and is refilled at a rate of `requests` per `per`.
+/// A request takes one token from its bucket; if the bucket is empty, it is rejected with a
+/// `429 Too Many Requests` response and a `Retry-After` header.
+///
+/// This is usually added with the `rate_limit` argument to the `#[server]` macro:
+///
+/// ```rust,ignore
+/// #[server(rate_limit = "10/minute", rate_limit_key = Header("X-Forwarded-For"))]
+/// pub async fn send_message(text: String) -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// The buckets are shared by every clone of the layer.
+pub struct RateLimitLayer<K = Global> {
+    key: Arc<K>,
+    capacity: f64,
+    rate: f64,
+    buckets: Arc<Mutex<HashMap<Option<String>, Bucket>>>,
+}
+
+impl<K> Clone for RateLimitLayer<K> {
+    fn clone(&self) -> Self {
+        Self {
+            key: Arc::clone(&self.key),
+            capacity: self.capacity,
+            rate: self.rate,
+            buckets: Arc::clone(&self.buckets),
+        }
+    }
+}
+
+impl RateLimitLayer {
+    /// Creates a layer that allows `requests` requests every `per`, across all clients.
+    ///
+    /// # Panics
+    /// Panics if `requests` or `per` is zero.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        assert!(
+            requests > 0 && !per.is_zero(),
+            "a rate limit must allow at least one request per nonzero period"
+        );
+        Self {
+            key: Arc::new(Global),
+            capacity: requests.into(),
+            rate: f64::from(requests) / per.as_secs_f64(),
+            buckets: Default::default(),
+        }
+    }
+}
+
+impl<K> RateLimitLayer<K> {
+    /// Limits each key separately, instead of all requests together.
+    pub fn key_by<K2>(self, key: K2) -> RateLimitLayer<K2> {
+        RateLimitLayer {
+            key: Arc::new(key),
+            capacity: self.capacity,
+            rate: self.rate,
+            buckets: Default::default(),
+        }
+    }
+
+    /// Takes a token from the bucket for `key`, or returns how long to wait until one is available.
+    fn acquire(&self, key: Option<String>) -> Result<(), Duration> {
+        self.acquire_at(key, Instant::now())
+    }
+
+    fn acquire_at(
+        &self,
+        key: Option<String>,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().expect("lock poisoned");
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&key) {
+            // a full bucket is the same as a new one, so these can be dropped for free
+            buckets.retain(|_, bucket| {
+                bucket.tokens_at(now, self.capacity, self.rate) < self.capacity
+            });
+            if buckets.len() >= MAX_BUCKETS {
+                let oldest = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.updated)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        let tokens = bucket.refill(now, self.capacity, self.rate);
+        if tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - tokens) / self.rate))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Returns the number of tokens in the bucket at `now`, without updating it.
+    fn tokens_at(&self, now: Instant, capacity: f64, rate: f64) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated);
+        (self.tokens + elapsed.as_secs_f64() * rate).min(capacity)
+    }
+
+    /// Adds the tokens earned since the last update, and returns the new number of tokens.
+    fn refill(&mut self, now: Instant, capacity: f64, rate: f64) -> f64 {
+        self.tokens = self.tokens_at(now, capacity, rate);
+        self.updated = now;
+        self.tokens
+    }
+}
+
+impl<Request, Response, K> Layer<Request, Response> for RateLimitLayer<K>
+where
+    Request: Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+    K: RateLimitKey<Request>,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(RateLimitService {
+            inner,
+            limit: self.clone(),
+            ty: PhantomData,
+        })
+    }
+}
+
+struct RateLimitService<Request, Response, K> {
+    inner: BoxedService<Request, Response>,
+    limit: RateLimitLayer<K>,
+    ty: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request, Response, K> Service<Request, Response>
+    for RateLimitService<Request, Response, K>
+where
+    Request: Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+    K: RateLimitKey<Request>,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        match self.limit.acquire(self.limit.key.key(&req)) {
+            Ok(()) => self.inner.0.run(req),
+            Err(retry_after) => Box::pin(async move {
+                let mut res = too_many_requests("rate limit exceeded");
+                // round up, so that a retry after this many seconds succeeds
+                let secs = retry_after.as_secs()
+                    + u64::from(retry_after.subsec_nanos() > 0);
+                _ = Res::<NoCustomError>::append_header(
+                    &mut res,
+                    "Retry-After",
+                    &secs.to_string(),
+                );
+                res
+            }),
+        }
+    }
+}
+
+/// Server middleware that limits how many calls to a server function can run at the same time.
+///
+/// Calls that arrive while `max` calls are already running are rejected with a
+/// `429 Too Many Requests` response, rather than queued.
+///
+/// This is usually added with the `concurrency_limit` argument to the `#[server]` macro:
+///
+/// ```rust,ignore
+/// #[server(concurrency_limit = 4)]
+/// pub async fn render_report(id: u32) -> Result<Vec<u8>, ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// The count of running calls is shared by every clone of the layer.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitLayer {
+    max: usize,
+    running: Arc<AtomicUsize>,
+}
+
+impl ConcurrencyLimitLayer {
+    /// Creates a layer that allows at most `max` calls to run at the same time.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            running: Default::default(),
+        }
+    }
+
+    fn acquire(&self) -> Option<RunningGuard> {
+        self.running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < self.max).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| RunningGuard(Arc::clone(&self.running)))
+    }
+}
+
+/// Marks a call as running until it is dropped.
+struct RunningGuard(Arc<AtomicUsize>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<Request, Response> Layer<Request, Response> for ConcurrencyLimitLayer
+where
+    Request: Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(ConcurrencyLimitService {
+            inner,
+            limit: self.clone(),
+            ty: PhantomData,
+        })
+    }
+}
+
+struct ConcurrencyLimitService<Request, Response> {
+    inner: BoxedService<Request, Response>,
+    limit: ConcurrencyLimitLayer,
+    ty: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for ConcurrencyLimitService<Request, Response>
+where
+    Request: Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let Some(guard) = self.limit.acquire() else {
+            return Box::pin(async move {
+                too_many_requests("concurrency limit exceeded")
+            });
+        };
+        let res = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = res.await;
+            drop(guard);
+            res
+        })
+    }
+}
+
+fn too_many_requests<Response>(message: &str) -> Response
+where
+    Response: Res<NoCustomError>,
+{
+    let err = ServerFnError::<NoCustomError>::ServerError(message.into());
+    let mut res = Response::error_response("", &err);
+    _ = Res::<NoCustomError>::set_status(&mut res, 429);
+    res
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use crate::response::generic::Body;
+    use bytes::Bytes;
+    use futures::{channel::oneshot, executor::block_on};
+    use http::{Request, Response, StatusCode};
+
+    /// Responds once the receiver it was given resolves, or right away if it has none.
+    struct Waits(Option<oneshot::Receiver<()>>);
+
+    impl Service<Request<Bytes>, Response<Body>> for Waits {
+        fn run(
+            &mut self,
+            _req: Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            let wait = self.0.take();
+            Box::pin(async move {
+                if let Some(wait) = wait {
+                    _ = wait.await;
+                }
+                Response::new(Body::Sync(Bytes::new()))
+            })
+        }
+    }
+
+    fn request(header: Option<&str>) -> Request<Bytes> {
+        let mut req = Request::builder().uri("/api/send_message");
+        if let Some(value) = header {
+            req = req.header("X-Forwarded-For", value);
+        }
+        req.body(Bytes::new()).unwrap()
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limit = RateLimitLayer::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert_eq!(limit.acquire_at(None, start), Ok(()));
+        assert_eq!(limit.acquire_at(None, start), Ok(()));
+        assert_eq!(limit.acquire_at(None, start), Err(Duration::from_secs(30)));
+
+        let later = start + Duration::from_secs(30);
+        assert_eq!(limit.acquire_at(None, later), Ok(()));
+        assert!(limit.acquire_at(None, later).is_err());
+    }
+
+    #[test]
+    fn requests_are_limited_by_key() {
+        let limit = RateLimitLayer::new(1, Duration::from_secs(60))
+            .key_by(Header("X-Forwarded-For"));
+        let mut service = Layer::<Request<Bytes>, Response<Body>>::layer(
+            &limit,
+            BoxedService::new(Waits(None)),
+        );
+        let mut status =
+            |header| block_on(service.0.run(request(header))).status();
+
+        assert_eq!(status(Some("10.0.0.1")), StatusCode::OK);
+        assert_eq!(status(Some("10.0.0.2")), StatusCode::OK);
+        assert_eq!(status(Some("10.0.0.1")), StatusCode::TOO_MANY_REQUESTS);
+
+        // requests without a key share a bucket, rather than going unlimited
+        assert_eq!(status(None), StatusCode::OK);
+        assert_eq!(status(None), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn rejections_say_when_to_retry() {
+        let limit = RateLimitLayer::new(1, Duration::from_secs(10));
+        let mut service = Layer::<Request<Bytes>, Response<Body>>::layer(
+            &limit,
+            BoxedService::new(Waits(None)),
+        );
+        block_on(service.0.run(request(None)));
+        let res = block_on(service.0.run(request(None)));
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = res.headers()["Retry-After"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=10).contains(&retry_after));
+    }
+
+    #[test]
+    fn least_recently_used_buckets_are_evicted() {
+        let limit = RateLimitLayer::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        for i in 0..MAX_BUCKETS {
+            let now = start + Duration::from_millis(i as u64);
+            assert_eq!(limit.acquire_at(Some(i.to_string()), now), Ok(()));
+        }
+
+        let now = start + Duration::from_millis(MAX_BUCKETS as u64);
+        assert_eq!(limit.acquire_at(Some("new".into()), now), Ok(()));
+        let buckets = limit.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_BUCKETS);
+        assert!(!buckets.contains_key(&Some("0".into())));
+        assert!(buckets.contains_key(&Some("1".into())));
+    }
+
+    #[test]
+    fn running_calls_are_limited() {
+        let limit = ConcurrencyLimitLayer::new(1);
+        let (done, wait) = oneshot::channel();
+        let mut slow = Layer::<Request<Bytes>, Response<Body>>::layer(
+            &limit,
+            BoxedService::new(Waits(Some(wait))),
+        );
+        let mut fast = Layer::<Request<Bytes>, Response<Body>>::layer(
+            &limit,
+            BoxedService::new(Waits(None)),
+        );
+
+        let running = slow.0.run(request(None));
+        let res = block_on(fast.0.run(request(None)));
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        done.send(()).unwrap();
+        assert_eq!(block_on(running).status(), StatusCode::OK);
+        let res = block_on(fast.0.run(request(None)));
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
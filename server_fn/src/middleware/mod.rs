@@ -1,3 +1,15 @@
+/// Middleware that makes server function responses cacheable.
+pub mod cache;
+/// Middleware for server function calls on the client.
+pub mod client;
+/// Middleware that compresses server function responses.
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+pub mod compression;
+/// Middleware that protects server functions against cross-site request forgery.
+pub mod csrf;
+/// Middleware that limits how often and how concurrently server functions are called.
+pub mod limit;
+
 use std::{future::Future, pin::Pin};
 
 /// An abstraction over a middleware layer, which can be used to add additional
@@ -5,6 +17,13 @@ use std::{future::Future, pin::Pin};
 pub trait Layer<Req, Res>: Send + Sync + 'static {
     /// Adds this layer to the inner service.
     fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res>;
+
+    /// A name for this layer, used to describe the middleware of a server function.
+    ///
+    /// By default, this is the name of its type.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 /// A type-erased service, which takes an HTTP request and returns a response.
@@ -147,7 +166,7 @@ mod actix {
             &mut self,
             req: ActixRequest,
         ) -> Pin<Box<dyn Future<Output = ActixResponse> + Send>> {
-            let path = req.0 .0.uri().path().to_string();
+            let path = req.uri().path().to_string();
             let inner = self.call(req.0.take().0);
             Box::pin(async move {
                 ActixResponse::from(inner.await.unwrap_or_else(|e| {
@@ -0,0 +1,353 @@
+use http::Method;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+/// A description of a server function, registered by the `#[server]` macro when the `openapi`
+/// feature is enabled.
+///
+/// Every server function whose arguments are encoded with `serde` describes its arguments with
+/// the [`JsonSchema`] of its argument struct, and every server function that does not stream its
+/// response describes its output with the [`JsonSchema`] of its output type. Both must therefore
+/// implement [`JsonSchema`].
+#[derive(Debug, Clone)]
+pub struct ServerFnSchema {
+    name: &'static str,
+    path: &'static str,
+    method: Method,
+    input_content_type: &'static str,
+    output_content_type: &'static str,
+    description: &'static str,
+    input: Option<fn(&mut SchemaGenerator) -> Schema>,
+    output: Option<fn(&mut SchemaGenerator) -> Schema>,
+}
+
+impl ServerFnSchema {
+    /// Describes a server function without any argument or output schemas.
+    pub const fn new(
+        name: &'static str,
+        path: &'static str,
+        method: Method,
+        input_content_type: &'static str,
+        output_content_type: &'static str,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            method,
+            input_content_type,
+            output_content_type,
+            description,
+            input: None,
+            output: None,
+        }
+    }
+
+    /// Describes the arguments of the server function with the schema of `T`.
+    pub const fn with_input<T: JsonSchema>(mut self) -> Self {
+        self.input = Some(T::json_schema);
+        self
+    }
+
+    /// Describes the output of the server function with the schema of `T`.
+    pub const fn with_output<T: JsonSchema>(mut self) -> Self {
+        self.output = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// The name of the server function, used as the operation ID.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The path of the server function.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The HTTP method the server function expects.
+    pub fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn operation(&self, gen: &mut SchemaGenerator) -> Value {
+        let mut operation = Map::new();
+        operation.insert("operationId".into(), self.name.into());
+        if !self.description.is_empty() {
+            operation.insert("description".into(), self.description.into());
+        }
+
+        let input = self.input.map(|schema| schema(gen));
+        if self.method == Method::GET {
+            // GET requests send their arguments in the query string
+            let parameters =
+                input.as_ref().map(query_parameters).unwrap_or_default();
+            if !parameters.is_empty() {
+                operation.insert("parameters".into(), parameters.into());
+            }
+        } else {
+            operation.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": content(self.input_content_type, input),
+                }),
+            );
+        }
+
+        let output = self.output.map(|schema| schema(gen));
+        operation.insert(
+            "responses".into(),
+            json!({
+                "200": {
+                    "description": "The output of the server function.",
+                    "content": content(self.output_content_type, output),
+                },
+                "default": {
+                    "description": "An error returned by the server function.",
+                    "content": {
+                        "text/plain": {
+                            "schema": { "type": "string" }
+                        }
+                    }
+                }
+            }),
+        );
+        Value::Object(operation)
+    }
+}
+
+fn content(content_type: &str, schema: Option<Schema>) -> Value {
+    let media_type = match schema {
+        Some(schema) => json!({ "schema": schema }),
+        None => json!({}),
+    };
+    json!({ content_type: media_type })
+}
+
+/// Describes each field of an argument struct as a query parameter.
+fn query_parameters(schema: &Schema) -> Vec<Value> {
+    let Schema::Object(schema) = schema else {
+        return Vec::new();
+    };
+    let Some(object) = schema.object.as_ref() else {
+        return Vec::new();
+    };
+    object
+        .properties
+        .iter()
+        .map(|(name, schema)| {
+            json!({
+                "name": name,
+                "in": "query",
+                "required": object.required.contains(name),
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+inventory::collect!(ServerFnSchema);
+
+/// Builds an [OpenAPI 3.1](https://spec.openapis.org/oas/v3.1.0) document that describes every
+/// registered server function.
+///
+/// ```rust,ignore
+/// let document = OpenApi::new("My App", "1.0.0")
+///     .server("https://example.com")
+///     .to_json();
+/// ```
+///
+/// Only server functions compiled with the `openapi` feature of `server_fn` (or of `leptos`)
+/// are included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    servers: Vec<String>,
+}
+
+impl OpenApi {
+    /// Creates a document with the given API title and version.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            servers: Vec::new(),
+        }
+    }
+
+    /// Sets the description of the API.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds the URL of a server that hosts the API. Server function paths are relative to it.
+    pub fn server(mut self, url: impl Into<String>) -> Self {
+        self.servers.push(url.into());
+        self
+    }
+
+    /// Generates the document.
+    pub fn document(&self) -> Value {
+        let mut settings = SchemaSettings::draft2019_09();
+        settings.definitions_path = "#/components/schemas/".into();
+        settings.meta_schema = None;
+        let mut gen = SchemaGenerator::new(settings);
+
+        let mut paths = Map::new();
+        for server_fn in inventory::iter::<ServerFnSchema> {
+            let path = paths
+                .entry(server_fn.path)
+                .or_insert_with(|| Value::Object(Map::new()));
+            path[server_fn.method.as_str().to_lowercase()] =
+                server_fn.operation(&mut gen);
+        }
+
+        let mut info = json!({
+            "title": self.title,
+            "version": self.version,
+        });
+        if let Some(description) = &self.description {
+            info["description"] = description.as_str().into();
+        }
+
+        let mut document = json!({
+            "openapi": "3.1.0",
+            "info": info,
+            "paths": paths,
+            "components": {
+                "schemas": gen.take_definitions(),
+            },
+        });
+        if !self.servers.is_empty() {
+            document["servers"] = self
+                .servers
+                .iter()
+                .map(|url| json!({ "url": url }))
+                .collect();
+        }
+        document
+    }
+
+    /// Generates the document, serialized as JSON.
+    pub fn to_json(&self) -> String {
+        self.document().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpenApi, ServerFnSchema};
+    use http::Method;
+    use schemars::JsonSchema;
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct ListPosts {
+        page: u32,
+        tag: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct AddPost {
+        title: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Post {
+        id: u64,
+        title: String,
+    }
+
+    inventory::submit! {
+        ServerFnSchema::new(
+            "ListPosts",
+            "/api/list_posts",
+            Method::GET,
+            "application/x-www-form-urlencoded",
+            "application/json",
+            "Lists the posts.",
+        )
+        .with_input::<ListPosts>()
+        .with_output::<Vec<Post>>()
+    }
+
+    inventory::submit! {
+        ServerFnSchema::new(
+            "AddPost",
+            "/api/add_post",
+            Method::POST,
+            "application/json",
+            "application/json",
+            "",
+        )
+        .with_input::<AddPost>()
+        .with_output::<Post>()
+    }
+
+    #[test]
+    fn describes_registered_server_functions() {
+        let document = OpenApi::new("Blog", "1.0.0")
+            .description("A blog.")
+            .server("https://example.com")
+            .document();
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(
+            document["info"],
+            json!({ "title": "Blog", "version": "1.0.0", "description": "A blog." })
+        );
+        assert_eq!(
+            document["servers"],
+            json!([{ "url": "https://example.com" }])
+        );
+
+        // the arguments of GET server functions are query parameters
+        let list = &document["paths"]["/api/list_posts"]["get"];
+        assert_eq!(list["operationId"], "ListPosts");
+        assert_eq!(list["description"], "Lists the posts.");
+        assert!(list.get("requestBody").is_none());
+        let parameters = list["parameters"].as_array().unwrap();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0]["name"], "page");
+        assert_eq!(parameters[0]["in"], "query");
+        assert_eq!(parameters[0]["required"], true);
+        assert_eq!(parameters[1]["name"], "tag");
+        assert_eq!(parameters[1]["required"], false);
+        assert_eq!(
+            list["responses"]["200"]["content"]["application/json"]["schema"]
+                ["items"]["$ref"],
+            "#/components/schemas/Post"
+        );
+
+        // the arguments of other server functions are the request body
+        let add = &document["paths"]["/api/add_post"]["post"];
+        assert!(add.get("description").is_none());
+        let body = &add["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(body["required"], json!(["title"]));
+        assert_eq!(
+            add["responses"]["200"]["content"]["application/json"]["schema"]
+                ["$ref"],
+            "#/components/schemas/Post"
+        );
+        assert_eq!(
+            add["responses"]["default"]["content"]["text/plain"]["schema"]
+                ["type"],
+            "string"
+        );
+
+        // output types are shared between server functions as components
+        let post = &document["components"]["schemas"]["Post"];
+        assert_eq!(post["required"], json!(["id", "title"]));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::ServerFnTraitObj;
+use http::Method;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Iterates over every server function that was registered automatically for this pair of
+/// request and response types, including those that share a path with another one.
+///
+/// Server functions registered with `register_explicit` are not included. Each server
+/// integration also has a `server_fns` function that lists the server functions it will
+/// actually serve.
+pub fn registered<Req, Res>(
+) -> impl Iterator<Item = &'static ServerFnTraitObj<Req, Res>>
+where
+    Req: 'static,
+    Res: 'static,
+{
+    inventory::iter::<ServerFnTraitObj<Req, Res>>.into_iter()
+}
+
+/// A path and method at which more than one server function is registered.
+///
+/// Only one of these server functions can be called: the others are silently shadowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePath {
+    /// The path that is registered more than once.
+    pub path: &'static str,
+    /// The HTTP method that is registered more than once at this path.
+    pub method: Method,
+    /// How many server functions are registered at this path and method.
+    pub count: usize,
+}
+
+impl fmt::Display for DuplicatePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} server functions are registered at {} {}",
+            self.count, self.method, self.path
+        )
+    }
+}
+
+impl std::error::Error for DuplicatePath {}
+
+/// Returns each path and method at which more than one of these server functions is
+/// registered, sorted by path.
+///
+/// This can be used to check for collisions at startup, which can happen when two server
+/// functions are given the same `endpoint`:
+///
+/// ```rust,ignore
+/// use server_fn::registry;
+///
+/// let duplicates = registry::duplicate_paths(registry::registered::<
+///     Request<Body>,
+///     Response<Body>,
+/// >());
+/// assert!(duplicates.is_empty(), "{duplicates:?}");
+/// ```
+pub fn duplicate_paths<'a, Req, Res>(
+    server_fns: impl IntoIterator<Item = &'a ServerFnTraitObj<Req, Res>>,
+) -> Vec<DuplicatePath>
+where
+    Req: 'a,
+    Res: 'a,
+{
+    let mut counts = HashMap::<_, usize>::new();
+    for server_fn in server_fns {
+        *counts
+            .entry((server_fn.path(), server_fn.method()))
+            .or_default() += 1;
+    }
+    let mut duplicates = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|((path, method), count)| DuplicatePath {
+            path,
+            method,
+            count,
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort_by(|a, b| {
+        (a.path, a.method.as_str()).cmp(&(b.path, b.method.as_str()))
+    });
+    duplicates
+}
+
+/// Groups the versions of each server function, with the versions in each group sorted from
+/// oldest to newest.
+///
+/// Server functions are grouped by their path without its version segment, so the versions of
+/// a server function are grouped together when they share an `endpoint`, regardless of the
+/// modules they are defined in. Server functions with different names in different modules
+/// are never grouped together by mistake.
+///
+/// The different versions of a server function can be served side by side, as each version is
+/// part of the path, so that clients that were built against an older version keep working
+/// during a deployment. A common way to do this is to keep the older version in a module of
+/// its own:
+///
+/// ```rust,ignore
+/// mod v1 {
+///     #[server(version = 1, endpoint = "add_todo")]
+///     pub async fn add_todo(title: String) -> Result<(), ServerFnError> {
+///         super::add_todo(title, false).await
+///     }
+/// }
+///
+/// #[server(version = 2, endpoint = "add_todo")]
+/// pub async fn add_todo(title: String, done: bool) -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// Here, `versions` groups both of them under `/api/add_todo`.
+pub fn versions<'a, Req, Res>(
+    server_fns: impl IntoIterator<Item = &'a ServerFnTraitObj<Req, Res>>,
+) -> BTreeMap<String, Vec<&'a ServerFnTraitObj<Req, Res>>>
+where
+    Req: 'a,
+    Res: 'a,
+{
+    let mut versions = BTreeMap::<_, Vec<_>>::new();
+    for server_fn in server_fns {
+        versions
+            .entry(unversioned_path(server_fn))
+            .or_default()
+            .push(server_fn);
+    }
+    for server_fns in versions.values_mut() {
+        server_fns.sort_by_key(|server_fn| server_fn.version());
+    }
+    versions
+}
+
+/// The path of a server function, without the segment that holds its version.
+fn unversioned_path<Req, Res>(
+    server_fn: &ServerFnTraitObj<Req, Res>,
+) -> String {
+    let path = server_fn.path();
+    match server_fn.version() {
+        Some(version) => path.replacen(&format!("/v{version}/"), "/", 1),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_fn(
+        path: &'static str,
+        method: Method,
+    ) -> ServerFnTraitObj<(), ()> {
+        ServerFnTraitObj::new(path, method, |_| Box::pin(async {}), Vec::new)
+    }
+
+    fn versioned(
+        name: &'static str,
+        path: &'static str,
+        version: Option<u32>,
+    ) -> ServerFnTraitObj<(), ()> {
+        server_fn(path, Method::POST)
+            .with_name(name)
+            .with_version(version)
+    }
+
+    #[test]
+    fn finds_duplicate_paths() {
+        let server_fns = [
+            server_fn("/api/b", Method::POST),
+            server_fn("/api/a", Method::POST),
+            server_fn("/api/b", Method::POST),
+            server_fn("/api/a", Method::GET),
+            server_fn("/api/b", Method::POST),
+            server_fn("/api/a", Method::POST),
+            server_fn("/api/c", Method::POST),
+        ];
+        let duplicates = duplicate_paths(&server_fns);
+        assert_eq!(
+            duplicates,
+            [
+                DuplicatePath {
+                    path: "/api/a",
+                    method: Method::POST,
+                    count: 2,
+                },
+                DuplicatePath {
+                    path: "/api/b",
+                    method: Method::POST,
+                    count: 3,
+                },
+            ]
+        );
+        assert_eq!(
+            duplicates[1].to_string(),
+            "3 server functions are registered at POST /api/b"
+        );
+    }
+
+    #[test]
+    fn unique_paths_are_not_duplicates() {
+        let server_fns = [
+            server_fn("/api/a", Method::POST),
+            server_fn("/api/a", Method::GET),
+            server_fn("/api/b", Method::POST),
+        ];
+        assert!(duplicate_paths(&server_fns).is_empty());
+    }
+
+    #[test]
+    fn groups_versions_by_path() {
+        let server_fns = [
+            versioned("app::AddTodo", "/api/v2/add_todo", Some(2)),
+            versioned("app::v1::AddTodo", "/api/v1/add_todo", Some(1)),
+            versioned("app::AddTodo", "/api/add_todo", None),
+            // same type name, but a different server function
+            versioned("app::admin::AddTodo", "/api/v2/admin_add_todo", Some(2)),
+        ];
+        let versions = versions(&server_fns)
+            .into_iter()
+            .map(|(path, server_fns)| {
+                let names = server_fns
+                    .iter()
+                    .map(|server_fn| (server_fn.name(), server_fn.version()))
+                    .collect::<Vec<_>>();
+                (path, names)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            versions,
+            [
+                (
+                    "/api/add_todo".to_string(),
+                    vec![
+                        ("app::AddTodo", None),
+                        ("app::v1::AddTodo", Some(1)),
+                        ("app::AddTodo", Some(2)),
+                    ]
+                ),
+                (
+                    "/api/admin_add_todo".to_string(),
+                    vec![("app::admin::AddTodo", Some(2))]
+                ),
+            ]
+        );
+    }
+}
@@ -1,7 +1,15 @@
-use crate::{error::ServerFnError, request::Req};
-use actix_web::{web::Payload, HttpRequest};
+use crate::{
+    error::ServerFnError,
+    request::{Req, WebsocketReq},
+    response::actix::ActixResponse,
+};
+use actix_web::{
+    http::{header::HeaderMap, Method, Uri},
+    web::Payload,
+    HttpRequest,
+};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Sink, Stream};
 use send_wrapper::SendWrapper;
 use std::{borrow::Cow, future::Future};
 
@@ -10,18 +18,53 @@ use std::{borrow::Cow, future::Future};
 /// This uses a [`SendWrapper`] that allows the Actix `HttpRequest` type to be `Send`, but panics
 /// if it it is ever sent to another thread. Actix pins request handling to a single thread, so this
 /// is necessary to be compatible with traits that require `Send` but should never panic in actual use.
-pub struct ActixRequest(pub(crate) SendWrapper<(HttpRequest, Payload)>);
+pub struct ActixRequest(
+    pub(crate) SendWrapper<(HttpRequest, Payload)>,
+    pub(crate) Option<CallHead>,
+);
+
+/// The method, URI and headers of a call in a batch, which is run with the `HttpRequest` of the
+/// batch request, so that it has the same app data, extensions and connection info.
+pub(crate) struct CallHead {
+    pub(crate) method: Method,
+    pub(crate) uri: Uri,
+    pub(crate) headers: HeaderMap,
+}
 
 impl ActixRequest {
     /// Returns the raw Actix request, and its body.
+    ///
+    /// For a call in a batch, this is the request that the batch was sent with.
     pub fn take(self) -> (HttpRequest, Payload) {
         self.0.take()
     }
 
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        match &self.1 {
+            Some(head) => &head.method,
+            None => self.0 .0.method(),
+        }
+    }
+
+    /// The URI of the request.
+    pub fn uri(&self) -> &Uri {
+        match &self.1 {
+            Some(head) => &head.uri,
+            None => self.0 .0.uri(),
+        }
+    }
+
+    /// The headers of the request.
+    pub fn headers(&self) -> &HeaderMap {
+        match &self.1 {
+            Some(head) => &head.headers,
+            None => self.0 .0.headers(),
+        }
+    }
+
     fn header(&self, name: &str) -> Option<Cow<'_, str>> {
-        self.0
-             .0
-            .headers()
+        self.headers()
             .get(name)
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
@@ -29,7 +72,7 @@ impl ActixRequest {
 
 impl From<(HttpRequest, Payload)> for ActixRequest {
     fn from(value: (HttpRequest, Payload)) -> Self {
-        ActixRequest(SendWrapper::new(value))
+        ActixRequest(SendWrapper::new(value), None)
     }
 }
 
@@ -38,7 +81,11 @@ where
     CustErr: 'static,
 {
     fn as_query(&self) -> Option<&str> {
-        self.0 .0.uri().query()
+        self.uri().query()
+    }
+
+    fn path(&self) -> Option<&str> {
+        Some(self.uri().path())
     }
 
     fn to_content_type(&self) -> Option<Cow<'_, str>> {
@@ -53,6 +100,10 @@ where
         self.header("Referer")
     }
 
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        ActixRequest::header(self, name)
+    }
+
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
@@ -94,3 +145,86 @@ where
         Ok(futures::stream::once(async { todo!() }))
     }
 }
+
+impl<CustErr> WebsocketReq<CustErr> for ActixRequest
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = ActixResponse;
+
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+                Self::WebsocketResponse,
+            ),
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            use actix_ws::Message;
+            use futures::{channel::mpsc, SinkExt, StreamExt};
+
+            let (req, payload) = self.0.take();
+            let (response, mut session, mut msg_stream) =
+                actix_ws::handle(&req, payload)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+            let (mut incoming_tx, incoming_rx) = mpsc::channel(2048);
+            let (outgoing_tx, mut outgoing_rx) =
+                mpsc::channel::<Result<Bytes, Bytes>>(2048);
+
+            let mut pong_session = session.clone();
+            actix_web::rt::spawn(async move {
+                while let Some(Ok(msg)) = msg_stream.next().await {
+                    let frame = match msg {
+                        Message::Binary(data) => Ok(data),
+                        Message::Text(err) => Err(err.into_bytes()),
+                        Message::Ping(bytes) => {
+                            if pong_session.pong(&bytes).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    if incoming_tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            // data is sent as binary frames, errors as text frames
+            actix_web::rt::spawn(async move {
+                while let Some(frame) = outgoing_rx.next().await {
+                    let res = match frame {
+                        Ok(data) => session.binary(data).await,
+                        Err(err) => {
+                            session
+                                .text(
+                                    String::from_utf8_lossy(&err).into_owned(),
+                                )
+                                .await
+                        }
+                    };
+                    if res.is_err() {
+                        return;
+                    }
+                }
+                let _ = session.close(None).await;
+            });
+
+            Ok((incoming_rx, outgoing_tx, ActixResponse::from(response)))
+        })
+    }
+
+    fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+        actix_web::rt::spawn(future);
+    }
+}
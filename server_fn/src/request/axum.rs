@@ -1,12 +1,15 @@
-use crate::{error::ServerFnError, request::Req};
+use crate::{
+    error::ServerFnError,
+    request::{Req, WebsocketReq},
+};
 use axum::body::{Body, Bytes};
-use futures::{Stream, StreamExt};
+use futures::{Sink, Stream, StreamExt};
 use http::{
     header::{ACCEPT, CONTENT_TYPE, REFERER},
     Request,
 };
 use http_body_util::BodyExt;
-use std::borrow::Cow;
+use std::{borrow::Cow, future::Future};
 
 impl<CustErr> Req<CustErr> for Request<Body>
 where
@@ -16,6 +19,10 @@ where
         self.uri().query()
     }
 
+    fn path(&self) -> Option<&str> {
+        Some(self.uri().path())
+    }
+
     fn to_content_type(&self) -> Option<Cow<'_, str>> {
         self.headers()
             .get(CONTENT_TYPE)
@@ -34,6 +41,12 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(name)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         let (_parts, body) = self.into_parts();
 
@@ -60,3 +73,113 @@ where
         }))
     }
 }
+
+impl<CustErr> WebsocketReq<CustErr> for Request<Body>
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = http::Response<Body>;
+
+    #[cfg(feature = "axum-websocket")]
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+            impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            Self::WebsocketResponse,
+        ),
+        ServerFnError<CustErr>,
+    > {
+        use axum::extract::{
+            ws::{Message, WebSocketUpgrade},
+            FromRequestParts,
+        };
+        use futures::{channel::mpsc, SinkExt};
+
+        let (mut parts, _body) = self.into_parts();
+        let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+        let (incoming_tx, incoming_rx) = mpsc::channel(2048);
+        let (outgoing_tx, outgoing_rx) =
+            mpsc::channel::<Result<Bytes, Bytes>>(2048);
+
+        let response = upgrade.on_upgrade(|socket| async move {
+            let (ws_sink, mut ws_stream) = socket.split();
+
+            // data is sent as binary frames, errors as text frames
+            let outgoing = outgoing_rx
+                .map(|frame| {
+                    Ok(match frame {
+                        Ok(data) => Message::Binary(data.into()),
+                        Err(err) => Message::Text(
+                            String::from_utf8_lossy(&err).into_owned(),
+                        ),
+                    })
+                })
+                .forward(ws_sink);
+
+            let incoming = async move {
+                let mut incoming_tx = incoming_tx;
+                while let Some(Ok(msg)) = ws_stream.next().await {
+                    let frame = match msg {
+                        Message::Binary(data) => Ok(Bytes::from(data)),
+                        Message::Text(err) => Err(Bytes::from(err)),
+                        Message::Close(_) => break,
+                        // pings and pongs are handled by axum
+                        _ => continue,
+                    };
+                    if incoming_tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            };
+
+            let _ = futures::join!(outgoing, incoming);
+        });
+
+        Ok((incoming_rx, outgoing_tx, response))
+    }
+
+    #[cfg(not(feature = "axum-websocket"))]
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+            impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            Self::WebsocketResponse,
+        ),
+        ServerFnError<CustErr>,
+    > {
+        use crate::error::{
+            NoCustomError, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+        };
+
+        // the handshake is refused, so the client sees why the connection failed rather
+        // than a connection that never receives anything
+        let err = ServerFnError::<NoCustomError>::Response(
+            "Websockets require the `axum-websocket` feature of `server_fn`."
+                .into(),
+        );
+        let response = http::Response::builder()
+            .status(http::StatusCode::NOT_IMPLEMENTED)
+            .header(SERVER_FN_ERROR_HEADER, self.uri().path())
+            .body(Body::from(err.ser().unwrap_or_else(|_| err.to_string())))
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        Ok((futures::stream::empty(), futures::sink::drain(), response))
+    }
+
+    #[cfg(feature = "axum-websocket")]
+    fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+        tokio::spawn(future);
+    }
+
+    #[cfg(not(feature = "axum-websocket"))]
+    fn spawn(_future: impl Future<Output = ()> + Send + 'static) {
+        // the handshake has been refused with `501 Not Implemented`, so there is no
+        // connection for the server function to run on
+    }
+}
@@ -5,12 +5,20 @@ use futures::{Stream, StreamExt};
 pub use gloo_net::http::Request;
 use js_sys::{Reflect, Uint8Array};
 use send_wrapper::SendWrapper;
-use std::ops::{Deref, DerefMut};
-use wasm_bindgen::JsValue;
+use std::{
+    cell::Cell,
+    fmt,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use wasm_streams::ReadableStream;
 use web_sys::{
-    AbortController, AbortSignal, FormData, Headers, RequestInit,
-    UrlSearchParams,
+    AbortController, AbortSignal, Event, FormData, Headers, ProgressEvent,
+    RequestInit, ResponseInit, UrlSearchParams, XmlHttpRequest,
+    XmlHttpRequestResponseType,
 };
 
 /// A `fetch` request made in the browser.
@@ -21,6 +29,134 @@ pub struct BrowserRequest(pub(crate) SendWrapper<RequestInner>);
 pub(crate) struct RequestInner {
     pub(crate) request: Request,
     pub(crate) abort_ctrl: Option<AbortOnDrop>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) upload: Option<Upload>,
+    /// Whether the response may be stored in the client's cache.
+    pub(crate) cache: bool,
+}
+
+/// The body of a request that reports its progress as it is uploaded.
+pub(crate) struct Upload {
+    body: Bytes,
+    on_progress: Arc<dyn Fn(u64) + Send + Sync>,
+}
+
+impl fmt::Debug for Upload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upload")
+            .field("len", &self.body.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Upload {
+    /// Sends the request with an `XMLHttpRequest`, which, unlike `fetch`, reports how much of
+    /// the body has been uploaded.
+    ///
+    /// The request is aborted if the returned future is dropped before it completes.
+    pub(crate) async fn send<CustErr>(
+        self,
+        request: &Request,
+        timeout: Option<Duration>,
+    ) -> Result<gloo_net::http::Response, ServerFnError<CustErr>> {
+        let js_error = |e: JsValue| ServerFnError::Request(format!("{e:?}"));
+
+        let xhr = XmlHttpRequest::new().map_err(js_error)?;
+        xhr.open(request.method().as_str(), &request.url())
+            .map_err(js_error)?;
+        for (name, value) in request.headers().entries() {
+            xhr.set_request_header(&name, &value).map_err(js_error)?;
+        }
+        xhr.set_response_type(XmlHttpRequestResponseType::Arraybuffer);
+        if let Some(timeout) = timeout {
+            xhr.set_timeout(timeout.as_millis().try_into().unwrap_or(u32::MAX));
+        }
+
+        let on_progress = self.on_progress;
+        let on_progress = Closure::<dyn FnMut(ProgressEvent)>::new(
+            move |ev: ProgressEvent| on_progress(ev.loaded() as u64),
+        );
+        xhr.upload()
+            .map_err(js_error)?
+            .set_onprogress(Some(on_progress.as_ref().unchecked_ref()));
+
+        // resolves with the type of the event that ended the request
+        let (tx, rx) = futures::channel::oneshot::channel();
+        let tx = Cell::new(Some(tx));
+        let on_end = Closure::<dyn FnMut(Event)>::new(move |ev: Event| {
+            if let Some(tx) = tx.take() {
+                _ = tx.send(ev.type_());
+            }
+        });
+        let on_end = on_end.as_ref().unchecked_ref();
+        xhr.set_onload(Some(on_end));
+        xhr.set_onerror(Some(on_end));
+        xhr.set_onabort(Some(on_end));
+        xhr.set_ontimeout(Some(on_end));
+
+        let mut guard = AbortXhrOnDrop(Some(xhr.clone()));
+        xhr.send_with_opt_u8_array(Some(&self.body))
+            .map_err(js_error)?;
+        let ended = rx.await.unwrap_or_default();
+        guard.0.take();
+        match ended.as_str() {
+            "load" => {}
+            "timeout" => {
+                return Err(ServerFnError::Timeout(format!(
+                    "no response within {:?}",
+                    timeout.unwrap_or_default()
+                )))
+            }
+            "abort" => {
+                return Err(ServerFnError::Cancelled(
+                    "the upload was aborted".into(),
+                ))
+            }
+            _ => {
+                return Err(ServerFnError::Request(format!(
+                    "the upload to {} failed",
+                    request.url()
+                )))
+            }
+        }
+
+        // rebuilds the response as a `fetch` response, so that it is read like any other
+        let headers = Headers::new().map_err(js_error)?;
+        for line in xhr.get_all_response_headers().map_err(js_error)?.lines() {
+            if let Some((name, value)) = line.split_once(':') {
+                headers
+                    .append(name.trim(), value.trim())
+                    .map_err(js_error)?;
+            }
+        }
+        let status = xhr.status().map_err(js_error)?;
+        let init = ResponseInit::new();
+        init.set_status(status);
+        init.set_status_text(&xhr.status_text().map_err(js_error)?);
+        init.set_headers(&headers);
+        // these statuses cannot have a body
+        let body = if matches!(status, 101 | 204 | 205 | 304) {
+            None
+        } else {
+            Some(Uint8Array::new(&xhr.response().map_err(js_error)?))
+        };
+        let response = web_sys::Response::new_with_opt_js_u8_array_and_init(
+            body.as_ref(),
+            &init,
+        )
+        .map_err(js_error)?;
+        Ok(response.into())
+    }
+}
+
+struct AbortXhrOnDrop(Option<XmlHttpRequest>);
+
+impl Drop for AbortXhrOnDrop {
+    fn drop(&mut self) {
+        if let Some(xhr) = self.0.take() {
+            _ = xhr.abort();
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -31,6 +167,84 @@ impl AbortOnDrop {
     pub fn prevent_cancellation(&mut self) {
         self.0.take();
     }
+
+    /// Aborts the request once the timeout has elapsed, unless the timer is dropped first.
+    pub fn abort_after(&self, timeout: Duration) -> Option<AbortTimer> {
+        let ctrl = self.0.clone()?;
+        let fired = Rc::new(Cell::new(false));
+        let callback = Closure::<dyn FnMut()>::new({
+            let fired = Rc::clone(&fired);
+            move || {
+                fired.set(true);
+                ctrl.abort();
+            }
+        });
+        let handle = web_sys::window()?
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.as_ref().unchecked_ref(),
+                timeout.as_millis().try_into().unwrap_or(i32::MAX),
+            )
+            .ok()?;
+        Some(AbortTimer {
+            handle,
+            timeout,
+            fired,
+            _callback: callback,
+        })
+    }
+}
+
+/// A timer that aborts a request when its timeout elapses.
+pub(crate) struct AbortTimer {
+    handle: i32,
+    timeout: Duration,
+    fired: Rc<Cell<bool>>,
+    _callback: Closure<dyn FnMut()>,
+}
+
+impl AbortTimer {
+    /// Returns a [`ServerFnError::Timeout`] if the request was aborted because this timer fired.
+    pub fn timeout_error<CustErr>(&self) -> Option<ServerFnError<CustErr>> {
+        self.fired.get().then(|| {
+            ServerFnError::Timeout(format!(
+                "no response within {:?}",
+                self.timeout
+            ))
+        })
+    }
+}
+
+/// Converts an error from a request that was aborted into a [`ServerFnError::Timeout`] or a
+/// [`ServerFnError::Cancelled`].
+pub(crate) fn abort_error<CustErr>(
+    e: &gloo_net::Error,
+    timer: Option<&AbortTimer>,
+) -> Option<ServerFnError<CustErr>> {
+    timer
+        .and_then(AbortTimer::timeout_error)
+        .or_else(|| match e {
+            gloo_net::Error::JsError(js) if js.name == "AbortError" => {
+                Some(ServerFnError::Cancelled(e.to_string()))
+            }
+            _ => None,
+        })
+}
+
+impl std::fmt::Debug for AbortTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AbortTimer")
+            .field("timeout", &self.timeout)
+            .field("fired", &self.fired.get())
+            .finish()
+    }
+}
+
+impl Drop for AbortTimer {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            window.clear_timeout_with_handle(self.handle);
+        }
+    }
 }
 
 impl Drop for AbortOnDrop {
@@ -109,6 +323,9 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
         })))
     }
 
@@ -131,6 +348,9 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
         })))
     }
 
@@ -155,6 +375,9 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
         })))
     }
 
@@ -175,9 +398,29 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body.0.take())
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
         })))
     }
 
+    fn try_new_post_bytes_with_progress(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+        on_progress: Arc<dyn Fn(u64) + Send + Sync>,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let mut req = <Self as ClientReq<CustErr>>::try_new_post_bytes(
+            path,
+            accepts,
+            content_type,
+            body.clone(),
+        )?;
+        req.0.upload = Some(Upload { body, on_progress });
+        Ok(req)
+    }
+
     fn try_new_post_form_data(
         path: &str,
         accepts: &str,
@@ -204,6 +447,9 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(url_params)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
         })))
     }
 
@@ -213,13 +459,56 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         body: impl Stream<Item = Bytes> + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        // TODO abort signal
         let (request, abort_ctrl) =
             streaming_request(path, accepts, content_type, body)
                 .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
         Ok(Self(SendWrapper::new(RequestInner {
             request,
             abort_ctrl,
+            timeout: None,
+            upload: None,
+            cache: false,
+        })))
+    }
+
+    fn try_set_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        // headers that the browser does not allow to be set are silently ignored
+        self.headers()
+            .into_raw()
+            .set(name, value)
+            .map_err(|e| ServerFnError::Request(format!("{e:?}")))
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.0.timeout = Some(timeout);
+    }
+
+    fn enable_cache(&mut self) {
+        self.0.cache = true;
+    }
+
+    fn try_clone_request(&self) -> Option<Self> {
+        // the body of a request cannot be read synchronously, so only requests without a
+        // body can be recreated
+        if self.method() != gloo_net::http::Method::GET {
+            return None;
+        }
+        let (abort_ctrl, abort_signal) = abort_signal();
+        let request = Request::get(&self.url())
+            .headers(self.headers())
+            .abort_signal(abort_signal.as_ref())
+            .build()
+            .ok()?;
+        Some(Self(SendWrapper::new(RequestInner {
+            request,
+            abort_ctrl,
+            timeout: self.0.timeout,
+            upload: None,
+            cache: self.0.cache,
         })))
     }
 }
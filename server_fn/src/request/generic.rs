@@ -12,14 +12,18 @@
 //! * `wasm32-wasip*` integration crate `leptos_wasi` is using this
 //!   crate under the hood.
 
-use crate::request::Req;
+use crate::{
+    request::{ClientReq, Req, WebsocketReq},
+    response::generic::Body,
+    ServerFnError,
+};
 use bytes::Bytes;
 use futures::{
     stream::{self, Stream},
-    StreamExt,
+    Sink, StreamExt,
 };
-use http::Request;
-use std::borrow::Cow;
+use http::{header, Method, Request, Response};
+use std::{borrow::Cow, future::Future, str::FromStr};
 
 impl<CustErr> Req<CustErr> for Request<Bytes>
 where
@@ -68,7 +72,160 @@ where
             .map(|val| String::from_utf8_lossy(val.as_bytes()))
     }
 
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(name)
+            .map(|val| String::from_utf8_lossy(val.as_bytes()))
+    }
+
     fn as_query(&self) -> Option<&str> {
         self.uri().query()
     }
+
+    fn path(&self) -> Option<&str> {
+        Some(self.uri().path())
+    }
+}
+
+impl<CustErr> WebsocketReq<CustErr> for Request<Bytes>
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = Response<Body>;
+
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+            impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            Self::WebsocketResponse,
+        ),
+        crate::ServerFnError<CustErr>,
+    > {
+        Err::<
+            (
+                stream::Empty<Result<Bytes, Bytes>>,
+                futures::sink::Drain<Result<Bytes, Bytes>>,
+                Self::WebsocketResponse,
+            ),
+            _,
+        >(crate::ServerFnError::Response(
+            "Websockets are not supported by the generic backend.".into(),
+        ))
+    }
+
+    fn spawn(_future: impl Future<Output = ()> + Send + 'static) {
+        // never called, because `try_into_websocket` always fails
+    }
+}
+
+/// Builds requests in the generic backend’s own request type, so that server functions can be
+/// called in the same process, as with [`TestClient`](crate::testing::TestClient).
+///
+/// Requests with form data, multipart, or streaming bodies are not supported.
+impl<CustErr> ClientReq<CustErr> for Request<Bytes> {
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        new_request(
+            Method::GET,
+            &format!("{path}?{query}"),
+            accepts,
+            content_type,
+            Bytes::new(),
+        )
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        new_request(Method::POST, path, accepts, content_type, body.into())
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        new_request(Method::POST, path, accepts, content_type, body)
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "Form data is not supported by the generic backend.".into(),
+        ))
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "Multipart bodies are not supported by the generic backend.".into(),
+        ))
+    }
+
+    fn try_new_streaming(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(
+            "Streaming bodies are not supported by the generic backend.".into(),
+        ))
+    }
+
+    fn try_set_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let name = header::HeaderName::from_str(name)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        let value = header::HeaderValue::from_str(value)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        self.headers_mut().insert(name, value);
+        Ok(())
+    }
+
+    fn try_clone_request(&self) -> Option<Self> {
+        let mut req = Request::new(self.body().clone());
+        *req.method_mut() = self.method().clone();
+        *req.uri_mut() = self.uri().clone();
+        *req.headers_mut() = self.headers().clone();
+        Some(req)
+    }
+}
+
+fn new_request<CustErr>(
+    method: Method,
+    uri: &str,
+    accepts: &str,
+    content_type: &str,
+    body: Bytes,
+) -> Result<Request<Bytes>, ServerFnError<CustErr>> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::ACCEPT, accepts)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body)
+        .map_err(|e| ServerFnError::Request(e.to_string()))
 }
@@ -1,7 +1,7 @@
 use crate::error::ServerFnError;
 use bytes::Bytes;
-use futures::Stream;
-use std::{borrow::Cow, future::Future};
+use futures::{Sink, Stream};
+use std::{borrow::Cow, future::Future, sync::Arc, time::Duration};
 
 /// Request types for Actix.
 #[cfg(feature = "actix")]
@@ -72,6 +72,81 @@ where
         content_type: &str,
         body: impl Stream<Item = Bytes> + Send + 'static,
     ) -> Result<Self, ServerFnError<CustErr>>;
+
+    /// Attempts to construct a new `POST` request with a binary body, which calls
+    /// `on_progress` with the number of bytes sent so far while the body is uploaded.
+    ///
+    /// The progress is approximate, as clients can only count the bytes they have handed to
+    /// the connection, some of which may still be buffered rather than sent.
+    ///
+    /// By default, this constructs the request with
+    /// [`try_new_post_bytes`](Self::try_new_post_bytes), and reports the whole body as sent
+    /// once the request has been constructed.
+    fn try_new_post_bytes_with_progress(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+        on_progress: Arc<dyn Fn(u64) + Send + Sync>,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let len = body.len() as u64;
+        let req = Self::try_new_post_bytes(path, accepts, content_type, body)?;
+        on_progress(len);
+        Ok(req)
+    }
+
+    /// Sets a header on the request, replacing any value it already had.
+    ///
+    /// By default, this returns an error, as the request cannot be changed.
+    fn try_set_header(
+        &mut self,
+        name: &str,
+        _value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(format!(
+            "Cannot set the {name} header on this request."
+        )))
+    }
+
+    /// Sets a timeout for the request, after which the client aborts it and returns a
+    /// [`ServerFnError::Timeout`].
+    ///
+    /// By default, this does nothing.
+    fn set_timeout(&mut self, _timeout: Duration) {}
+
+    /// Allows the client to store the response in its cache, as the server function declares
+    /// that its responses can be cached.
+    ///
+    /// By default, this does nothing.
+    fn enable_cache(&mut self) {}
+
+    /// Attempts to create a copy of this request, so that it can be sent again when it is
+    /// retried.
+    ///
+    /// By default, this returns `None`, which means the request will not be retried.
+    fn try_clone_request(&self) -> Option<Self> {
+        None
+    }
+}
+
+/// How much of a request body has been uploaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UploadProgress {
+    /// The number of bytes that have been sent.
+    pub sent: u64,
+    /// The size of the whole body, in bytes.
+    pub total: u64,
+}
+
+impl UploadProgress {
+    /// Returns the fraction of the body that has been sent, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.sent as f64 / self.total as f64
+        }
+    }
 }
 
 /// Represents the request as received by the server.
@@ -91,6 +166,21 @@ where
     /// Returns the `Referer` header, if any.
     fn referer(&self) -> Option<Cow<'_, str>>;
 
+    /// Returns the path of the request’s URL, if it is known.
+    ///
+    /// By default, this returns `None`.
+    fn path(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the value of the given header, if any.
+    ///
+    /// By default, this returns `None`.
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        _ = name;
+        None
+    }
+
     /// Attempts to extract the body of the request into [`Bytes`].
     fn try_into_bytes(
         self,
@@ -110,6 +200,36 @@ where
     >;
 }
 
+/// A request, as received by the server, that can be upgraded to a websocket connection.
+///
+/// Each frame sent over the websocket is either a binary frame, which carries data
+/// (`Ok(_)`), or a text frame, which carries a serialized error (`Err(_)`).
+pub trait WebsocketReq<CustErr>: Req<CustErr> {
+    /// The response that is returned to the client to complete the handshake.
+    type WebsocketResponse;
+
+    /// Attempts to upgrade the request to a websocket connection.
+    ///
+    /// Returns a stream of incoming frames, a sink for outgoing frames, and the response
+    /// that completes the handshake.
+    #[allow(clippy::type_complexity)]
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (
+                impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+                Self::WebsocketResponse,
+            ),
+            ServerFnError<CustErr>,
+        >,
+    > + Send;
+
+    /// Spawns a task that keeps running after the handshake response has been sent.
+    fn spawn(future: impl Future<Output = ()> + Send + 'static);
+}
+
 /// A mocked request type that can be used in place of the actual server request,
 /// when compiling for the browser.
 pub struct BrowserMockReq;
@@ -133,6 +253,10 @@ where
     fn referer(&self) -> Option<Cow<'_, str>> {
         unreachable!()
     }
+
+    fn header(&self, _name: &str) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         unreachable!()
     }
@@ -150,3 +274,33 @@ where
         Ok(futures::stream::once(async { unreachable!() }))
     }
 }
+
+impl<CustErr> WebsocketReq<CustErr> for BrowserMockReq
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = crate::response::BrowserMockRes;
+
+    #[allow(unreachable_code)]
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+            impl Sink<Result<Bytes, Bytes>> + Send + 'static,
+            Self::WebsocketResponse,
+        ),
+        ServerFnError<CustErr>,
+    > {
+        unreachable!();
+        Ok((
+            futures::stream::empty(),
+            futures::sink::drain(),
+            crate::response::BrowserMockRes,
+        ))
+    }
+
+    fn spawn(_future: impl Future<Output = ()> + Send + 'static) {
+        unreachable!()
+    }
+}
@@ -1,10 +1,14 @@
 use super::ClientReq;
 use crate::{client::get_server_url, error::ServerFnError};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{stream, Stream, StreamExt};
 use once_cell::sync::Lazy;
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{
+    header::{HeaderName, HeaderValue, ACCEPT, CONTENT_LENGTH, CONTENT_TYPE},
+    Body,
+};
 pub use reqwest::{multipart::Form, Client, Method, Request, Url};
+use std::{convert::Infallible, str::FromStr, sync::Arc, time::Duration};
 
 pub(crate) static CLIENT: Lazy<Client> = Lazy::new(Client::new);
 
@@ -62,6 +66,39 @@ impl<CustErr> ClientReq<CustErr> for Request {
             .map_err(|e| ServerFnError::Request(e.to_string()))
     }
 
+    fn try_new_post_bytes_with_progress(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+        on_progress: Arc<dyn Fn(u64) + Send + Sync>,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        // the body is streamed in chunks, each of which is counted as sent when reqwest pulls
+        // it from the stream, so that progress follows the connection's backpressure. This is
+        // approximate: a chunk that has been pulled may still be in the connection's buffers
+        const CHUNK_SIZE: usize = 64 * 1024;
+        let url = format!("{}{}", get_server_url(), path);
+        let len = body.len();
+        let mut sent = 0;
+        let chunks =
+            stream::iter((0..len).step_by(CHUNK_SIZE).map(move |start| {
+                body.slice(start..(start + CHUNK_SIZE).min(len))
+            }))
+            .map(move |chunk| {
+                sent += chunk.len() as u64;
+                on_progress(sent);
+                Ok::<_, Infallible>(chunk)
+            });
+        CLIENT
+            .post(url)
+            .header(CONTENT_TYPE, content_type)
+            .header(ACCEPT, accepts)
+            .header(CONTENT_LENGTH, len)
+            .body(Body::wrap_stream(chunks))
+            .build()
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
+
     fn try_new_multipart(
         path: &str,
         accepts: &str,
@@ -115,4 +152,25 @@ impl<CustErr> ClientReq<CustErr> for Request {
                 .map_err(|e| ServerFnError::Request(e.to_string()))
         }*/
     }
+
+    fn try_set_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let name = HeaderName::from_str(name)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        self.headers_mut().insert(name, value);
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        *self.timeout_mut() = Some(timeout);
+    }
+
+    fn try_clone_request(&self) -> Option<Self> {
+        self.try_clone()
+    }
 }
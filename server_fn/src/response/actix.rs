@@ -1,11 +1,9 @@
 use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
-};
+use crate::error::{ServerFnError, ServerFnErrorErr, SERVER_FN_ERROR_HEADER};
 use actix_web::{
     http::{
         header,
-        header::{HeaderValue, LOCATION},
+        header::{HeaderName, HeaderValue, LOCATION},
         StatusCode,
     },
     HttpResponse,
@@ -15,6 +13,7 @@ use futures::{Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{
     fmt::{Debug, Display},
+    future::Future,
     str::FromStr,
 };
 
@@ -40,7 +39,7 @@ impl From<HttpResponse> for ActixResponse {
 
 impl<CustErr> Res<CustErr> for ActixResponse
 where
-    CustErr: FromStr + Display + Debug + 'static,
+    CustErr: Display + Debug + 'static,
 {
     fn try_from_string(
         content_type: &str,
@@ -84,7 +83,24 @@ where
         ActixResponse(SendWrapper::new(
             HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
                 .append_header((SERVER_FN_ERROR_HEADER, path))
-                .body(err.ser().unwrap_or_else(|_| err.to_string())),
+                .body(err.ser_text().unwrap_or_else(|_| err.to_string())),
+        ))
+    }
+
+    fn encoded_error_response(
+        path: &str,
+        status: u16,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        ActixResponse(SendWrapper::new(
+            HttpResponse::build(
+                StatusCode::from_u16(status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            )
+            .append_header((SERVER_FN_ERROR_HEADER, path))
+            .content_type(content_type)
+            .body(data),
         ))
     }
 
@@ -94,4 +110,73 @@ where
             self.0.headers_mut().insert(LOCATION, path);
         }
     }
+
+    fn set_status(
+        &mut self,
+        status: u16,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        *self.0.status_mut() = StatusCode::from_u16(status)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        Ok(())
+    }
+
+    fn append_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let name = HeaderName::from_str(name)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        self.0.headers_mut().append(name, value);
+        Ok(())
+    }
+
+    fn status(&self) -> u16 {
+        self.0.status().as_u16()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        self.0
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    fn buffer_body(
+        &mut self,
+    ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
+    {
+        // Actix bodies are not `Send`, but they are only ever read on the current thread
+        SendWrapper::new(async move {
+            let (res, body) = take_response(self).into_parts();
+            let data = actix_web::body::to_bytes(body)
+                .await
+                .map_err(|e| ServerFnError::Response(e.to_string()));
+            let body = data.as_ref().cloned().unwrap_or_default();
+            *self.0 = res.set_body(body).map_into_boxed_body();
+            data
+        })
+    }
+
+    fn clear_body(&mut self) -> Result<(), ServerFnError<CustErr>> {
+        *self.0 = take_response(self).drop_body().map_into_boxed_body();
+        Ok(())
+    }
+
+    fn set_body(&mut self, data: Bytes) -> Result<(), ServerFnError<CustErr>> {
+        *self.0 = take_response(self).set_body(data).map_into_boxed_body();
+        Ok(())
+    }
+}
+
+fn take_response(res: &mut ActixResponse) -> HttpResponse {
+    std::mem::replace(&mut *res.0, HttpResponse::NoContent().finish())
 }
@@ -1,5 +1,9 @@
 use super::ClientRes;
-use crate::{error::ServerFnError, redirect::REDIRECT_HEADER};
+use crate::{
+    error::ServerFnError,
+    redirect::REDIRECT_HEADER,
+    request::browser::{abort_error, AbortOnDrop, AbortTimer},
+};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 pub use gloo_net::http::Response;
@@ -10,7 +14,31 @@ use wasm_bindgen::JsCast;
 use wasm_streams::ReadableStream;
 
 /// The response to a `fetch` request made in the browser.
-pub struct BrowserResponse(pub(crate) SendWrapper<Response>);
+pub struct BrowserResponse(pub(crate) SendWrapper<ResponseInner>);
+
+pub(crate) struct ResponseInner {
+    pub(crate) response: Response,
+    // the request is aborted if the response is dropped before its body has been read, or if
+    // the timeout elapses while it is being read
+    pub(crate) abort_ctrl: Option<AbortOnDrop>,
+    pub(crate) timer: Option<AbortTimer>,
+}
+
+impl ResponseInner {
+    fn body_error<CustErr>(
+        &self,
+        e: gloo_net::Error,
+    ) -> ServerFnError<CustErr> {
+        abort_error(&e, self.timer.as_ref())
+            .unwrap_or_else(|| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    fn finish(&mut self) {
+        if let Some(ctrl) = self.abort_ctrl.as_mut() {
+            ctrl.prevent_cancellation();
+        }
+    }
+}
 
 impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn try_into_string(
@@ -20,10 +48,10 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
-                .text()
-                .await
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            let mut inner = self.0.take();
+            let text = inner.response.text().await;
+            inner.finish();
+            text.map_err(|e| inner.body_error(e))
         })
     }
 
@@ -34,11 +62,10 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
-                .binary()
-                .await
-                .map(Bytes::from)
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            let mut inner = self.0.take();
+            let data = inner.response.binary().await;
+            inner.finish();
+            data.map(Bytes::from).map_err(|e| inner.body_error(e))
         })
     }
 
@@ -48,41 +75,60 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
         ServerFnError<CustErr>,
     > {
-        let stream = ReadableStream::from_raw(self.0.body().unwrap())
-            .into_stream()
-            .map(|data| match data {
-                Err(e) => {
-                    web_sys::console::error_1(&e);
-                    Err(ServerFnError::Request(format!("{e:?}")))
-                }
-                Ok(data) => {
-                    let data = data.unchecked_into::<Uint8Array>();
-                    let mut buf = Vec::new();
-                    let length = data.length();
-                    buf.resize(length as usize, 0);
-                    data.copy_to(&mut buf);
-                    Ok(Bytes::from(buf))
-                }
-            });
+        let inner = self.0.take();
+        let body = inner.response.body().unwrap();
+        let stream =
+            ReadableStream::from_raw(body)
+                .into_stream()
+                .map(move |data| match data {
+                    Err(e) => {
+                        web_sys::console::error_1(&e);
+                        // the request stays alive (and can be aborted) as long as the stream does
+                        Err(inner
+                            .timer
+                            .as_ref()
+                            .and_then(AbortTimer::timeout_error)
+                            .unwrap_or_else(|| {
+                                ServerFnError::Request(format!("{e:?}"))
+                            }))
+                    }
+                    Ok(data) => {
+                        let data = data.unchecked_into::<Uint8Array>();
+                        let mut buf = Vec::new();
+                        let length = data.length();
+                        buf.resize(length as usize, 0);
+                        data.copy_to(&mut buf);
+                        Ok(Bytes::from(buf))
+                    }
+                });
         Ok(SendWrapper::new(stream))
     }
 
     fn status(&self) -> u16 {
-        self.0.status()
+        self.0.response.status()
     }
 
     fn status_text(&self) -> String {
-        self.0.status_text()
+        self.0.response.status_text()
     }
 
     fn location(&self) -> String {
         self.0
+            .response
             .headers()
             .get("Location")
-            .unwrap_or_else(|| self.0.url())
+            .unwrap_or_else(|| self.0.response.url())
     }
 
     fn has_redirect(&self) -> bool {
-        self.0.headers().get(REDIRECT_HEADER).is_some()
+        self.0.response.headers().get(REDIRECT_HEADER).is_some()
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.0.response.headers().get(name)
+    }
+
+    fn is_body_decoded(&self) -> bool {
+        true
     }
 }
@@ -12,13 +12,11 @@
 //! * `wasm32-wasip*` integration crate `leptos_wasi` is using this
 //!   crate under the hood.
 
-use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
-};
-use bytes::Bytes;
+use super::{ClientRes, Res};
+use crate::error::{ServerFnError, ServerFnErrorErr, SERVER_FN_ERROR_HEADER};
+use bytes::{Bytes, BytesMut};
 use futures::{Stream, TryStreamExt};
-use http::{header, HeaderValue, Response, StatusCode};
+use http::{header, HeaderName, HeaderValue, Response, StatusCode};
 use std::{
     fmt::{Debug, Display},
     pin::Pin,
@@ -46,7 +44,7 @@ impl From<String> for Body {
 
 impl<CustErr> Res<CustErr> for Response<Body>
 where
-    CustErr: Send + Sync + Debug + FromStr + Display + 'static,
+    CustErr: Send + Sync + Debug + Display + 'static,
 {
     fn try_from_string(
         content_type: &str,
@@ -92,7 +90,24 @@ where
         Response::builder()
             .status(http::StatusCode::INTERNAL_SERVER_ERROR)
             .header(SERVER_FN_ERROR_HEADER, path)
-            .body(err.ser().unwrap_or_else(|_| err.to_string()).into())
+            .body(err.ser_text().unwrap_or_else(|_| err.to_string()).into())
+            .unwrap()
+    }
+
+    fn encoded_error_response(
+        path: &str,
+        status: u16,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        Response::builder()
+            .status(
+                StatusCode::from_u16(status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            )
+            .header(SERVER_FN_ERROR_HEADER, path)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::Sync(data))
             .unwrap()
     }
 
@@ -102,4 +117,121 @@ where
             *self.status_mut() = StatusCode::FOUND;
         }
     }
+
+    fn set_status(
+        &mut self,
+        status: u16,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        *self.status_mut() = StatusCode::from_u16(status)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        Ok(())
+    }
+
+    fn append_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let name = HeaderName::from_str(name)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        self.headers_mut().append(name, value);
+        Ok(())
+    }
+
+    fn status(&self) -> u16 {
+        Response::status(self).as_u16()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        Response::headers(self)
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    async fn buffer_body(&mut self) -> Result<Bytes, ServerFnError<CustErr>> {
+        let data = match std::mem::replace(
+            self.body_mut(),
+            Body::Sync(Bytes::new()),
+        ) {
+            Body::Sync(data) => data,
+            Body::Async(stream) => stream
+                .try_fold(BytesMut::new(), |mut data, chunk| async move {
+                    data.extend_from_slice(&chunk);
+                    Ok(data)
+                })
+                .await
+                .map_err(|e| ServerFnError::Response(e.to_string()))?
+                .freeze(),
+        };
+        *self.body_mut() = Body::Sync(data.clone());
+        Ok(data)
+    }
+
+    fn clear_body(&mut self) -> Result<(), ServerFnError<CustErr>> {
+        *self.body_mut() = Body::Sync(Bytes::new());
+        Ok(())
+    }
+
+    fn set_body(&mut self, data: Bytes) -> Result<(), ServerFnError<CustErr>> {
+        *self.body_mut() = Body::Sync(data);
+        Ok(())
+    }
+}
+
+/// Reads responses that were buffered into the generic backend’s own types, as with
+/// [`TestClient`](crate::testing::TestClient).
+impl<CustErr> ClientRes<CustErr> for Response<Bytes> {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        String::from_utf8(self.into_body().into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.into_body())
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        let body = self.into_body();
+        Ok(futures::stream::once(async move { Ok(body) }))
+    }
+
+    fn status(&self) -> u16 {
+        Response::status(self).as_u16()
+    }
+
+    fn status_text(&self) -> String {
+        Response::status(self)
+            .canonical_reason()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn location(&self) -> String {
+        ClientRes::<CustErr>::header(self, header::LOCATION.as_str())
+            .unwrap_or_default()
+    }
+
+    fn has_redirect(&self) -> bool {
+        ClientRes::<CustErr>::header(self, crate::redirect::REDIRECT_HEADER)
+            .is_some()
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers()
+            .get(name)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
 }
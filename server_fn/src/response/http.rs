@@ -1,11 +1,10 @@
 use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
-};
+use crate::error::{ServerFnError, ServerFnErrorErr, SERVER_FN_ERROR_HEADER};
 use axum::body::Body;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
-use http::{header, HeaderValue, Response, StatusCode};
+use http::{header, HeaderName, HeaderValue, Response, StatusCode};
+use http_body_util::BodyExt;
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
@@ -13,7 +12,7 @@ use std::{
 
 impl<CustErr> Res<CustErr> for Response<Body>
 where
-    CustErr: Send + Sync + Debug + FromStr + Display + 'static,
+    CustErr: Send + Sync + Debug + Display + 'static,
 {
     fn try_from_string(
         content_type: &str,
@@ -59,7 +58,24 @@ where
         Response::builder()
             .status(http::StatusCode::INTERNAL_SERVER_ERROR)
             .header(SERVER_FN_ERROR_HEADER, path)
-            .body(err.ser().unwrap_or_else(|_| err.to_string()).into())
+            .body(err.ser_text().unwrap_or_else(|_| err.to_string()).into())
+            .unwrap()
+    }
+
+    fn encoded_error_response(
+        path: &str,
+        status: u16,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        Response::builder()
+            .status(
+                StatusCode::from_u16(status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            )
+            .header(SERVER_FN_ERROR_HEADER, path)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(data))
             .unwrap()
     }
 
@@ -69,4 +85,63 @@ where
             *self.status_mut() = StatusCode::FOUND;
         }
     }
+
+    fn set_status(
+        &mut self,
+        status: u16,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        *self.status_mut() = StatusCode::from_u16(status)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        Ok(())
+    }
+
+    fn append_header(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let name = HeaderName::from_str(name)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        self.headers_mut().append(name, value);
+        Ok(())
+    }
+
+    fn status(&self) -> u16 {
+        Response::status(self).as_u16()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        Response::headers(self)
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    async fn buffer_body(&mut self) -> Result<Bytes, ServerFnError<CustErr>> {
+        let body = std::mem::take(self.body_mut());
+        let data = body
+            .collect()
+            .await
+            .map_err(|e| ServerFnError::Response(e.to_string()))?
+            .to_bytes();
+        *self.body_mut() = Body::from(data.clone());
+        Ok(data)
+    }
+
+    fn clear_body(&mut self) -> Result<(), ServerFnError<CustErr>> {
+        *self.body_mut() = Body::empty();
+        Ok(())
+    }
+
+    fn set_body(&mut self, data: Bytes) -> Result<(), ServerFnError<CustErr>> {
+        *self.body_mut() = Body::from(data);
+        Ok(())
+    }
 }
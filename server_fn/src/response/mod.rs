@@ -46,8 +46,100 @@ where
     /// Converts an error into a response, with a `500` status code and the error text as its body.
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self;
 
+    /// Converts an error that has already been encoded into a response, with the given status
+    /// code and the encoded error as its body.
+    ///
+    /// By default, this creates the response with [`Res::try_from_bytes`] and
+    /// [`Res::set_status`], and falls back to [`Res::error_response`] if either fails.
+    fn encoded_error_response(
+        path: &str,
+        status: u16,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        Self::try_from_bytes(content_type, data)
+            .and_then(|mut res| {
+                res.set_status(status)?;
+                Ok(res)
+            })
+            .unwrap_or_else(|e| Self::error_response(path, &e))
+    }
+
     /// Redirect the response by setting a 302 code and Location header.
     fn redirect(&mut self, path: &str);
+
+    /// Sets the status code of the response.
+    ///
+    /// By default, this returns an error.
+    fn set_status(
+        &mut self,
+        status: u16,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(format!(
+            "Cannot set the status code {status} on this response."
+        )))
+    }
+
+    /// Appends a header to the response.
+    ///
+    /// By default, this returns an error.
+    fn append_header(
+        &mut self,
+        name: &str,
+        _value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(format!(
+            "Cannot set the {name} header on this response."
+        )))
+    }
+
+    /// The status code of the response.
+    ///
+    /// By default, this returns `200`.
+    fn status(&self) -> u16 {
+        200
+    }
+
+    /// The headers of the response, as name/value pairs.
+    ///
+    /// By default, this returns no headers.
+    fn headers(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Reads the whole body of the response, leaving the buffered body in its place so that
+    /// the response can still be sent.
+    ///
+    /// By default, this returns an error.
+    fn buffer_body(
+        &mut self,
+    ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
+    {
+        async {
+            Err(ServerFnError::Response(
+                "Cannot read the body of this response.".into(),
+            ))
+        }
+    }
+
+    /// Replaces the body of the response with an empty body.
+    ///
+    /// By default, this returns an error, and leaves the body as it is.
+    fn clear_body(&mut self) -> Result<(), ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(
+            "Cannot clear the body of this response.".into(),
+        ))
+    }
+
+    /// Replaces the body of the response with the given bytes.
+    ///
+    /// By default, this returns an error, and leaves the body as it is.
+    fn set_body(&mut self, data: Bytes) -> Result<(), ServerFnError<CustErr>> {
+        _ = data;
+        Err(ServerFnError::Response(
+            "Cannot replace the body of this response.".into(),
+        ))
+    }
 }
 
 /// Represents the response as received by the client.
@@ -81,6 +173,22 @@ pub trait ClientRes<CustErr> {
 
     /// Whether the response has the [`REDIRECT_HEADER`](crate::redirect::REDIRECT_HEADER) set.
     fn has_redirect(&self) -> bool;
+
+    /// The value of the given header, if it is set.
+    ///
+    /// By default, this returns `None`.
+    fn header(&self, name: &str) -> Option<String> {
+        _ = name;
+        None
+    }
+
+    /// Whether the body has already been decompressed according to the `Content-Encoding`
+    /// header, as browsers do.
+    ///
+    /// By default, this is `false`.
+    fn is_body_decoded(&self) -> bool {
+        false
+    }
 }
 
 /// A mocked response type that can be used in place of the actual server response,
@@ -110,6 +218,15 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
         unreachable!()
     }
 
+    fn encoded_error_response(
+        _path: &str,
+        _status: u16,
+        _content_type: &str,
+        _data: Bytes,
+    ) -> Self {
+        unreachable!()
+    }
+
     fn try_from_stream(
         _content_type: &str,
         _data: impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>>,
@@ -120,4 +237,39 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
     fn redirect(&mut self, _path: &str) {
         unreachable!()
     }
+
+    fn set_status(
+        &mut self,
+        _status: u16,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
+    fn append_header(
+        &mut self,
+        _name: &str,
+        _value: &str,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
+    fn status(&self) -> u16 {
+        unreachable!()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        unreachable!()
+    }
+
+    async fn buffer_body(&mut self) -> Result<Bytes, ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
+    fn clear_body(&mut self) -> Result<(), ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
+    fn set_body(&mut self, _data: Bytes) -> Result<(), ServerFnError<CustErr>> {
+        unreachable!()
+    }
 }
@@ -4,17 +4,22 @@ use bytes::Bytes;
 use futures::{Stream, TryStreamExt};
 use reqwest::Response;
 
+/// Reading the body can time out, if a timeout has been set on the request.
+fn body_error<CustErr>(e: reqwest::Error) -> ServerFnError<CustErr> {
+    if e.is_timeout() {
+        ServerFnError::Timeout(e.to_string())
+    } else {
+        ServerFnError::Deserialization(e.to_string())
+    }
+}
+
 impl<CustErr> ClientRes<CustErr> for Response {
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
-        self.text()
-            .await
-            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        self.text().await.map_err(body_error)
     }
 
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
-        self.bytes()
-            .await
-            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        self.bytes().await.map_err(body_error)
     }
 
     fn try_into_stream(
@@ -46,4 +51,10 @@ impl<CustErr> ClientRes<CustErr> for Response {
     fn has_redirect(&self) -> bool {
         self.headers().get("Location").is_some()
     }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers()
+            .get(name)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+    }
 }
@@ -0,0 +1,251 @@
+use crate::{
+    codec::{Encoding, FromRes, IntoReq},
+    error::ServerFnError,
+    middleware::BoxedService,
+    response::Res,
+    ServerFn, ServerFnTraitObj,
+};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Request, Response};
+
+/// Converts a request in the generic backend’s request type into the request type of a server
+/// integration, so that its server functions can be called with a [`TestClient`].
+pub trait FromHttpRequest {
+    /// Converts the request.
+    fn from_http_request(req: Request<Bytes>) -> Self;
+}
+
+impl FromHttpRequest for Request<Bytes> {
+    fn from_http_request(req: Request<Bytes>) -> Self {
+        req
+    }
+}
+
+#[cfg(feature = "axum-no-default")]
+impl FromHttpRequest for Request<axum::body::Body> {
+    fn from_http_request(req: Request<Bytes>) -> Self {
+        req.map(axum::body::Body::from)
+    }
+}
+
+/// Calls server functions in the same process, without starting a server.
+///
+/// Each call encodes its arguments into a request, runs the server function with its
+/// middleware, and decodes the output from the response, just as a real call would:
+///
+/// ```rust,ignore
+/// use server_fn::testing::TestClient;
+///
+/// #[tokio::test]
+/// async fn adds() {
+///     let client = TestClient::new();
+///     let sum = client.call(AddNumbers { a: 1, b: 2 }).await.unwrap();
+///     assert_eq!(sum, 3);
+/// }
+/// ```
+///
+/// Server functions with streaming or websocket arguments cannot be called, and multipart
+/// arguments must be built with a `MultipartForm` rather than the browser's `FormData`.
+#[derive(Debug, Clone, Default)]
+pub struct TestClient {
+    headers: HeaderMap,
+}
+
+impl TestClient {
+    /// Creates a client that sends no additional headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header to every request, like a `Cookie` or `Authorization` header.
+    ///
+    /// # Panics
+    /// Panics if the name or the value is not a valid header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.append(
+            HeaderName::try_from(name).expect("invalid header name"),
+            HeaderValue::try_from(value).expect("invalid header value"),
+        );
+        self
+    }
+
+    /// Calls the server function, and decodes its output or error.
+    pub async fn call<T>(
+        &self,
+        input: T,
+    ) -> Result<T::Output, ServerFnError<T::Error>>
+    where
+        T: ServerFn
+            + IntoReq<T::InputEncoding, Request<Bytes>, T::Error>
+            + 'static,
+        T::ServerRequest: FromHttpRequest + 'static,
+        T::ServerResponse: 'static,
+        T::Output: FromRes<T::OutputEncoding, Response<Bytes>, T::Error>,
+    {
+        let res = self.respond(input).await?;
+        if (400..=599).contains(&res.status().as_u16()) {
+            Err(T::decode_error(res).await)
+        } else {
+            T::Output::from_res(res).await
+        }
+    }
+
+    /// Calls the server function, and returns its response without decoding it.
+    pub async fn respond<T>(
+        &self,
+        input: T,
+    ) -> Result<Response<Bytes>, ServerFnError<T::Error>>
+    where
+        T: ServerFn
+            + IntoReq<T::InputEncoding, Request<Bytes>, T::Error>
+            + 'static,
+        T::ServerRequest: FromHttpRequest + 'static,
+        T::ServerResponse: 'static,
+    {
+        let mut req: Request<Bytes> =
+            input.into_req(T::PATH, T::OutputEncoding::CONTENT_TYPE)?;
+        for (name, value) in &self.headers {
+            req.headers_mut().append(name, value.clone());
+        }
+
+        let mut service = BoxedService::new(ServerFnTraitObj::new(
+            T::PATH,
+            T::InputEncoding::METHOD,
+            |req| Box::pin(T::run_on_server(req)),
+            T::middlewares,
+        ));
+        for middleware in T::middlewares() {
+            service = middleware.layer(service);
+        }
+        let mut res = service
+            .0
+            .run(T::ServerRequest::from_http_request(req))
+            .await;
+
+        let body = res.buffer_body().await?;
+        let mut builder = Response::builder().status(res.status());
+        for (name, value) in Res::<T::Error>::headers(&res) {
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(body)
+            .map_err(|e| ServerFnError::Response(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Client,
+        codec::Json,
+        error::NoCustomError,
+        middleware::{Layer, Service},
+        response::generic::Body,
+    };
+    use futures::executor::block_on;
+    use serde::{Deserialize, Serialize};
+    use std::{future::Future, pin::Pin, sync::Arc};
+
+    /// The server functions below are only ever called with a [`TestClient`].
+    struct Unused;
+
+    impl Client<NoCustomError> for Unused {
+        type Request = Request<Bytes>;
+        type Response = Response<Bytes>;
+
+        async fn send(
+            _req: Request<Bytes>,
+        ) -> Result<Response<Bytes>, ServerFnError> {
+            unreachable!()
+        }
+
+        async fn sleep(_duration: std::time::Duration) {
+            unreachable!()
+        }
+    }
+
+    /// Rejects requests without an `Authorization` header.
+    struct RequireAuth;
+
+    impl Layer<Request<Bytes>, Response<Body>> for RequireAuth {
+        fn layer(
+            &self,
+            inner: BoxedService<Request<Bytes>, Response<Body>>,
+        ) -> BoxedService<Request<Bytes>, Response<Body>> {
+            BoxedService::new(RequireAuthService(inner))
+        }
+    }
+
+    struct RequireAuthService(BoxedService<Request<Bytes>, Response<Body>>);
+
+    impl Service<Request<Bytes>, Response<Body>> for RequireAuthService {
+        fn run(
+            &mut self,
+            req: Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+            if req.headers().contains_key("Authorization") {
+                return self.0 .0.run(req);
+            }
+            let err = ServerFnError::<NoCustomError>::ServerError(
+                "unauthorized".into(),
+            );
+            let res = Response::error_response(req.uri().path(), &err);
+            Box::pin(async move { res })
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct AddNumbers {
+        a: u8,
+        b: u8,
+    }
+
+    impl ServerFn for AddNumbers {
+        const PATH: &'static str = "/api/add_numbers";
+        type Client = Unused;
+        type ServerRequest = Request<Bytes>;
+        type ServerResponse = Response<Body>;
+        type Output = u8;
+        type InputEncoding = Json;
+        type OutputEncoding = Json;
+        type Error = NoCustomError;
+
+        fn middlewares() -> Vec<Arc<dyn Layer<Request<Bytes>, Response<Body>>>>
+        {
+            vec![Arc::new(RequireAuth)]
+        }
+
+        async fn run_body(self) -> Result<u8, ServerFnError> {
+            self.a
+                .checked_add(self.b)
+                .ok_or_else(|| ServerFnError::ServerError("overflow".into()))
+        }
+    }
+
+    #[test]
+    fn calls_run_the_server_function_with_its_middleware() {
+        let client = TestClient::new().header("Authorization", "Bearer token");
+        let sum = block_on(client.call(AddNumbers { a: 1, b: 2 }));
+        assert_eq!(sum, Ok(3));
+
+        let res = block_on(TestClient::new().call(AddNumbers { a: 1, b: 2 }));
+        assert_eq!(res, Err(ServerFnError::ServerError("unauthorized".into())));
+    }
+
+    #[test]
+    fn errors_are_decoded() {
+        let client = TestClient::new().header("Authorization", "Bearer token");
+        let res = block_on(client.call(AddNumbers { a: 200, b: 100 }));
+        assert_eq!(res, Err(ServerFnError::ServerError("overflow".into())));
+    }
+
+    #[test]
+    fn responses_are_returned_as_sent() {
+        let client = TestClient::new().header("Authorization", "Bearer token");
+        let res = block_on(client.respond(AddNumbers { a: 1, b: 2 })).unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["Content-Type"], "application/json");
+        assert_eq!(res.body(), "3");
+    }
+}
@@ -40,16 +40,21 @@ pub fn server_macro_impl(
 ) -> Result<TokenStream2> {
     let mut body = syn::parse::<ServerFnBody>(body.into())?;
 
-    // extract all #[middleware] attributes, removing them from signature of dummy
+    // extract all #[middleware] and #[client_middleware] attributes, removing them from
+    // signature of dummy
     let mut middlewares: Vec<Middleware> = vec![];
+    let mut client_middlewares: Vec<Middleware> = vec![];
     body.attrs.retain(|attr| {
-        if attr.meta.path().is_ident("middleware") {
-            if let Ok(middleware) = attr.parse_args() {
-                middlewares.push(middleware);
-                false
-            } else {
-                true
-            }
+        let target = if attr.meta.path().is_ident("middleware") {
+            &mut middlewares
+        } else if attr.meta.path().is_ident("client_middleware") {
+            &mut client_middlewares
+        } else {
+            return true;
+        };
+        if let Ok(middleware) = attr.parse_args() {
+            target.push(middleware);
+            false
         } else {
             true
         }
@@ -175,6 +180,9 @@ pub fn server_macro_impl(
                     } else if attr.path().is_ident("ignore") {
                         // Allow #[ignore]
                         Ok(attr.clone())
+                    } else if attr.path().is_ident("prost") {
+                        // Allow #[prost(...)], for the `Protobuf` encoding
+                        Ok(attr.clone())
                     } else {
                         Err(Error::new(
                             attr.span(),
@@ -206,6 +214,15 @@ pub fn server_macro_impl(
         client,
         custom_wrapper,
         impl_from,
+        structured_errors,
+        timeout,
+        retries,
+        cache,
+        rate_limit,
+        rate_limit_key,
+        concurrency_limit,
+        csrf,
+        version,
     } = args;
     let prefix = prefix.unwrap_or_else(|| Literal::string(default_path));
     let fn_path = fn_path.unwrap_or_else(|| Literal::string(""));
@@ -229,6 +246,30 @@ pub fn server_macro_impl(
                 #server_fn_path::codec::PostUrl
             }
         });
+    let output_ident = match &output {
+        Some(Type::Path(path)) => {
+            path.path.segments.last().map(|seg| seg.ident.to_string())
+        }
+        _ => None,
+    };
+    // a websocket uses the same encoding in both directions
+    let websocket = input_ident.as_deref() == Some("Websocket");
+    if websocket {
+        if let Some(output) = &output {
+            return Err(syn::Error::new(
+                output.span(),
+                "the output encoding of a websocket server function is \
+                 determined by its `Websocket` input encoding",
+            ));
+        }
+        if body.inputs.len() != 1 {
+            return Err(syn::Error::new(
+                body.inputs.span(),
+                "websocket server functions should take exactly one \
+                 argument, a `BoxedStream` of inputs",
+            ));
+        }
+    }
     let output = output
         .map(|n| {
             if builtin_encoding {
@@ -238,8 +279,12 @@ pub fn server_macro_impl(
             }
         })
         .unwrap_or_else(|| {
-            quote! {
-                #server_fn_path::codec::Json
+            if websocket {
+                input.clone()
+            } else {
+                quote! {
+                    #server_fn_path::codec::Json
+                }
             }
         });
     // default to PascalCase version of function name if no struct name given
@@ -330,23 +375,83 @@ pub fn server_macro_impl(
         });
 
     // build server fn path
-    let serde_path = server_fn_path.as_ref().map(|path| {
-        let path = path
-            .segments
-            .iter()
-            .map(|segment| segment.ident.to_string())
-            .collect::<Vec<_>>();
-        let path = path.join("::");
-        format!("{path}::serde")
-    });
+    let reexport_path = |name: &str| {
+        server_fn_path.as_ref().map(|path| {
+            let path = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>();
+            let path = path.join("::");
+            format!("{path}::{name}")
+        })
+    };
+    let serde_path = reexport_path("serde");
+    let schemars_path = reexport_path("schemars");
+    let bitcode_path = reexport_path("bitcode");
     let server_fn_path = server_fn_path
         .map(|path| quote!(#path))
         .unwrap_or_else(|| quote! { server_fn });
 
-    let key_env_var = match option_env!("SERVER_FN_OVERRIDE_KEY") {
-        Some(_) => "SERVER_FN_OVERRIDE_KEY",
-        None => "CARGO_MANIFEST_DIR",
+    // the hash in the default path is computed from the location of the server function by
+    // default, or from its module and name, or only its name, with `SERVER_FN_PATH_HASH`
+    let hash_input = match option_env!("SERVER_FN_PATH_HASH") {
+        None | Some("location") => {
+            let key_env_var = match option_env!("SERVER_FN_OVERRIDE_KEY") {
+                Some(_) => "SERVER_FN_OVERRIDE_KEY",
+                None => "CARGO_MANIFEST_DIR",
+            };
+            quote! {
+                concat!(env!(#key_env_var), ":", file!(), ":", line!(), ":", column!())
+            }
+        }
+        Some(hash) => {
+            // the manifest directory depends on where the crate is built, so is not stable
+            let key_env_var = match option_env!("SERVER_FN_OVERRIDE_KEY") {
+                Some(_) => "SERVER_FN_OVERRIDE_KEY",
+                None => "CARGO_PKG_NAME",
+            };
+            match hash {
+                "module" => quote! {
+                    concat!(env!(#key_env_var), ":", module_path!(), "::", #fn_name_as_str)
+                },
+                "name" => quote! {
+                    concat!(env!(#key_env_var), ":", #fn_name_as_str)
+                },
+                _ => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        format!(
+                            "unknown SERVER_FN_PATH_HASH `{hash}`: expected \
+                             `location`, `module`, or `name`"
+                        ),
+                    ))
+                }
+            }
+        }
     };
+    // a version becomes part of the path, so that several versions can be served side by side
+    let version = version
+        .map(|version| version.base10_parse::<u32>())
+        .transpose()?;
+    let version_segment = version
+        .map(|version| format!("/v{version}"))
+        .unwrap_or_default();
+    let version_const = version.map(|version| {
+        quote! {
+            const VERSION: Option<u32> = Some(#version);
+        }
+    });
+    // streamed responses are never cached by the client, as it would have to buffer them
+    let streamed_output = matches!(
+        output_ident.as_deref(),
+        Some("Streaming") | Some("StreamingText") | Some("EventStream")
+    );
+    let cache_const = (cache.is_some() && !streamed_output).then(|| {
+        quote! {
+            const CACHE: bool = true;
+        }
+    });
 
     let link_to_server_fn = format!(
         "Serialized arguments for the [`{fn_name_as_str}`] server \
@@ -376,6 +481,12 @@ pub fn server_macro_impl(
                     },
                     #wrapped_struct_name_turbofish::middlewares
                 )
+                .with_encodings(
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::CONTENT_TYPE,
+                    <#wrapped_struct_name as ServerFn>::OutputEncoding::CONTENT_TYPE,
+                )
+                .with_name(concat!(module_path!(), "::", stringify!(#struct_name)))
+                .with_version(<#wrapped_struct_name as ServerFn>::VERSION)
             }}
         }
     } else {
@@ -469,6 +580,7 @@ pub fn server_macro_impl(
     enum PathInfo {
         Serde,
         Rkyv,
+        Bitcode,
         None,
     }
 
@@ -479,9 +591,16 @@ pub fn server_macro_impl(
                 Clone, #server_fn_path::rkyv::Archive, #server_fn_path::rkyv::Serialize, #server_fn_path::rkyv::Deserialize
             },
         ),
+        Some("Bitcode") => (
+            PathInfo::Bitcode,
+            quote! {
+                Clone, #server_fn_path::bitcode::Encode, #server_fn_path::bitcode::Decode
+            },
+        ),
         Some("MultipartFormData")
         | Some("Streaming")
-        | Some("StreamingText") => (PathInfo::None, quote! {}),
+        | Some("StreamingText")
+        | Some("Websocket") => (PathInfo::None, quote! {}),
         Some("SerdeLite") => (
             PathInfo::Serde,
             quote! {
@@ -501,11 +620,23 @@ pub fn server_macro_impl(
             ),
         },
     };
+    // with the `openapi` feature, arguments encoded with serde are described by a JSON schema
+    let openapi = cfg!(feature = "ssr") && cfg!(feature = "openapi");
+    let serde_args = matches!(path, PathInfo::Serde);
+    let schema_derive = (openapi && serde_args).then(|| {
+        quote! {
+            #[derive(#server_fn_path::schemars::JsonSchema)]
+            #[schemars(crate = #schemars_path)]
+        }
+    });
     let addl_path = match path {
         PathInfo::Serde => quote! {
             #[serde(crate = #serde_path)]
         },
         PathInfo::Rkyv => quote! {},
+        PathInfo::Bitcode => quote! {
+            #[bitcode(crate = #bitcode_path)]
+        },
         PathInfo::None => quote! {},
     };
 
@@ -599,16 +730,18 @@ pub fn server_macro_impl(
         if #fn_path.is_empty() {
             #server_fn_path::const_format::concatcp!(
                 #prefix,
+                #version_segment,
                 "/",
                 #fn_name_as_str,
                 #server_fn_path::xxhash_rust::const_xxh64::xxh64(
-                    concat!(env!(#key_env_var), ":", file!(), ":", line!(), ":", column!()).as_bytes(),
+                    #hash_input.as_bytes(),
                     0
                 )
             )
         } else {
             #server_fn_path::const_format::concatcp!(
                 #prefix,
+                #version_segment,
                 #fn_path
             )
         }
@@ -616,23 +749,244 @@ pub fn server_macro_impl(
 
     // only emit the dummy (unmodified server-only body) for the server build
     let dummy = cfg!(feature = "ssr").then_some(dummy);
+    // the cache and compression layers are the innermost middleware, so that they see the
+    // encoded response
+    if let Some(cache) = cache {
+        middlewares.insert(
+            0,
+            Middleware {
+                expr: parse_quote! {
+                    #server_fn_path::middleware::cache::CacheLayer::new(#cache)
+                },
+            },
+        );
+    }
+    // compression is inside the cache layer, so that the `ETag` is that of the body that is sent
+    if output_ident.as_deref() == Some("Compressed") {
+        middlewares.insert(
+            0,
+            Middleware {
+                expr: parse_quote! {
+                    #server_fn_path::middleware::compression::CompressionLayer
+                },
+            },
+        );
+    }
+    if csrf.is_some_and(|csrf| csrf.value) {
+        middlewares.push(Middleware {
+            expr: parse_quote! {
+                #server_fn_path::middleware::csrf::CsrfLayer
+            },
+        });
+    }
+    // the limits are the outermost middleware, so that rejected calls do no other work, and
+    // are kept in statics, so that their state is shared by every call
+    let mut limits = Vec::new();
+    if let Some(RateLimit { requests, per_secs }) = rate_limit {
+        let key = rate_limit_key.map(|key| quote! { .key_by(#key) });
+        limits.push(quote! {
+            #server_fn_path::middleware::limit::RateLimitLayer::new(
+                #requests,
+                std::time::Duration::from_secs(#per_secs),
+            )
+            #key
+        });
+    } else if let Some(key) = rate_limit_key {
+        return Err(syn::Error::new_spanned(
+            key,
+            "`rate_limit_key` requires a `rate_limit`",
+        ));
+    }
+    if let Some(max) = concurrency_limit {
+        limits.push(quote! {
+            #server_fn_path::middleware::limit::ConcurrencyLimitLayer::new(#max)
+        });
+    }
     let middlewares = if cfg!(feature = "ssr") {
         quote! {
             vec![
                 #(
                     std::sync::Arc::new(#middlewares),
-                ),*
+                )*
+                #(
+                    {
+                        static LIMIT: std::sync::OnceLock<
+                            std::sync::Arc<dyn #server_fn_path::middleware::Layer<#req, #res>>
+                        > = std::sync::OnceLock::new();
+                        std::sync::Arc::clone(
+                            LIMIT.get_or_init(|| std::sync::Arc::new(#limits)),
+                        )
+                    },
+                )*
             ]
         }
     } else {
         quote! { vec![] }
     };
 
+    let client_middlewares_impl = (!client_middlewares.is_empty()).then(|| {
+        quote! {
+            fn client_middlewares() -> #server_fn_path::middleware::client::ClientMiddlewareSet<
+                <Self::Client as #server_fn_path::client::Client<Self::Error>>::Request,
+                <Self::Client as #server_fn_path::client::Client<Self::Error>>::Response,
+            > {
+                vec![
+                    #(
+                        std::sync::Arc::new(#client_middlewares),
+                    ),*
+                ]
+            }
+        }
+    });
+
+    // websockets replace the usual request/response cycle on both sides
+    let websocket_impl = websocket.then(|| {
+        quote! {
+            fn run_on_server(
+                req: Self::ServerRequest,
+            ) -> impl std::future::Future<Output = Self::ServerResponse> + Send {
+                #server_fn_path::codec::run_websocket_server::<Self, _, _, _, _>(req)
+            }
+
+            fn run_on_client(
+                self,
+            ) -> impl std::future::Future<
+                Output = Result<Self::Output, #server_fn_path::ServerFnError<Self::Error>>,
+            > + Send {
+                #server_fn_path::codec::run_websocket_client::<Self, _, _, _, _>(self)
+            }
+        }
+    });
+
+    // server-sent events are resumed on the server, and received through an `EventSource`
+    // in the browser
+    let event_stream_impl = (output_ident.as_deref() == Some("EventStream"))
+        .then(|| {
+            quote! {
+                fn execute_on_server(
+                    req: Self::ServerRequest,
+                ) -> impl std::future::Future<
+                    Output = Result<Self::ServerResponse, #server_fn_path::ServerFnError<Self::Error>>,
+                > + Send {
+                    #server_fn_path::codec::execute_event_stream_server::<Self, _>(req)
+                }
+
+                fn run_on_client(
+                    self,
+                ) -> impl std::future::Future<
+                    Output = Result<Self::Output, #server_fn_path::ServerFnError<Self::Error>>,
+                > + Send {
+                    #server_fn_path::codec::run_event_stream_client::<Self, _>(self)
+                }
+            }
+        });
+
+    // structured errors are serialized with the output encoding, instead of as a string
+    let structured_errors_impl = structured_errors
+        .map(|v| v.value)
+        .unwrap_or(false)
+        .then(|| {
+            quote! {
+                fn error_response(
+                    err: &#server_fn_path::ServerFnError<Self::Error>,
+                ) -> Self::ServerResponse {
+                    #server_fn_path::error::structured_error_response::<
+                        Self::OutputEncoding,
+                        _,
+                        _,
+                    >(Self::PATH, err)
+                }
+
+                fn decode_error<Response>(
+                    res: Response,
+                ) -> impl std::future::Future<
+                    Output = #server_fn_path::ServerFnError<Self::Error>,
+                > + Send
+                where
+                    Response: #server_fn_path::response::ClientRes<Self::Error> + Send,
+                {
+                    #server_fn_path::error::decode_structured_error::<
+                        Self::OutputEncoding,
+                        _,
+                        _,
+                    >(res)
+                }
+            }
+        });
+
+    // per-function overrides of the client's default call options
+    let call_options_impl = (timeout.is_some() || retries.is_some()).then(|| {
+        let timeout = timeout.map(|ms| {
+            quote! {
+                let options = options.timeout(std::time::Duration::from_millis(#ms));
+            }
+        });
+        let retries = retries.map(|retries| {
+            quote! {
+                let options = options.retries(#retries);
+            }
+        });
+        quote! {
+            fn call_options() -> #server_fn_path::client::CallOptions {
+                let options = #server_fn_path::client::get_call_options();
+                #timeout
+                #retries
+                options
+            }
+        }
+    });
+
+    // description of the server function for the OpenAPI document
+    let openapi_inventory = (openapi && !websocket).then(|| {
+        let description = body
+            .docs
+            .iter()
+            .map(|(doc, _)| doc.strip_prefix(' ').unwrap_or(doc))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let with_input = serde_args.then(|| {
+            quote! { .with_input::<#struct_name>() }
+        });
+        // only outputs that are encoded with serde can be described by a JSON schema
+        let serde_output = !matches!(
+            output_ident.as_deref(),
+            Some("Streaming")
+                | Some("StreamingText")
+                | Some("EventStream")
+                | Some("Rkyv")
+                | Some("Bitcode")
+                | Some("Protobuf")
+        );
+        let with_output = serde_output.then(|| {
+            quote! { .with_output::<#output_ty>() }
+        });
+        quote! {
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::{ServerFn, codec::Encoding};
+                #server_fn_path::openapi::ServerFnSchema::new(
+                    #fn_name_as_str,
+                    #wrapped_struct_name_turbofish::PATH,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::CONTENT_TYPE,
+                    <#wrapped_struct_name as ServerFn>::OutputEncoding::CONTENT_TYPE,
+                    #description,
+                )
+                #with_input
+                #with_output
+            }}
+        }
+    });
+
+    // `prost::Message` already implements `Debug`
+    let debug_derive =
+        (input_ident.as_deref() != Some("Protobuf")).then(|| quote! { Debug, });
+
     Ok(quote::quote! {
         #args_docs
         #docs
-        #[derive(Debug, #derives)]
+        #[derive(#debug_derive #derives)]
         #addl_path
+        #schema_derive
         pub struct #struct_name {
             #(#fields),*
         }
@@ -642,6 +996,9 @@ pub fn server_macro_impl(
         impl #server_fn_path::ServerFn for #wrapped_struct_name {
             const PATH: &'static str = #path;
 
+            #version_const
+            #cache_const
+
             type Client = #client;
             type ServerRequest = #req;
             type ServerResponse = #res;
@@ -654,11 +1011,23 @@ pub fn server_macro_impl(
                 #middlewares
             }
 
+            #client_middlewares_impl
+
             #run_body
+
+            #websocket_impl
+
+            #event_stream_impl
+
+            #structured_errors_impl
+
+            #call_options_impl
         }
 
         #inventory
 
+        #openapi_inventory
+
         #func
 
         #dummy
@@ -701,6 +1070,41 @@ impl Parse for Middleware {
     }
 }
 
+/// A rate limit like `"10/minute"`, given to the `rate_limit` argument.
+#[derive(Debug)]
+struct RateLimit {
+    requests: u32,
+    per_secs: u64,
+}
+
+impl Parse for RateLimit {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        let value = lit.value();
+        let invalid = || {
+            syn::Error::new(
+                lit.span(),
+                "expected a rate limit like \"10/second\", \"10/minute\", \
+                 or \"10/hour\"",
+            )
+        };
+        let (requests, period) = value.split_once('/').ok_or_else(invalid)?;
+        let requests = requests
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|requests| *requests > 0)
+            .ok_or_else(invalid)?;
+        let per_secs = match period.trim() {
+            "s" | "sec" | "second" => 1,
+            "m" | "min" | "minute" => 60,
+            "h" | "hour" => 60 * 60,
+            _ => return Err(invalid()),
+        };
+        Ok(RateLimit { requests, per_secs })
+    }
+}
+
 fn output_type(return_ty: &Type) -> Result<&GenericArgument> {
     if let syn::Type::Path(pat) = &return_ty {
         if pat.path.segments[0].ident == "Result" {
@@ -777,6 +1181,15 @@ struct ServerFnArgs {
     custom_wrapper: Option<Path>,
     builtin_encoding: bool,
     impl_from: Option<LitBool>,
+    structured_errors: Option<LitBool>,
+    timeout: Option<LitInt>,
+    retries: Option<LitInt>,
+    cache: Option<LitStr>,
+    rate_limit: Option<RateLimit>,
+    rate_limit_key: Option<syn::Expr>,
+    concurrency_limit: Option<LitInt>,
+    csrf: Option<LitBool>,
+    version: Option<LitInt>,
 }
 
 impl Parse for ServerFnArgs {
@@ -796,6 +1209,15 @@ impl Parse for ServerFnArgs {
         let mut client: Option<Type> = None;
         let mut custom_wrapper: Option<Path> = None;
         let mut impl_from: Option<LitBool> = None;
+        let mut structured_errors: Option<LitBool> = None;
+        let mut timeout: Option<LitInt> = None;
+        let mut retries: Option<LitInt> = None;
+        let mut cache: Option<LitStr> = None;
+        let mut rate_limit: Option<RateLimit> = None;
+        let mut rate_limit_key: Option<syn::Expr> = None;
+        let mut concurrency_limit: Option<LitInt> = None;
+        let mut csrf: Option<LitBool> = None;
+        let mut version: Option<LitInt> = None;
 
         let mut use_key_and_value = false;
         let mut arg_pos = 0;
@@ -919,6 +1341,80 @@ impl Parse for ServerFnArgs {
                             ));
                         }
                         impl_from = Some(stream.parse()?);
+                    } else if key == "structured_errors" {
+                        if structured_errors.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: \
+                                 `structured_errors`",
+                            ));
+                        }
+                        structured_errors = Some(stream.parse()?);
+                    } else if key == "timeout" {
+                        if timeout.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `timeout`",
+                            ));
+                        }
+                        timeout = Some(stream.parse()?);
+                    } else if key == "retries" {
+                        if retries.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `retries`",
+                            ));
+                        }
+                        retries = Some(stream.parse()?);
+                    } else if key == "cache" {
+                        if cache.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `cache`",
+                            ));
+                        }
+                        cache = Some(stream.parse()?);
+                    } else if key == "rate_limit" {
+                        if rate_limit.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `rate_limit`",
+                            ));
+                        }
+                        rate_limit = Some(stream.parse()?);
+                    } else if key == "rate_limit_key" {
+                        if rate_limit_key.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `rate_limit_key`",
+                            ));
+                        }
+                        rate_limit_key = Some(stream.parse()?);
+                    } else if key == "concurrency_limit" {
+                        if concurrency_limit.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: \
+                                 `concurrency_limit`",
+                            ));
+                        }
+                        concurrency_limit = Some(stream.parse()?);
+                    } else if key == "csrf" {
+                        if csrf.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `csrf`",
+                            ));
+                        }
+                        csrf = Some(stream.parse()?);
+                    } else if key == "version" {
+                        if version.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `version`",
+                            ));
+                        }
+                        version = Some(stream.parse()?);
                     } else {
                         return Err(lookahead.error());
                     }
@@ -1016,6 +1512,15 @@ impl Parse for ServerFnArgs {
             client,
             custom_wrapper,
             impl_from,
+            structured_errors,
+            timeout,
+            retries,
+            cache,
+            rate_limit,
+            rate_limit_key,
+            concurrency_limit,
+            csrf,
+            version,
         })
     }
 }